@@ -8,6 +8,8 @@ use axum::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::infrastructure::{telemetry, token_store::TokenStoreError};
+
 pub const API_DOCUMENT_URL: &str =
     "https://github.com/sheroz/axum-rest-api-sample/blob/main/docs/api-docs.md";
 
@@ -126,6 +128,27 @@ pub enum APIErrorCode {
     TransferDestinationAccountNotFound,
     TransferAccountsAreSame,
     ResourceNotFound,
+    NoteArchiveInvalid,
+    NoteImportNotFound,
+    NoteEncrypted,
+    NoteNotEncrypted,
+    NoteEncryptionInvalid,
+    NoteKeyNotFound,
+    NoteIdMismatch,
+    UserKeyNotFound,
+    ReminderNotFound,
+    ReminderInvalid,
+    TemplateNotFound,
+    TemplateInvalid,
+    CommentNotFound,
+    CommentInvalid,
+    StatsTimeseriesInvalid,
+    JobNotFound,
+    JobAlreadyRunning,
+    TaskNotFound,
+    WebhookNotFound,
+    WebhookDeliveryNotFound,
+    WebhookInvalid,
     ApiVersionError,
     DatabaseError,
     RedisError,
@@ -146,6 +169,7 @@ impl Display for APIErrorCode {
 pub enum APIErrorKind {
     AuthenticationError,
     ResourceNotFound,
+    ResourceConflict,
     ValidationError,
     DatabaseError,
     RedisError,
@@ -225,9 +249,12 @@ impl APIErrorEntry {
     }
 
     pub fn trace_id(mut self) -> Self {
-        // Generate a new trace id.
-        let mut trace_id = uuid::Uuid::new_v4().to_string();
-        trace_id.retain(|c| c != '-');
+        // The trace id of the request, or a new id outside of a traced request.
+        let trace_id = telemetry::current_trace_id().unwrap_or_else(|| {
+            let mut trace_id = uuid::Uuid::new_v4().to_string();
+            trace_id.retain(|c| c != '-');
+            trace_id
+        });
         self.trace_id = Some(trace_id);
         self
     }
@@ -285,7 +312,7 @@ impl From<redis::RedisError> for APIErrorEntry {
                 .trace_id()
         } else {
             // Build the entry with a trace id to find the exact error in the log when needed.
-            let error_entry = Self::from(StatusCode::INTERNAL_SERVER_ERROR).trace_id();
+            let error_entry = Self::from(redis_error_status(&e)).trace_id();
             let trace_id = error_entry.trace_id.as_deref().unwrap_or("");
             // The error must be logged here. Otherwise, we would lose it.
             tracing::error!("Redis error: {}, trace id: {}", e.to_string(), trace_id);
@@ -336,15 +363,37 @@ impl From<sqlx::Error> for APIError {
     }
 }
 
+// Redis being unreachable or not responding is a temporary outage.
+pub fn redis_error_status(error: &redis::RedisError) -> StatusCode {
+    if error.is_io_error()
+        || error.is_timeout()
+        || error.is_connection_refusal()
+        || error.is_connection_dropped()
+    {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 impl From<redis::RedisError> for APIError {
     fn from(error: redis::RedisError) -> Self {
         Self {
-            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            status: redis_error_status(&error).as_u16(),
             errors: vec![APIErrorEntry::from(error)],
         }
     }
 }
 
+impl From<TokenStoreError> for APIError {
+    fn from(error: TokenStoreError) -> Self {
+        match error {
+            TokenStoreError::Redis(e) => e.into(),
+            TokenStoreError::Database(e) => e.into(),
+        }
+    }
+}
+
 impl IntoResponse for APIError {
     fn into_response(self) -> Response {
         tracing::error!("Error response: {:?}", self);
@@ -52,7 +52,6 @@ where
     T: for<'de> serde::Deserialize<'de> + std::fmt::Debug + ClaimsMethods + Sync + Send,
 {
     // Extract the token from the authorization header.
-    println!("{:?}", parts);
     let TypedHeader(Authorization(bearer)) = parts
         .extract::<TypedHeader<Authorization<Bearer>>>()
         .await
@@ -65,11 +64,15 @@ where
     let state = Arc::from_ref(state);
 
     // Decode the token.
-    let claims = decode_token::<T>(bearer.token(), &state.config)?;
+    let config = state.config.get();
+    let claims = decode_token::<T>(bearer.token(), &config)?;
 
     // Check for revoked tokens if enabled by configuration.
-    if state.config.jwt_enable_revoked_tokens {
+    if config.jwt_enable_revoked_tokens {
         auth::validate_revoked(&claims, &state).await?
     }
+
+    // Attach the authenticated user to the log lines of the request.
+    tracing::Span::current().record("user_id", claims.get_sub());
     Ok(claims)
 }
@@ -1,17 +1,24 @@
+use std::fmt;
+
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use metrics::counter;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::types::Uuid;
 
 use crate::{
-    api::{APIError, APIErrorCode, APIErrorEntry, APIErrorKind, version::APIVersion},
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind, error::redis_error_status,
+        version::APIVersion,
+    },
     application::{
+        constants::METRICS_AUTH_LOGINS_TOTAL,
         repository::user_repo,
         security::{
             auth::{self, AuthError, JwtTokens},
             jwt::{AccessClaims, ClaimsMethods, RefreshClaims},
         },
-        service::token_service,
+        service::{stats_service, token_service},
         state::SharedState,
     },
 };
@@ -21,12 +28,21 @@ pub struct AdminToken {
     username: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct LoginUser {
     username: String,
     password_hash: String,
 }
 
+// The password hash is never logged.
+impl fmt::Debug for LoginUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginUser")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RevokeUser {
     user_id: Uuid,
@@ -42,12 +58,15 @@ pub async fn login_handler(
     if let Ok(user) = user_repo::get_by_username(&login.username, &state).await {
         if user.active && user.password_hash == login.password_hash {
             tracing::trace!("access granted, user: {}", user.id);
-            let tokens = auth::generate_tokens(user, &state.config);
+            counter!(METRICS_AUTH_LOGINS_TOTAL, "result" => "success").increment(1);
+            stats_service::record_login(user.id, &state).await;
+            let tokens = auth::generate_tokens(user, &state.config.get());
             let response = tokens_to_response(tokens);
             return Ok(response);
         }
     }
 
+    counter!(METRICS_AUTH_LOGINS_TOTAL, "result" => "failure").increment(1);
     tracing::error!("access denied: {:#?}", login);
     Err(AuthError::WrongCredentials)?
 }
@@ -61,7 +80,7 @@ pub async fn generate_token(
     if let Ok(user) = user_repo::get_by_username(&admin.username, &state).await {
         if user.is_admin() {
             tracing::trace!("access granted, user: {}", user.id);
-            let tokens = auth::generate_tokens(user, &state.config);
+            let tokens = auth::generate_tokens(user, &state.config.get());
             let response = tokens_to_response(tokens);
             return Ok(response);
         }
@@ -143,13 +162,12 @@ fn tokens_to_response(jwt_tokens: JwtTokens) -> impl IntoResponse {
         "token_type": "Bearer"
     });
 
-    tracing::trace!("JWT: generated response {:#?}", json);
     Json(json)
 }
 
 impl From<AuthError> for APIError {
     fn from(auth_error: AuthError) -> Self {
-        let (status_code, code) = match auth_error {
+        let (status_code, code) = match &auth_error {
             AuthError::WrongCredentials => (
                 StatusCode::UNAUTHORIZED,
                 APIErrorCode::AuthenticationWrongCredentials,
@@ -171,9 +189,7 @@ impl From<AuthError> for APIError {
                 StatusCode::BAD_REQUEST,
                 APIErrorCode::AuthenticationRevokedTokensInactive,
             ),
-            AuthError::RedisError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, APIErrorCode::RedisError)
-            }
+            AuthError::RedisError(e) => (redis_error_status(e), APIErrorCode::RedisError),
             AuthError::SQLxError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 APIErrorCode::DatabaseError,
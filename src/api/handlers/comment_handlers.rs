@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use sqlx::types::Uuid;
+use thiserror::Error;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        handlers::note_handlers::{find_note, subject, validate_note_access},
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::{COMMENT_DEFAULT_PAGE_SIZE, COMMENT_MAX_LENGTH, COMMENT_MAX_PAGE_SIZE},
+        repository::note_comment_repo,
+        security::{
+            auth::AuthError,
+            jwt::{AccessClaims, ClaimsMethods},
+        },
+        service::comment_service,
+        state::SharedState,
+    },
+    domain::models::note_comment::{
+        NoteComment, NoteCommentPage, NoteCommentRequest, NoteCommentThread,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct NoteCommentQuery {
+    page: Option<i64>,
+    per_page: Option<i64>,
+    resolved: Option<bool>,
+}
+
+// Lists a page of the comment threads of the note.
+pub async fn list_note_comments_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id)): Path<(String, Uuid)>,
+    Query(query): Query<NoteCommentQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteCommentPage>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}", note_id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(COMMENT_DEFAULT_PAGE_SIZE)
+        .clamp(1, COMMENT_MAX_PAGE_SIZE);
+    let total = note_comment_repo::count_threads(note_id, query.resolved, &state).await?;
+    let roots = note_comment_repo::list_threads(
+        note_id,
+        query.resolved,
+        per_page,
+        (page - 1).saturating_mul(per_page),
+        &state,
+    )
+    .await?;
+
+    let thread_ids: Vec<Uuid> = roots.iter().map(|c| c.id).collect();
+    let mut replies: HashMap<Uuid, Vec<NoteComment>> = HashMap::new();
+    for reply in note_comment_repo::list_replies(&thread_ids, &state).await? {
+        replies.entry(reply.thread_id).or_default().push(reply);
+    }
+    let threads = roots
+        .into_iter()
+        .map(|comment| NoteCommentThread {
+            replies: replies.remove(&comment.id).unwrap_or_default(),
+            comment,
+        })
+        .collect();
+
+    Ok(Json(NoteCommentPage {
+        threads,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+pub async fn add_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteCommentRequest>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}", note_id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    validate_text(&request.text)?;
+
+    // Replies join the thread of their parent, which must be a comment of the same note.
+    let id = Uuid::new_v4();
+    let thread_id = match request.parent_id {
+        Some(parent_id) => {
+            note_comment_repo::get_by_id(note_id, parent_id, &state)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::RowNotFound => {
+                        invalid_comment(format!("parent comment not found: {}", parent_id))
+                    }
+                    _ => APIError::from(e),
+                })?
+                .thread_id
+        }
+        None => id,
+    };
+
+    let comment = NoteComment {
+        id,
+        note_id,
+        thread_id,
+        parent_id: request.parent_id,
+        user_id: subject(&access_claims)?,
+        mentions: comment_service::resolve_mentions(&request.text, &state).await?,
+        text: request.text,
+        resolved_at: None,
+        resolved_by: None,
+        edited_at: None,
+        created_at: None,
+        updated_at: None,
+    };
+    let comment = note_comment_repo::add(comment, &state).await?;
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+pub async fn get_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id, id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteComment>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}, id: {}", note_id, id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let comment = find_comment(note_id, id, &state).await?;
+    Ok(Json(comment))
+}
+
+// Edits the text of the comment, only the author can edit a comment.
+pub async fn update_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id, id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteCommentRequest>,
+) -> Result<Json<NoteComment>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}, id: {}", note_id, id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let comment = find_comment(note_id, id, &state).await?;
+    if comment.user_id != subject(&access_claims)? {
+        Err(AuthError::Forbidden)?
+    }
+    validate_text(&request.text)?;
+
+    let mentions = comment_service::resolve_mentions(&request.text, &state).await?;
+    let comment = note_comment_repo::update_text(id, &request.text, &mentions, &state).await?;
+    Ok(Json(comment))
+}
+
+// Deletes the comment with its replies. The author, the note owner and admins can delete a comment.
+pub async fn delete_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id, id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}, id: {}", note_id, id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let comment = find_comment(note_id, id, &state).await?;
+    let sub = subject(&access_claims)?;
+    if comment.user_id != sub && note.user_id != sub {
+        access_claims.validate_role_admin()?;
+    }
+
+    if note_comment_repo::delete(id, &state).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StatusCode::NOT_FOUND)?
+    }
+}
+
+pub async fn resolve_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id, id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteComment>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}, id: {}", note_id, id);
+    let comment = find_thread(note_id, id, &access_claims, &state).await?;
+    let resolved_by = subject(&access_claims)?;
+    let comment = note_comment_repo::set_resolved(comment.id, Some(resolved_by), &state).await?;
+    Ok(Json(comment))
+}
+
+pub async fn unresolve_note_comment_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id, id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteComment>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}, id: {}", note_id, id);
+    let comment = find_thread(note_id, id, &access_claims, &state).await?;
+    let comment = note_comment_repo::set_resolved(comment.id, None, &state).await?;
+    Ok(Json(comment))
+}
+
+async fn find_comment(
+    note_id: Uuid,
+    id: Uuid,
+    state: &SharedState,
+) -> Result<NoteComment, APIError> {
+    note_comment_repo::get_by_id(note_id, id, state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let comment_error = CommentError::CommentNotFound(note_id, id);
+                (
+                    comment_error.status_code(),
+                    APIErrorEntry::from(comment_error),
+                )
+                    .into()
+            }
+            _ => APIError::from(e),
+        })
+}
+
+// Finds the root comment of a thread, anyone who can read the note can resolve its threads.
+async fn find_thread(
+    note_id: Uuid,
+    id: Uuid,
+    access_claims: &AccessClaims,
+    state: &SharedState,
+) -> Result<NoteComment, APIError> {
+    let note = find_note(note_id, state).await?;
+    validate_note_access(&note, access_claims, state).await?;
+    let comment = find_comment(note_id, id, state).await?;
+    if !comment.is_root() {
+        return Err(invalid_comment(
+            "only the root comment of a thread can be resolved".to_owned(),
+        ));
+    }
+    Ok(comment)
+}
+
+fn validate_text(text: &str) -> Result<(), APIError> {
+    if text.trim().is_empty() {
+        return Err(invalid_comment("the comment must not be empty".to_owned()));
+    }
+    if text.chars().count() > COMMENT_MAX_LENGTH {
+        return Err(invalid_comment(format!(
+            "the comment must not be longer than {} characters",
+            COMMENT_MAX_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn invalid_comment(reason: String) -> APIError {
+    let comment_error = CommentError::InvalidComment(reason);
+    (
+        comment_error.status_code(),
+        APIErrorEntry::from(comment_error),
+    )
+        .into()
+}
+
+#[derive(Debug, Error)]
+enum CommentError {
+    #[error("comment not found: {1}, note: {0}")]
+    CommentNotFound(Uuid, Uuid),
+    #[error("invalid comment: {0}")]
+    InvalidComment(String),
+}
+
+impl CommentError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::CommentNotFound(_, _) => StatusCode::NOT_FOUND,
+            Self::InvalidComment(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl From<CommentError> for APIErrorEntry {
+    fn from(comment_error: CommentError) -> Self {
+        let message = comment_error.to_string();
+        match comment_error {
+            CommentError::CommentNotFound(note_id, comment_id) => Self::new(&message)
+                .code(APIErrorCode::CommentNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("comment with the ID '{}' does not exist on the note '{}'", comment_id, note_id))
+                .detail(serde_json::json!({"note_id": note_id, "comment_id": comment_id}))
+                .reason("must be an existing comment of the note")
+                .instance(&format!("/api/v1/notes/{}/comments/{}", note_id, comment_id))
+                .trace_id()
+                .help(&format!("please check if the note and comment IDs are correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            CommentError::InvalidComment(reason) => Self::new(&message)
+                .code(APIErrorCode::CommentInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the comment is not valid")
+                .reason(&reason)
+                .instance("/api/v1/notes/comments")
+                .trace_id()
+                .help(&format!("please check the comment text and its parent or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use thiserror::Error;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::{JOB_RUNS_DEFAULT_LIMIT, JOB_RUNS_MAX_LIMIT},
+        repository::job_repo,
+        security::jwt::{AccessClaims, ClaimsMethods},
+        service::job_service,
+        state::SharedState,
+    },
+    domain::models::job::{JobName, JobResponse, JobRun, JobRunsQuery, JobTrigger},
+};
+
+// The jobs with their schedules and last runs, admins only.
+pub async fn list_jobs_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<JobResponse>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+
+    let jobs = job_service::list(&state).await?;
+    Ok(Json(jobs))
+}
+
+// The last runs of a job, the most recent first, admins only.
+pub async fn list_job_runs_handler(
+    access_claims: AccessClaims,
+    Path((version, name)): Path<(String, String)>,
+    Query(query): Query<JobRunsQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<JobRun>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+    let job = find_job(&name)?;
+
+    let limit = query
+        .limit
+        .unwrap_or(JOB_RUNS_DEFAULT_LIMIT)
+        .clamp(1, JOB_RUNS_MAX_LIMIT);
+    let runs = job_repo::list_by_job(job, limit, &state).await?;
+    Ok(Json(runs))
+}
+
+// Starts a run of a job in the background, admins only.
+// The run is accepted when the job is not running on any instance.
+pub async fn run_job_handler(
+    access_claims: AccessClaims,
+    Path((version, name)): Path<(String, String)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+    let job = find_job(&name)?;
+
+    let Some((run, lock)) = job_service::claim(job, JobTrigger::Manual, None, &state).await? else {
+        let job_error = JobError::AlreadyRunning(job);
+        return Err((job_error.status_code(), APIErrorEntry::from(job_error)).into());
+    };
+    let response = run.clone();
+    tokio::spawn(async move {
+        job_service::execute(run, lock, &state).await;
+    });
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+fn find_job(name: &str) -> Result<JobName, APIError> {
+    JobName::from_name(name).ok_or_else(|| {
+        let job_error = JobError::JobNotFound(name.to_owned());
+        (job_error.status_code(), APIErrorEntry::from(job_error)).into()
+    })
+}
+
+#[derive(Debug, Error)]
+enum JobError {
+    #[error("job not found: {0}")]
+    JobNotFound(String),
+    #[error("job already running: {}", .0.as_str())]
+    AlreadyRunning(JobName),
+}
+
+impl JobError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::JobNotFound(_) => StatusCode::NOT_FOUND,
+            Self::AlreadyRunning(_) => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl From<JobError> for APIErrorEntry {
+    fn from(job_error: JobError) -> Self {
+        let message = job_error.to_string();
+        match job_error {
+            JobError::JobNotFound(name) => Self::new(&message)
+                .code(APIErrorCode::JobNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("the job '{}' does not exist", name))
+                .detail(serde_json::json!({"job": name}))
+                .reason("must be `token_cleanup`, `purge` or `stats_rollup`")
+                .instance(&format!("/api/v1/jobs/{}", name))
+                .trace_id()
+                .help(&format!("please check the job name or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            JobError::AlreadyRunning(job) => Self::new(&message)
+                .code(APIErrorCode::JobAlreadyRunning)
+                .kind(APIErrorKind::ResourceConflict)
+                .description(&format!("the job '{}' is running on an instance", job.as_str()))
+                .detail(serde_json::json!({"job": job}))
+                .reason("must not be running")
+                .instance(&format!("/api/v1/jobs/{}/run", job.as_str()))
+                .trace_id()
+                .help(&format!("please retry when the running job has finished or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
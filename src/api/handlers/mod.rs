@@ -1,4 +1,10 @@
 pub mod auth_handlers;
+pub mod comment_handlers;
+pub mod job_handlers;
 pub mod note_handlers;
+pub mod reminder_handlers;
 pub mod stats_handlers;
+pub mod task_handlers;
+pub mod template_handlers;
 pub mod user_handlers;
+pub mod webhook_handlers;
@@ -1,12 +1,17 @@
+use std::sync::Arc;
+
 use axum::{
     Json,
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Response},
 };
 use chrono::Utc;
+use serde::Deserialize;
 use sqlx::types::Uuid;
 use thiserror::Error;
+use tokio::sync::mpsc;
 
 use crate::{
     api::{
@@ -15,60 +20,110 @@ use crate::{
         version::{self, APIVersion},
     },
     application::{
-        repository::note_repo,
-        security::jwt::{AccessClaims, ClaimsMethods},
+        constants::NOTE_IMPORT_BACKGROUND_THRESHOLD,
+        repository::{
+            note_flags_repo, note_import_repo, note_key_repo, note_link_repo, note_repo,
+            user_key_repo,
+        },
+        security::{
+            auth::AuthError,
+            jwt::{AccessClaims, ClaimsMethods},
+        },
+        service::{archive_service, encryption_service, render_service},
         state::SharedState,
     },
     domain::models::note::Note,
+    domain::models::note_flags::{NoteFilter, NoteFlags, NoteFlagsRequest, ViewedNote},
+    domain::models::note_import::{NoteArchiveFormat, NoteImport},
+    domain::models::note_key::{NoteKey, NoteKeyRequest},
+    domain::models::note_link::{NoteGraph, NoteLink},
     domain::models::user::SimpleUser,
 };
 
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteFormat {
+    Json,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoteFormatQuery {
+    format: Option<NoteFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoteArchiveQuery {
+    format: Option<NoteArchiveFormat>,
+    user_id: Option<Uuid>,
+}
+
 pub async fn list_notes_handler(
     api_version: APIVersion,
     access_claims: AccessClaims,
+    Query(filter): Query<NoteFilter>,
     State(state): State<SharedState>,
-) -> Result<Json<Vec<Note>>, APIError> {
+) -> Result<Json<Vec<ViewedNote>>, APIError> {
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
     access_claims.validate_role_admin()?;
-    let notes = note_repo::list(&state).await?;
+    let notes = note_repo::list(subject(&access_claims)?, &filter, &state).await?;
     Ok(Json(notes))
 }
 
 pub async fn list_notes_by_user_handler(
     api_version: APIVersion,
     access_claims: AccessClaims,
+    Query(filter): Query<NoteFilter>,
     State(state): State<SharedState>,
     Json(user): Json<SimpleUser>,
-) -> Result<Json<Vec<Note>>, APIError> {
+) -> Result<Json<Vec<ViewedNote>>, APIError> {
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
-    access_claims.validate_role_admin()?;
-    let notes = note_repo::list_by_user(user.id, &state).await?;
+    let user_id = resolve_user_id(&access_claims, Some(user.id))?;
+    let viewer_id = subject(&access_claims)?;
+    let notes = note_repo::list_by_user(user_id, viewer_id, &filter, &state).await?;
     Ok(Json(notes))
 }
 
 pub async fn get_note_handler(
     access_claims: AccessClaims,
     Path((version, id)): Path<(String, Uuid)>,
+    Query(query): Query<NoteFormatQuery>,
+    headers: HeaderMap,
     State(state): State<SharedState>,
-) -> Result<Json<Note>, APIError> {
+) -> Result<Response, APIError> {
     let api_version: APIVersion = version::parse_version(&version)?;
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
     tracing::trace!("id: {}", id);
-    access_claims.validate_role_admin()?;
-    let user = note_repo::get_by_id(id, &state)
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => {
-                let user_error = NoteError::NoteNotFound(id);
-                (user_error.status_code(), APIErrorEntry::from(user_error)).into()
-            }
-            _ => APIError::from(e),
-        })?;
+    let note = find_note(id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
 
-    Ok(Json(user))
+    // Content negotiation: an explicit `format` query parameter takes precedence over `Accept`.
+    let accepts_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/html"));
+
+    let render = query
+        .format
+        .map_or(accepts_html, |format| format == NoteFormat::Html);
+    if !render {
+        return Ok(Json(note).into_response());
+    }
+    if note.is_encrypted() {
+        // The server cannot read the text of an encrypted note.
+        let note_error = NoteError::NoteEncrypted(id);
+        return Err((note_error.status_code(), APIErrorEntry::from(note_error)).into());
+    }
+
+    let rendered = render_service::render_note(&note, &state).await;
+    if accepts_html {
+        Ok(Html(rendered.html).into_response())
+    } else {
+        Ok(Json(rendered).into_response())
+    }
 }
 
 pub async fn add_note_handler(
@@ -80,6 +135,7 @@ pub async fn add_note_handler(
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
     access_claims.validate_role_admin()?;
+    validate_encryption(&note)?;
     let naive_now = Utc::now().naive_utc();
     note.created_at = Some(naive_now);
     note.updated_at = Some(naive_now);
@@ -97,6 +153,15 @@ pub async fn update_note_handler(
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
     tracing::trace!("id: {}", id);
+    if note.id != id {
+        let note_error = NoteError::NoteIdMismatch(id, note.id);
+        return Err((note_error.status_code(), APIErrorEntry::from(note_error)).into());
+    }
+    // Users update their own notes, admins can update any and move a note to another user.
+    let stored = find_note(id, &state).await?;
+    resolve_user_id(&access_claims, Some(stored.user_id))?;
+    resolve_user_id(&access_claims, Some(note.user_id))?;
+    validate_encryption(&note)?;
     let note = note_repo::update(note, &state).await?;
     Ok(Json(note))
 }
@@ -118,16 +183,379 @@ pub async fn delete_note_handler(
     }
 }
 
+// Gets the flags of the note set by the user.
+pub async fn get_note_flags_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteFlags>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note = find_note(id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let note_flags = note_flags_repo::get(id, subject(&access_claims)?, &state).await?;
+    Ok(Json(note_flags))
+}
+
+// Sets the flags of the note for the user, every viewer of a note has its own flags.
+pub async fn update_note_flags_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteFlagsRequest>,
+) -> Result<Json<NoteFlags>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note = find_note(id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let note_flags =
+        note_flags_repo::upsert(id, subject(&access_claims)?, &request, &state).await?;
+    Ok(Json(note_flags))
+}
+
+pub async fn list_note_links_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<NoteLink>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note = find_note(id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let links = note_link_repo::list_by_source(id, &state).await?;
+    Ok(Json(links))
+}
+
+pub async fn list_note_backlinks_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Note>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note = find_note(id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    let notes = note_link_repo::list_backlinks(id, &state).await?;
+    Ok(Json(notes))
+}
+
+pub async fn note_graph_handler(
+    access_claims: AccessClaims,
+    Path((version, user_id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteGraph>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("user_id: {}", user_id);
+    if subject(&access_claims)? != user_id {
+        // Only admin can export the graph of other users.
+        access_claims.validate_role_admin()?;
+    }
+    let graph = note_link_repo::graph_by_user(user_id, &state).await?;
+    Ok(Json(graph))
+}
+
+pub async fn export_notes_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    Query(query): Query<NoteArchiveQuery>,
+    State(state): State<SharedState>,
+) -> Result<Response, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    let user_id = resolve_user_id(&access_claims, query.user_id)?;
+    let format = query.format.unwrap_or_default();
+    tracing::trace!("exporting notes, user: {}, format: {:?}", user_id, format);
+
+    // Notes are streamed from the database as they are encoded.
+    let (sender, receiver) = mpsc::channel(16);
+    tokio::spawn(archive_service::export(
+        user_id,
+        format,
+        Arc::clone(&state),
+        sender,
+    ));
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", format.file_name()),
+        ),
+    ];
+    Ok((headers, Body::from_stream(stream)).into_response())
+}
+
+pub async fn import_notes_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    Query(query): Query<NoteArchiveQuery>,
+    headers: HeaderMap,
+    State(state): State<SharedState>,
+    body: Bytes,
+) -> Result<impl IntoResponse, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    let user_id = resolve_user_id(&access_claims, query.user_id)?;
+
+    // The format can be given explicitly or by the content type.
+    let format = query.format.unwrap_or_else(|| {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if content_type.starts_with(NoteArchiveFormat::Tar.content_type()) {
+            NoteArchiveFormat::Tar
+        } else {
+            NoteArchiveFormat::Ndjson
+        }
+    });
+
+    let notes = archive_service::parse(format, &body).map_err(|e| {
+        let note_error = NoteError::InvalidArchive(e.to_string());
+        APIError::from((note_error.status_code(), APIErrorEntry::from(note_error)))
+    })?;
+
+    // Large imports are run by the task workers, the client polls the import record.
+    if notes.len() > NOTE_IMPORT_BACKGROUND_THRESHOLD {
+        let note_import = archive_service::enqueue_import(user_id, notes, &state).await?;
+        return Ok((StatusCode::ACCEPTED, Json(note_import)));
+    }
+
+    let note_import = note_import_repo::add(user_id, notes.len(), &state).await?;
+    let note_import = archive_service::import(note_import, notes, state).await?;
+    Ok((StatusCode::OK, Json(note_import)))
+}
+
+pub async fn get_note_import_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteImport>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note_import = note_import_repo::get_by_id(id, &state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let note_error = NoteError::NoteImportNotFound(id);
+                (note_error.status_code(), APIErrorEntry::from(note_error)).into()
+            }
+            _ => APIError::from(e),
+        })?;
+    resolve_user_id(&access_claims, Some(note_import.user_id))?;
+    Ok(Json(note_import))
+}
+
+pub async fn list_note_keys_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<NoteKey>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let note = find_note(id, &state).await?;
+    resolve_user_id(&access_claims, Some(note.user_id))?;
+    let note_keys = note_key_repo::list_by_note(id, &state).await?;
+    Ok(Json(note_keys))
+}
+
+pub async fn get_note_key_handler(
+    access_claims: AccessClaims,
+    Path((version, id, user_id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteKey>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}, user_id: {}", id, user_id);
+    let note = find_note(id, &state).await?;
+    if resolve_user_id(&access_claims, Some(user_id)).is_err() {
+        // The owner of the note can see the keys of all recipients.
+        resolve_user_id(&access_claims, Some(note.user_id))?;
+    }
+    let note_key = note_key_repo::get(id, user_id, &state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let note_error = NoteError::NoteKeyNotFound(id, user_id);
+                (note_error.status_code(), APIErrorEntry::from(note_error)).into()
+            }
+            _ => APIError::from(e),
+        })?;
+    Ok(Json(note_key))
+}
+
+pub async fn put_note_key_handler(
+    access_claims: AccessClaims,
+    Path((version, id, user_id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteKeyRequest>,
+) -> Result<Json<NoteKey>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}, user_id: {}", id, user_id);
+    let note = find_note(id, &state).await?;
+    if !note.is_encrypted() {
+        let note_error = NoteError::NoteNotEncrypted(id);
+        return Err((note_error.status_code(), APIErrorEntry::from(note_error)).into());
+    }
+
+    // Sharing re-wraps the data key, so only the users holding it can grant it:
+    // the owner and the existing recipients.
+    let sub = subject(&access_claims)?;
+    if sub != note.user_id && !note_key_repo::exists(id, sub, &state).await? {
+        Err(AuthError::Forbidden)?
+    }
+    encryption_service::validate_key(&request.wrapped_key, &request.algorithm)
+        .map_err(invalid_encryption)?;
+    if !user_key_repo::exists(user_id, &state).await? {
+        let note_error = NoteError::UserKeyNotFound(user_id);
+        return Err((note_error.status_code(), APIErrorEntry::from(note_error)).into());
+    }
+
+    let note_key = NoteKey {
+        note_id: id,
+        user_id,
+        wrapped_key: request.wrapped_key,
+        algorithm: request.algorithm,
+        wrapped_by: sub,
+        created_at: None,
+        updated_at: None,
+    };
+    let note_key = note_key_repo::upsert(note_key, &state).await?;
+    Ok(Json(note_key))
+}
+
+pub async fn delete_note_key_handler(
+    access_claims: AccessClaims,
+    Path((version, id, user_id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}, user_id: {}", id, user_id);
+    let note = find_note(id, &state).await?;
+    if resolve_user_id(&access_claims, Some(user_id)).is_err() {
+        // Recipients can leave a share, the owner can revoke any.
+        resolve_user_id(&access_claims, Some(note.user_id))?;
+    }
+    if note_key_repo::delete(id, user_id, &state).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StatusCode::NOT_FOUND)?
+    }
+}
+
+pub(crate) async fn find_note(id: Uuid, state: &SharedState) -> Result<Note, APIError> {
+    note_repo::get_by_id(id, state).await.map_err(|e| match e {
+        sqlx::Error::RowNotFound => {
+            let note_error = NoteError::NoteNotFound(id);
+            (note_error.status_code(), APIErrorEntry::from(note_error)).into()
+        }
+        _ => APIError::from(e),
+    })
+}
+
+// Admins can read any note, users their own notes and the encrypted notes shared with them.
+pub(crate) async fn validate_note_access(
+    note: &Note,
+    access_claims: &AccessClaims,
+    state: &SharedState,
+) -> Result<(), APIError> {
+    if access_claims.validate_role_admin().is_ok() {
+        return Ok(());
+    }
+    let sub = subject(access_claims)?;
+    if note.user_id == sub
+        || (note.is_encrypted() && note_key_repo::exists(note.id, sub, state).await?)
+    {
+        Ok(())
+    } else {
+        Err(AuthError::Forbidden)?
+    }
+}
+
+fn validate_encryption(note: &Note) -> Result<(), APIError> {
+    encryption_service::validate_note(note).map_err(invalid_encryption)
+}
+
+fn invalid_encryption(reason: String) -> APIError {
+    let note_error = NoteError::InvalidEncryption(reason);
+    (note_error.status_code(), APIErrorEntry::from(note_error)).into()
+}
+
+pub(crate) fn subject(access_claims: &AccessClaims) -> Result<Uuid, APIError> {
+    Ok(access_claims
+        .sub
+        .parse()
+        .map_err(|_| AuthError::InvalidToken)?)
+}
+
+// Resolves the owner of the notes: users manage their own notes, admins can manage any.
+fn resolve_user_id(access_claims: &AccessClaims, user_id: Option<Uuid>) -> Result<Uuid, APIError> {
+    let sub = subject(access_claims)?;
+    match user_id {
+        Some(user_id) if user_id != sub => {
+            access_claims.validate_role_admin()?;
+            Ok(user_id)
+        }
+        _ => Ok(sub),
+    }
+}
+
 #[derive(Debug, Error)]
 enum NoteError {
     #[error("note not found: {0}")]
     NoteNotFound(Uuid),
+    #[error("invalid note archive: {0}")]
+    InvalidArchive(String),
+    #[error("note import not found: {0}")]
+    NoteImportNotFound(Uuid),
+    #[error("note is encrypted: {0}")]
+    NoteEncrypted(Uuid),
+    #[error("note is not encrypted: {0}")]
+    NoteNotEncrypted(Uuid),
+    #[error("invalid note encryption: {0}")]
+    InvalidEncryption(String),
+    #[error("note key not found: {0}, user: {1}")]
+    NoteKeyNotFound(Uuid, Uuid),
+    #[error("user key not found: {0}")]
+    UserKeyNotFound(Uuid),
+    #[error("note ID mismatch: {1}, expected: {0}")]
+    NoteIdMismatch(Uuid, Uuid),
 }
 
 impl NoteError {
     const fn status_code(&self) -> StatusCode {
         match self {
             Self::NoteNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidArchive(_) => StatusCode::BAD_REQUEST,
+            Self::NoteImportNotFound(_) => StatusCode::NOT_FOUND,
+            Self::NoteEncrypted(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NoteNotEncrypted(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::InvalidEncryption(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NoteKeyNotFound(_, _) => StatusCode::NOT_FOUND,
+            Self::UserKeyNotFound(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NoteIdMismatch(_, _) => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -146,6 +574,84 @@ impl From<NoteError> for APIErrorEntry {
                 .trace_id()
                 .help(&format!("please check if the user ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
                 .doc_url(),
+            NoteError::InvalidArchive(_) => Self::new(&message)
+                .code(APIErrorCode::NoteArchiveInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the uploaded note archive could not be read")
+                .reason("must be a valid NDJSON document or tar archive of Markdown files")
+                .instance("/api/v1/notes/import")
+                .trace_id()
+                .help(&format!("please check the archive format or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::NoteImportNotFound(import_id) => Self::new(&message)
+                .code(APIErrorCode::NoteImportNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("note import with the ID '{}' does not exist in our records", import_id))
+                .detail(serde_json::json!({"import_id": import_id}))
+                .reason("must be an existing note import")
+                .instance(&format!("/api/v1/notes/import/{}", import_id))
+                .trace_id()
+                .help(&format!("please check if the import ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::NoteEncrypted(note_id) => Self::new(&message)
+                .code(APIErrorCode::NoteEncrypted)
+                .kind(APIErrorKind::ValidationError)
+                .description("the note is end-to-end encrypted and cannot be processed by the server")
+                .detail(serde_json::json!({"note_id": note_id}))
+                .reason("must be a plaintext note")
+                .instance(&format!("/api/v1/notes/{}", note_id))
+                .trace_id()
+                .help(&format!("please decrypt and render the note on the client or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::NoteNotEncrypted(note_id) => Self::new(&message)
+                .code(APIErrorCode::NoteNotEncrypted)
+                .kind(APIErrorKind::ValidationError)
+                .description("keys can only be shared for end-to-end encrypted notes")
+                .detail(serde_json::json!({"note_id": note_id}))
+                .reason("must be an encrypted note")
+                .instance(&format!("/api/v1/notes/{}/keys", note_id))
+                .trace_id()
+                .help(&format!("please refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::InvalidEncryption(reason) => Self::new(&message)
+                .code(APIErrorCode::NoteEncryptionInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the encryption metadata of the note is not valid")
+                .reason(&reason)
+                .instance("/api/v1/notes")
+                .trace_id()
+                .help(&format!("please check the ciphertext, nonce and keys or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::NoteKeyNotFound(note_id, user_id) => Self::new(&message)
+                .code(APIErrorCode::NoteKeyNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("the note with the ID '{}' is not shared with the user '{}'", note_id, user_id))
+                .detail(serde_json::json!({"note_id": note_id, "user_id": user_id}))
+                .reason("must be an existing note key")
+                .instance(&format!("/api/v1/notes/{}/keys/{}", note_id, user_id))
+                .trace_id()
+                .help(&format!("please check if the note and user IDs are correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::UserKeyNotFound(user_id) => Self::new(&message)
+                .code(APIErrorCode::UserKeyNotFound)
+                .kind(APIErrorKind::ValidationError)
+                .description(&format!("the user with the ID '{}' has not registered a public key", user_id))
+                .detail(serde_json::json!({"user_id": user_id}))
+                .reason("the recipient must have a registered public key")
+                .instance(&format!("/api/v1/users/{}/key", user_id))
+                .trace_id()
+                .help(&format!("please ask the recipient to register a public key or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            NoteError::NoteIdMismatch(note_id, body_id) => Self::new(&message)
+                .code(APIErrorCode::NoteIdMismatch)
+                .kind(APIErrorKind::ValidationError)
+                .description(&format!("the note ID '{}' of the body does not match the note ID '{}' of the path", body_id, note_id))
+                .detail(serde_json::json!({"note_id": note_id, "body_id": body_id}))
+                .reason("must be the note ID of the path")
+                .instance(&format!("/api/v1/notes/{}", note_id))
+                .trace_id()
+                .help(&format!("please check the note ID or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
         }
     }
 }
@@ -0,0 +1,308 @@
+use std::convert::Infallible;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use chrono::{TimeDelta, Utc};
+use futures::Stream;
+use sqlx::types::Uuid;
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        handlers::note_handlers::{find_note, subject, validate_note_access},
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::REMINDER_DEFAULT_SNOOZE_MINUTES,
+        repository::reminder_repo,
+        security::{
+            jwt::{AccessClaims, ClaimsMethods},
+            outbound,
+        },
+        service::reminder_service,
+        state::SharedState,
+    },
+    domain::models::reminder::{
+        Reminder, ReminderChannel, ReminderRequest, ReminderStatus, SnoozeRequest,
+    },
+};
+
+const REMINDER_EVENT_NAME: &str = "reminder";
+
+pub async fn add_note_reminder_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<ReminderRequest>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}", note_id);
+    let note = find_note(note_id, &state).await?;
+    validate_note_access(&note, &access_claims, &state).await?;
+    validate_request(&request, &state).await?;
+
+    let reminder = Reminder {
+        id: Uuid::new_v4(),
+        note_id,
+        user_id: subject(&access_claims)?,
+        starts_at: request.due_at,
+        due_at: request.due_at,
+        rrule: request.rrule,
+        channel: request.channel,
+        webhook_url: request.webhook_url,
+        status: ReminderStatus::Pending,
+        occurrences: 0,
+        attempts: 0,
+        last_fired_at: None,
+        last_error: None,
+        locked_until: None,
+        created_at: None,
+        updated_at: None,
+    };
+    let reminder = reminder_repo::add(reminder, &state).await?;
+    Ok((StatusCode::CREATED, Json(reminder)))
+}
+
+pub async fn list_note_reminders_handler(
+    access_claims: AccessClaims,
+    Path((version, note_id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Reminder>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("note_id: {}", note_id);
+    let user_id = subject(&access_claims)?;
+    let reminders = reminder_repo::list_by_note(note_id, user_id, &state).await?;
+    Ok(Json(reminders))
+}
+
+pub async fn list_reminders_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Reminder>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    let user_id = subject(&access_claims)?;
+    let reminders = reminder_repo::list_by_user(user_id, &state).await?;
+    Ok(Json(reminders))
+}
+
+pub async fn get_reminder_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Reminder>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let reminder = find_reminder(id, &access_claims, &state).await?;
+    Ok(Json(reminder))
+}
+
+pub async fn snooze_reminder_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<SnoozeRequest>,
+) -> Result<Json<Reminder>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let reminder = find_reminder(id, &access_claims, &state).await?;
+    if reminder.status == ReminderStatus::Dismissed {
+        return Err(invalid_reminder("a dismissed reminder cannot be snoozed"));
+    }
+
+    let now = Utc::now().naive_utc();
+    let due_at = match (request.until, request.minutes) {
+        (Some(until), _) => until,
+        (None, minutes) => {
+            let minutes = minutes.unwrap_or(REMINDER_DEFAULT_SNOOZE_MINUTES);
+            if minutes <= 0 {
+                return Err(invalid_reminder("the snooze minutes must be positive"));
+            }
+            now + TimeDelta::minutes(minutes)
+        }
+    };
+    if due_at <= now {
+        return Err(invalid_reminder(
+            "the reminder must be snoozed to the future",
+        ));
+    }
+
+    let reminder = reminder_repo::snooze(id, due_at, &state).await?;
+    Ok(Json(reminder))
+}
+
+pub async fn dismiss_reminder_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Reminder>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    find_reminder(id, &access_claims, &state).await?;
+    let reminder = reminder_repo::dismiss(id, &state).await?;
+    Ok(Json(reminder))
+}
+
+pub async fn delete_reminder_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    find_reminder(id, &access_claims, &state).await?;
+    if reminder_repo::delete(id, &state).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StatusCode::NOT_FOUND)?
+    }
+}
+
+// Streams the reminders of the user as server-sent events.
+pub async fn reminder_events_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    let user_id = subject(&access_claims)?;
+    let receiver = state.notifiers.subscribe(user_id);
+
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(notification) => {
+                    let event = Event::default()
+                        .event(REMINDER_EVENT_NAME)
+                        .json_data(&notification)
+                        .unwrap_or_default();
+                    return Some((Ok(event), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("reminder event stream lagged, skipped: {}", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+// Finds the reminder, users manage their own reminders, admins can manage any.
+async fn find_reminder(
+    id: Uuid,
+    access_claims: &AccessClaims,
+    state: &SharedState,
+) -> Result<Reminder, APIError> {
+    let reminder = reminder_repo::get_by_id(id, state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let reminder_error = ReminderError::ReminderNotFound(id);
+                (
+                    reminder_error.status_code(),
+                    APIErrorEntry::from(reminder_error),
+                )
+                    .into()
+            }
+            _ => APIError::from(e),
+        })?;
+    if reminder.user_id != subject(access_claims)? {
+        access_claims.validate_role_admin()?;
+    }
+    Ok(reminder)
+}
+
+async fn validate_request(request: &ReminderRequest, state: &SharedState) -> Result<(), APIError> {
+    reminder_service::validate_rrule(request.rrule.as_deref())
+        .map_err(|e| invalid_reminder(&format!("invalid rrule: {}", e)))?;
+    if request.channel == ReminderChannel::Webhook {
+        let Some(webhook_url) = request.webhook_url.as_deref() else {
+            return Err(invalid_reminder(
+                "the webhook channel requires an HTTP(S) webhook URL",
+            ));
+        };
+        let config = state.config.get();
+        outbound::validate_url(webhook_url, &config.webhook_allowed_hosts)
+            .await
+            .map_err(|e| invalid_reminder(&format!("invalid webhook URL: {}", e)))?;
+    }
+    Ok(())
+}
+
+fn invalid_reminder(reason: &str) -> APIError {
+    let reminder_error = ReminderError::InvalidReminder(reason.to_owned());
+    (
+        reminder_error.status_code(),
+        APIErrorEntry::from(reminder_error),
+    )
+        .into()
+}
+
+#[derive(Debug, Error)]
+enum ReminderError {
+    #[error("reminder not found: {0}")]
+    ReminderNotFound(Uuid),
+    #[error("invalid reminder: {0}")]
+    InvalidReminder(String),
+}
+
+impl ReminderError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ReminderNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidReminder(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl From<ReminderError> for APIErrorEntry {
+    fn from(reminder_error: ReminderError) -> Self {
+        let message = reminder_error.to_string();
+        match reminder_error {
+            ReminderError::ReminderNotFound(reminder_id) => Self::new(&message)
+                .code(APIErrorCode::ReminderNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("reminder with the ID '{}' does not exist in our records", reminder_id))
+                .detail(serde_json::json!({"reminder_id": reminder_id}))
+                .reason("must be an existing reminder")
+                .instance(&format!("/api/v1/reminders/{}", reminder_id))
+                .trace_id()
+                .help(&format!("please check if the reminder ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            ReminderError::InvalidReminder(reason) => Self::new(&message)
+                .code(APIErrorCode::ReminderInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the reminder is not valid")
+                .reason(&reason)
+                .instance("/api/v1/reminders")
+                .trace_id()
+                .help(&format!("please check the due time, the recurrence rule and the channel or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
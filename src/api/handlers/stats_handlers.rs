@@ -1,8 +1,7 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
 };
 use sqlx::types::Uuid;
 use thiserror::Error;
@@ -11,31 +10,36 @@ use crate::{
     api::{
         APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
         error::API_DOCUMENT_URL,
+        handlers::note_handlers::subject,
         version::{self, APIVersion},
     },
     application::{
-        repository::stats_repo,
+        constants::STATS_TIMESERIES_MAX_POINTS,
+        repository::{activity_repo, stats_repo},
         security::jwt::{AccessClaims, ClaimsMethods},
         state::SharedState,
     },
-    domain::models::stats::{StatRequest, StatResponse},
+    domain::models::stats::{StatResponse, Timeseries, TimeseriesQuery},
 };
 
+// Users can read their own stats, admins can read the stats of any user.
 pub async fn get_stats_handler(
     access_claims: AccessClaims,
-    Path((version, id)): Path<(String, Uuid)>,
+    Path((version, user_id)): Path<(String, Uuid)>,
     State(state): State<SharedState>,
 ) -> Result<Json<StatResponse>, APIError> {
     let api_version: APIVersion = version::parse_version(&version)?;
     tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
-    tracing::trace!("id: {}", id);
-    access_claims.validate_role_admin()?;
-    let stats = stats_repo::get_by_id(id, &state)
+    tracing::trace!("user_id: {}", user_id);
+    if user_id != subject(&access_claims)? {
+        access_claims.validate_role_admin()?;
+    }
+    let stats = stats_repo::get_by_user_id(user_id, &state)
         .await
         .map_err(|e| match e {
             sqlx::Error::RowNotFound => {
-                let stats_error = StatsError::StatsNotFound(id);
+                let stats_error = StatsError::StatsNotFound(user_id);
                 (stats_error.status_code(), APIErrorEntry::from(stats_error)).into()
             }
             _ => APIError::from(e),
@@ -44,27 +48,61 @@ pub async fn get_stats_handler(
     Ok(Json(stats))
 }
 
-pub async fn update_stats_count(
+// Time series of the activity of a user, or system-wide without a user.
+// Users can read their own series, admins can read any series.
+pub async fn get_timeseries_handler(
+    api_version: APIVersion,
     access_claims: AccessClaims,
+    Query(query): Query<TimeseriesQuery>,
     State(state): State<SharedState>,
-    Json(stat): Json<StatRequest>,
-) -> Result<impl IntoResponse, APIError> {
+) -> Result<Json<Timeseries>, APIError> {
+    tracing::trace!("api version: {}", api_version);
     tracing::trace!("authentication details: {:#?}", access_claims);
-    access_claims.validate_role_admin()?;
-    let stat = stats_repo::update(stat, &state).await?;
-    Ok((StatusCode::CREATED, Json(stat)))
+    tracing::trace!("query: {:?}", query);
+    if query.user_id != Some(subject(&access_claims)?) {
+        access_claims.validate_role_admin()?;
+    }
+    validate_query(&query)?;
+
+    let points = activity_repo::timeseries(&query, &state).await?;
+    Ok(Json(Timeseries {
+        metric: query.metric,
+        bucket: query.bucket,
+        user_id: query.user_id,
+        points,
+    }))
+}
+
+fn validate_query(query: &TimeseriesQuery) -> Result<(), APIError> {
+    let reason = if query.from > query.to {
+        "the start of the range must not be after its end".to_owned()
+    } else if (query.to - query.from).num_days() / query.bucket.days()
+        >= STATS_TIMESERIES_MAX_POINTS
+    {
+        format!(
+            "the range must not be longer than {} buckets",
+            STATS_TIMESERIES_MAX_POINTS
+        )
+    } else {
+        return Ok(());
+    };
+    let stats_error = StatsError::InvalidTimeseries(reason);
+    Err((stats_error.status_code(), APIErrorEntry::from(stats_error)).into())
 }
 
 #[derive(Debug, Error)]
 enum StatsError {
     #[error("stats not found: {0}")]
     StatsNotFound(Uuid),
+    #[error("invalid time series query: {0}")]
+    InvalidTimeseries(String),
 }
 
 impl StatsError {
     const fn status_code(&self) -> StatusCode {
         match self {
             Self::StatsNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidTimeseries(_) => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 }
@@ -83,6 +121,15 @@ impl From<StatsError> for APIErrorEntry {
                 .trace_id()
                 .help(&format!("please check if the user ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
                 .doc_url(),
+            StatsError::InvalidTimeseries(reason) => Self::new(&message)
+                .code(APIErrorCode::StatsTimeseriesInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the time series query is not valid")
+                .reason(&reason)
+                .instance("/api/v1/stats/timeseries")
+                .trace_id()
+                .help(&format!("please check the metric, the date range and the bucket or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
         }
     }
 }
@@ -0,0 +1,91 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use sqlx::types::Uuid;
+use thiserror::Error;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::{TASK_DEFAULT_LIMIT, TASK_MAX_LIMIT},
+        repository::task_repo,
+        security::jwt::{AccessClaims, ClaimsMethods},
+        state::SharedState,
+    },
+    domain::models::task::{TaskQuery, TaskSummary},
+};
+
+// The last updated tasks, e.g. the dead ones, admins only.
+pub async fn list_tasks_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    Query(query): Query<TaskQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<TaskSummary>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+
+    let limit = query
+        .limit
+        .unwrap_or(TASK_DEFAULT_LIMIT)
+        .clamp(1, TASK_MAX_LIMIT);
+    let tasks = task_repo::list(query.status, limit, &state).await?;
+    Ok(Json(tasks))
+}
+
+// Queues a dead task again, admins only.
+pub async fn retry_task_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<TaskSummary>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+
+    let task = task_repo::requeue_dead(id, &state).await?.ok_or_else(|| {
+        let task_error = TaskError::DeadTaskNotFound(id);
+        APIError::from((task_error.status_code(), APIErrorEntry::from(task_error)))
+    })?;
+    Ok(Json(task))
+}
+
+#[derive(Debug, Error)]
+enum TaskError {
+    #[error("dead task not found: {0}")]
+    DeadTaskNotFound(Uuid),
+}
+
+impl TaskError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::DeadTaskNotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+impl From<TaskError> for APIErrorEntry {
+    fn from(task_error: TaskError) -> Self {
+        let message = task_error.to_string();
+        match task_error {
+            TaskError::DeadTaskNotFound(id) => Self::new(&message)
+                .code(APIErrorCode::TaskNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("no dead task with the ID '{}' exists in our records", id))
+                .detail(serde_json::json!({"task_id": id}))
+                .reason("must be an existing dead task")
+                .instance(&format!("/api/v1/tasks/{}/retry", id))
+                .trace_id()
+                .help(&format!("please check the task ID and its status or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use sqlx::types::{Json as SqlxJson, Uuid};
+use thiserror::Error;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        handlers::note_handlers::subject,
+        version::{self, APIVersion},
+    },
+    application::{
+        repository::{note_repo, note_template_repo, user_repo},
+        security::jwt::{AccessClaims, ClaimsMethods},
+        service::template_service,
+        state::SharedState,
+    },
+    domain::models::{
+        note::Note,
+        note_template::{FromTemplateRequest, NoteTemplate, NoteTemplateRequest},
+    },
+};
+
+pub async fn list_templates_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<NoteTemplate>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    let user_id = subject(&access_claims)?;
+    let templates = note_template_repo::list_by_user(user_id, &state).await?;
+    Ok(Json(templates))
+}
+
+pub async fn get_template_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<NoteTemplate>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let template = find_template(id, &state).await?;
+    validate_template_access(&template, &access_claims, false)?;
+    Ok(Json(template))
+}
+
+pub async fn add_template_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteTemplateRequest>,
+) -> Result<impl IntoResponse, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    // Global templates are managed by admins.
+    let user_id = if request.global {
+        access_claims.validate_role_admin()?;
+        None
+    } else {
+        Some(subject(&access_claims)?)
+    };
+    validate_request(&request)?;
+
+    let template = NoteTemplate {
+        id: Uuid::new_v4(),
+        user_id,
+        name: request.name,
+        title: request.title,
+        text: request.text,
+        prompts: SqlxJson(request.prompts),
+        created_at: None,
+        updated_at: None,
+    };
+    let template = note_template_repo::add(template, &state).await?;
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+pub async fn update_template_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<NoteTemplateRequest>,
+) -> Result<Json<NoteTemplate>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let mut template = find_template(id, &state).await?;
+    validate_template_access(&template, &access_claims, true)?;
+    validate_request(&request)?;
+
+    // The owner of the template is kept, the `global` flag is only used on creation.
+    template.name = request.name;
+    template.title = request.title;
+    template.text = request.text;
+    template.prompts = SqlxJson(request.prompts);
+    let template = note_template_repo::update(template, &state).await?;
+    Ok(Json(template))
+}
+
+pub async fn delete_template_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    let template = find_template(id, &state).await?;
+    validate_template_access(&template, &access_claims, true)?;
+    if note_template_repo::delete(id, &state).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StatusCode::NOT_FOUND)?
+    }
+}
+
+// Renders the template into a new note of the user.
+pub async fn add_note_from_template_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<FromTemplateRequest>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("template_id: {}", id);
+    let template = find_template(id, &state).await?;
+    validate_template_access(&template, &access_claims, false)?;
+    let user = user_repo::get_by_id(subject(&access_claims)?, &state).await?;
+
+    let naive_now = Utc::now().naive_utc();
+    let (title, text) = template_service::render(&template, &request.values, &user, naive_now)
+        .map_err(invalid_template)?;
+    let note = Note {
+        id: Uuid::new_v4(),
+        user_id: user.id,
+        title,
+        text,
+        encryption: None,
+        created_at: Some(naive_now),
+        updated_at: Some(naive_now),
+    };
+    let note = note_repo::add(note, &state).await?;
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+async fn find_template(id: Uuid, state: &SharedState) -> Result<NoteTemplate, APIError> {
+    note_template_repo::get_by_id(id, state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let template_error = TemplateError::TemplateNotFound(id);
+                (
+                    template_error.status_code(),
+                    APIErrorEntry::from(template_error),
+                )
+                    .into()
+            }
+            _ => APIError::from(e),
+        })
+}
+
+// Global templates can be used by anyone and changed by admins,
+// users manage their own templates, admins can manage any.
+fn validate_template_access(
+    template: &NoteTemplate,
+    access_claims: &AccessClaims,
+    write: bool,
+) -> Result<(), APIError> {
+    let is_owner = template.user_id == Some(subject(access_claims)?);
+    if is_owner || (template.is_global() && !write) {
+        return Ok(());
+    }
+    Ok(access_claims.validate_role_admin()?)
+}
+
+fn validate_request(request: &NoteTemplateRequest) -> Result<(), APIError> {
+    template_service::validate(
+        &request.name,
+        request.title.as_deref(),
+        &request.text,
+        &request.prompts,
+    )
+    .map_err(invalid_template)
+}
+
+fn invalid_template(reason: String) -> APIError {
+    let template_error = TemplateError::InvalidTemplate(reason);
+    (
+        template_error.status_code(),
+        APIErrorEntry::from(template_error),
+    )
+        .into()
+}
+
+#[derive(Debug, Error)]
+enum TemplateError {
+    #[error("template not found: {0}")]
+    TemplateNotFound(Uuid),
+    #[error("invalid template: {0}")]
+    InvalidTemplate(String),
+}
+
+impl TemplateError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::TemplateNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidTemplate(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl From<TemplateError> for APIErrorEntry {
+    fn from(template_error: TemplateError) -> Self {
+        let message = template_error.to_string();
+        match template_error {
+            TemplateError::TemplateNotFound(template_id) => Self::new(&message)
+                .code(APIErrorCode::TemplateNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("template with the ID '{}' does not exist in our records", template_id))
+                .detail(serde_json::json!({"template_id": template_id}))
+                .reason("must be an existing template")
+                .instance(&format!("/api/v1/templates/{}", template_id))
+                .trace_id()
+                .help(&format!("please check if the template ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            TemplateError::InvalidTemplate(reason) => Self::new(&message)
+                .code(APIErrorCode::TemplateInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the template or the prompt values are not valid")
+                .reason(&reason)
+                .instance("/api/v1/templates")
+                .trace_id()
+                .help(&format!("please check the placeholders and the prompts or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
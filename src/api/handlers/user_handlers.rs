@@ -14,10 +14,12 @@ use crate::{
         version::{self, APIVersion},
     },
     application::{
-        repository::user_repo,
+        repository::{user_key_repo, user_repo},
         security::jwt::{AccessClaims, ClaimsMethods},
+        service::encryption_service,
         state::SharedState,
     },
+    domain::models::note_key::{UserKey, UserKeyRequest},
     domain::models::user::User,
 };
 
@@ -124,12 +126,68 @@ pub async fn delete_user_handler(
     }
 }
 
+pub async fn get_user_key_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<UserKey>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    // Public keys are readable by any user, they are needed to share encrypted notes.
+    let user_key = user_key_repo::get_by_user(id, &state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                let user_error = UserError::UserKeyNotFound(id);
+                (user_error.status_code(), APIErrorEntry::from(user_error)).into()
+            }
+            _ => APIError::from(e),
+        })?;
+
+    Ok(Json(user_key))
+}
+
+pub async fn put_user_key_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<UserKeyRequest>,
+) -> Result<Json<UserKey>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    if access_claims.sub != id.to_string() {
+        // Only admin can register the key of other users.
+        access_claims.validate_role_admin()?;
+    }
+    encryption_service::validate_key(&request.public_key, &request.algorithm).map_err(|e| {
+        let user_error = UserError::InvalidKey(e);
+        APIError::from((user_error.status_code(), APIErrorEntry::from(user_error)))
+    })?;
+    let user_key = UserKey {
+        user_id: id,
+        public_key: request.public_key,
+        algorithm: request.algorithm,
+        created_at: None,
+        updated_at: None,
+    };
+    let user_key = user_key_repo::upsert(user_key, &state).await?;
+    Ok(Json(user_key))
+}
+
 #[derive(Debug, Error)]
 enum UserError {
     #[error("user not found: {0}")]
     UserNotFound(Uuid),
     #[error("user not found: {0}")]
     UserNameNotFound(String),
+    #[error("user key not found: {0}")]
+    UserKeyNotFound(Uuid),
+    #[error("invalid user key: {0}")]
+    InvalidKey(String),
 }
 
 impl UserError {
@@ -137,6 +195,8 @@ impl UserError {
         match self {
             Self::UserNotFound(_) => StatusCode::NOT_FOUND,
             Self::UserNameNotFound(_) => StatusCode::NOT_FOUND,
+            Self::UserKeyNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidKey(_) => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 }
@@ -164,7 +224,26 @@ impl From<UserError> for APIErrorEntry {
                 .instance(&format!("/api/v1/users/username/{}", username))
                 .trace_id()
                 .help(&format!("please check if the username is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
-                .doc_url()
+                .doc_url(),
+            UserError::UserKeyNotFound(user_id) => Self::new(&message)
+                .code(APIErrorCode::UserKeyNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("the user with the ID '{}' has not registered a public key", user_id))
+                .detail(serde_json::json!({"user_id": user_id}))
+                .reason("must be an existing user key")
+                .instance(&format!("/api/v1/users/{}/key", user_id))
+                .trace_id()
+                .help(&format!("please check if the user ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            UserError::InvalidKey(reason) => Self::new(&message)
+                .code(APIErrorCode::NoteEncryptionInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the public key of the user is not valid")
+                .reason(&reason)
+                .instance("/api/v1/users")
+                .trace_id()
+                .help(&format!("please check the public key or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
         }
     }
 }
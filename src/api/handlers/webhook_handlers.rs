@@ -0,0 +1,266 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sqlx::types::Uuid;
+use thiserror::Error;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        error::API_DOCUMENT_URL,
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::{
+            WEBHOOK_DELIVERIES_DEFAULT_LIMIT, WEBHOOK_DELIVERIES_MAX_LIMIT,
+            WEBHOOK_SECRET_MIN_LENGTH,
+        },
+        repository::{webhook_delivery_repo, webhook_repo},
+        security::{
+            jwt::{AccessClaims, ClaimsMethods},
+            outbound,
+        },
+        service::webhook_service,
+        state::SharedState,
+    },
+    domain::models::webhook::{Webhook, WebhookDeliveriesQuery, WebhookDelivery, WebhookRequest},
+};
+
+// The webhooks are managed by the admins, they receive the events of every user.
+pub async fn list_webhooks_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Webhook>>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+    let webhooks = webhook_repo::list(&state).await?;
+    Ok(Json(webhooks))
+}
+
+pub async fn get_webhook_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<Json<Webhook>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    access_claims.validate_role_admin()?;
+    let webhook = find_webhook(id, &state).await?;
+    Ok(Json(webhook))
+}
+
+pub async fn add_webhook_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+    Json(request): Json<WebhookRequest>,
+) -> Result<impl IntoResponse, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    access_claims.validate_role_admin()?;
+    validate_request(&request, &state).await?;
+
+    let webhook = Webhook {
+        id: Uuid::new_v4(),
+        url: request.url,
+        secret: request.secret,
+        events: request.events,
+        active: request.active,
+        created_at: None,
+        updated_at: None,
+    };
+    let webhook = webhook_repo::add(webhook, &state).await?;
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+pub async fn update_webhook_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+    Json(request): Json<WebhookRequest>,
+) -> Result<Json<Webhook>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    access_claims.validate_role_admin()?;
+    let mut webhook = find_webhook(id, &state).await?;
+    validate_request(&request, &state).await?;
+
+    webhook.url = request.url;
+    webhook.secret = request.secret;
+    webhook.events = request.events;
+    webhook.active = request.active;
+    let webhook = webhook_repo::update(webhook, &state).await?;
+    Ok(Json(webhook))
+}
+
+pub async fn delete_webhook_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    access_claims.validate_role_admin()?;
+    if webhook_repo::delete(id, &state).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(webhook_error(WebhookError::WebhookNotFound(id)))
+    }
+}
+
+// The delivery log of the webhook, the most recent first.
+pub async fn list_webhook_deliveries_handler(
+    access_claims: AccessClaims,
+    Path((version, id)): Path<(String, Uuid)>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<WebhookDelivery>>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}", id);
+    access_claims.validate_role_admin()?;
+    find_webhook(id, &state).await?;
+
+    let limit = query
+        .limit
+        .unwrap_or(WEBHOOK_DELIVERIES_DEFAULT_LIMIT)
+        .clamp(1, WEBHOOK_DELIVERIES_MAX_LIMIT);
+    let deliveries = webhook_delivery_repo::list_by_webhook(id, limit, &state).await?;
+    Ok(Json(deliveries))
+}
+
+// Queues a new delivery of the event of a previous delivery.
+pub async fn redeliver_webhook_handler(
+    access_claims: AccessClaims,
+    Path((version, id, delivery_id)): Path<(String, Uuid, Uuid)>,
+    State(state): State<SharedState>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("id: {}, delivery_id: {}", id, delivery_id);
+    access_claims.validate_role_admin()?;
+
+    let delivery = webhook_delivery_repo::get_by_id(delivery_id, &state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                webhook_error(WebhookError::DeliveryNotFound(id, delivery_id))
+            }
+            _ => APIError::from(e),
+        })?;
+    if delivery.webhook_id != id {
+        return Err(webhook_error(WebhookError::DeliveryNotFound(
+            id,
+            delivery_id,
+        )));
+    }
+    let delivery = webhook_service::redeliver(delivery, &state).await?;
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+async fn find_webhook(id: Uuid, state: &SharedState) -> Result<Webhook, APIError> {
+    webhook_repo::get_by_id(id, state)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => webhook_error(WebhookError::WebhookNotFound(id)),
+            _ => APIError::from(e),
+        })
+}
+
+async fn validate_request(request: &WebhookRequest, state: &SharedState) -> Result<(), APIError> {
+    let config = state.config.get();
+    outbound::validate_url(&request.url, &config.webhook_allowed_hosts)
+        .await
+        .map_err(|e| invalid_webhook(&format!("invalid URL: {}", e)))?;
+    if request.secret.chars().count() < WEBHOOK_SECRET_MIN_LENGTH {
+        return Err(invalid_webhook(&format!(
+            "the secret must have at least {} characters",
+            WEBHOOK_SECRET_MIN_LENGTH
+        )));
+    }
+    if request.events.is_empty() {
+        return Err(invalid_webhook("at least one event must be subscribed"));
+    }
+    Ok(())
+}
+
+fn invalid_webhook(reason: &str) -> APIError {
+    webhook_error(WebhookError::InvalidWebhook(reason.to_owned()))
+}
+
+fn webhook_error(webhook_error: WebhookError) -> APIError {
+    (
+        webhook_error.status_code(),
+        APIErrorEntry::from(webhook_error),
+    )
+        .into()
+}
+
+#[derive(Debug, Error)]
+enum WebhookError {
+    #[error("webhook not found: {0}")]
+    WebhookNotFound(Uuid),
+    #[error("webhook delivery not found: {1}")]
+    DeliveryNotFound(Uuid, Uuid),
+    #[error("invalid webhook: {0}")]
+    InvalidWebhook(String),
+}
+
+impl WebhookError {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::WebhookNotFound(_) | Self::DeliveryNotFound(_, _) => StatusCode::NOT_FOUND,
+            Self::InvalidWebhook(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl From<WebhookError> for APIErrorEntry {
+    fn from(webhook_error: WebhookError) -> Self {
+        let message = webhook_error.to_string();
+        match webhook_error {
+            WebhookError::WebhookNotFound(id) => Self::new(&message)
+                .code(APIErrorCode::WebhookNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("webhook with the ID '{}' does not exist in our records", id))
+                .detail(serde_json::json!({"webhook_id": id}))
+                .reason("must be an existing webhook")
+                .instance(&format!("/api/v1/webhooks/{}", id))
+                .trace_id()
+                .help(&format!("please check if the webhook ID is correct or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            WebhookError::DeliveryNotFound(id, delivery_id) => Self::new(&message)
+                .code(APIErrorCode::WebhookDeliveryNotFound)
+                .kind(APIErrorKind::ResourceNotFound)
+                .description(&format!("no delivery with the ID '{}' exists for the webhook '{}'", delivery_id, id))
+                .detail(serde_json::json!({"webhook_id": id, "delivery_id": delivery_id}))
+                .reason("must be an existing delivery of the webhook")
+                .instance(&format!("/api/v1/webhooks/{}/deliveries/{}/redeliver", id, delivery_id))
+                .trace_id()
+                .help(&format!("please check the webhook and delivery IDs or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+            WebhookError::InvalidWebhook(reason) => Self::new(&message)
+                .code(APIErrorCode::WebhookInvalid)
+                .kind(APIErrorKind::ValidationError)
+                .description("the webhook is not valid")
+                .reason(&reason)
+                .instance("/api/v1/webhooks")
+                .trace_id()
+                .help(&format!("please check the URL, the secret and the events or refer to our documentation at {}#errors for more information", API_DOCUMENT_URL))
+                .doc_url(),
+        }
+    }
+}
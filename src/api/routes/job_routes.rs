@@ -0,0 +1,16 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    api::handlers::job_handlers::{list_job_runs_handler, list_jobs_handler, run_job_handler},
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_jobs_handler))
+        .route("/{name}/runs", get(list_job_runs_handler))
+        .route("/{name}/run", post(run_job_handler))
+}
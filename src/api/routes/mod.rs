@@ -1,4 +1,9 @@
 pub mod auth_routes;
+pub mod job_routes;
 pub mod note_routes;
+pub mod reminder_routes;
 pub mod stats_routes;
+pub mod task_routes;
+pub mod template_routes;
 pub mod user_routes;
+pub mod webhook_routes;
@@ -1,14 +1,28 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{delete, get, post, put},
 };
 
 use crate::{
-    api::handlers::note_handlers::{
-        add_note_handler, delete_note_handler, get_note_handler, list_notes_by_user_handler,
-        list_notes_handler, update_note_handler,
+    api::handlers::{
+        comment_handlers::{
+            add_note_comment_handler, delete_note_comment_handler, get_note_comment_handler,
+            list_note_comments_handler, resolve_note_comment_handler,
+            unresolve_note_comment_handler, update_note_comment_handler,
+        },
+        note_handlers::{
+            add_note_handler, delete_note_handler, delete_note_key_handler, export_notes_handler,
+            get_note_flags_handler, get_note_handler, get_note_import_handler,
+            get_note_key_handler, import_notes_handler, list_note_backlinks_handler,
+            list_note_keys_handler, list_note_links_handler, list_notes_by_user_handler,
+            list_notes_handler, note_graph_handler, put_note_key_handler,
+            update_note_flags_handler, update_note_handler,
+        },
+        reminder_handlers::{add_note_reminder_handler, list_note_reminders_handler},
+        template_handlers::add_note_from_template_handler,
     },
-    application::state::SharedState,
+    application::{constants::NOTE_IMPORT_MAX_BODY_BYTES, state::SharedState},
 };
 
 pub fn routes() -> Router<SharedState> {
@@ -18,5 +32,42 @@ pub fn routes() -> Router<SharedState> {
         .route("/{id}", get(get_note_handler))
         .route("/{id}", put(update_note_handler))
         .route("/{id}", delete(delete_note_handler))
+        .route("/{id}/flags", get(get_note_flags_handler))
+        .route("/{id}/flags", put(update_note_flags_handler))
+        .route("/{id}/links", get(list_note_links_handler))
+        .route("/{id}/backlinks", get(list_note_backlinks_handler))
+        .route("/{id}/comments", get(list_note_comments_handler))
+        .route("/{id}/comments", post(add_note_comment_handler))
+        .route("/{id}/comments/{comment_id}", get(get_note_comment_handler))
+        .route(
+            "/{id}/comments/{comment_id}",
+            put(update_note_comment_handler),
+        )
+        .route(
+            "/{id}/comments/{comment_id}",
+            delete(delete_note_comment_handler),
+        )
+        .route(
+            "/{id}/comments/{comment_id}/resolve",
+            post(resolve_note_comment_handler),
+        )
+        .route(
+            "/{id}/comments/{comment_id}/unresolve",
+            post(unresolve_note_comment_handler),
+        )
+        .route("/{id}/reminders", get(list_note_reminders_handler))
+        .route("/{id}/reminders", post(add_note_reminder_handler))
+        .route("/{id}/keys", get(list_note_keys_handler))
+        .route("/{id}/keys/{user_id}", get(get_note_key_handler))
+        .route("/{id}/keys/{user_id}", put(put_note_key_handler))
+        .route("/{id}/keys/{user_id}", delete(delete_note_key_handler))
         .route("/user", post(list_notes_by_user_handler))
+        .route("/from-template/{id}", post(add_note_from_template_handler))
+        .route("/graph/{user_id}", get(note_graph_handler))
+        .route("/export", get(export_notes_handler))
+        .route(
+            "/import",
+            post(import_notes_handler).layer(DefaultBodyLimit::max(NOTE_IMPORT_MAX_BODY_BYTES)),
+        )
+        .route("/import/{id}", get(get_note_import_handler))
 }
@@ -0,0 +1,22 @@
+use axum::{
+    Router,
+    routing::{delete, get, post},
+};
+
+use crate::{
+    api::handlers::reminder_handlers::{
+        delete_reminder_handler, dismiss_reminder_handler, get_reminder_handler,
+        list_reminders_handler, reminder_events_handler, snooze_reminder_handler,
+    },
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_reminders_handler))
+        .route("/events", get(reminder_events_handler))
+        .route("/{id}", get(get_reminder_handler))
+        .route("/{id}", delete(delete_reminder_handler))
+        .route("/{id}/snooze", post(snooze_reminder_handler))
+        .route("/{id}/dismiss", post(dismiss_reminder_handler))
+}
@@ -1,15 +1,12 @@
-use axum::{
-    Router,
-    routing::{get, post},
-};
+use axum::{Router, routing::get};
 
 use crate::{
-    api::handlers::stats_handlers::{get_stats_handler, update_stats_count},
+    api::handlers::stats_handlers::{get_stats_handler, get_timeseries_handler},
     application::state::SharedState,
 };
 
 pub fn routes() -> Router<SharedState> {
     Router::new()
-        .route("/{id}", get(get_stats_handler))
-        .route("/user", post(update_stats_count))
+        .route("/timeseries", get(get_timeseries_handler))
+        .route("/{user_id}", get(get_stats_handler))
 }
@@ -0,0 +1,15 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    api::handlers::task_handlers::{list_tasks_handler, retry_task_handler},
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_tasks_handler))
+        .route("/{id}/retry", post(retry_task_handler))
+}
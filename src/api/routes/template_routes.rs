@@ -0,0 +1,21 @@
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
+
+use crate::{
+    api::handlers::template_handlers::{
+        add_template_handler, delete_template_handler, get_template_handler,
+        list_templates_handler, update_template_handler,
+    },
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_templates_handler))
+        .route("/", post(add_template_handler))
+        .route("/{id}", get(get_template_handler))
+        .route("/{id}", put(update_template_handler))
+        .route("/{id}", delete(delete_template_handler))
+}
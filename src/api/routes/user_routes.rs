@@ -6,7 +6,7 @@ use axum::{
 use crate::{
     api::handlers::user_handlers::{
         add_user_handler, delete_user_handler, get_user_handler, get_user_handler_username,
-        list_users_handler, update_user_handler,
+        get_user_key_handler, list_users_handler, put_user_key_handler, update_user_handler,
     },
     application::state::SharedState,
 };
@@ -18,5 +18,7 @@ pub fn routes() -> Router<SharedState> {
         .route("/{id}", get(get_user_handler))
         .route("/{id}", put(update_user_handler))
         .route("/{id}", delete(delete_user_handler))
+        .route("/{id}/key", get(get_user_key_handler))
+        .route("/{id}/key", put(put_user_key_handler))
         .route("/username/{username}", get(get_user_handler_username))
 }
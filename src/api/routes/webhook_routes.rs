@@ -0,0 +1,27 @@
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
+
+use crate::{
+    api::handlers::webhook_handlers::{
+        add_webhook_handler, delete_webhook_handler, get_webhook_handler,
+        list_webhook_deliveries_handler, list_webhooks_handler, redeliver_webhook_handler,
+        update_webhook_handler,
+    },
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/", get(list_webhooks_handler))
+        .route("/", post(add_webhook_handler))
+        .route("/{id}", get(get_webhook_handler))
+        .route("/{id}", put(update_webhook_handler))
+        .route("/{id}", delete(delete_webhook_handler))
+        .route("/{id}/deliveries", get(list_webhook_deliveries_handler))
+        .route(
+            "/{id}/deliveries/{delivery_id}/redeliver",
+            post(redeliver_webhook_handler),
+        )
+}
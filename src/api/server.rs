@@ -1,15 +1,23 @@
-use std::{collections::HashMap, sync::Arc, time::SystemTime};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 
 use axum::{
     Json, Router,
     body::Body,
-    extract::{Query, Request},
-    http::{HeaderMap, Method, StatusCode},
+    extract::{MatchedPath, Query, Request, State},
+    http::{
+        HeaderMap, HeaderValue, Method, StatusCode,
+        header::{AUTHORIZATION, CONTENT_TYPE},
+    },
     middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{any, get},
 };
 use chrono::Utc;
+use metrics::{counter, gauge, histogram};
 use serde_json::json;
 use tokio::{
     net::TcpListener,
@@ -18,22 +26,40 @@ use tokio::{
         unix::{self, SignalKind},
     },
 };
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::Instrument;
+use uuid::Uuid;
 
 use crate::{
     api::{
         error::APIError,
-        routes::{auth_routes, note_routes, stats_routes, user_routes},
+        routes::{
+            auth_routes, job_routes, note_routes, reminder_routes, stats_routes, task_routes,
+            template_routes, user_routes, webhook_routes,
+        },
     },
-    application::{security::jwt::AccessClaims, state::SharedState},
+    application::{
+        constants::*, security::jwt::AccessClaims, service::health_service, state::SharedState,
+    },
+    domain::models::health::HealthStatus,
+    infrastructure::{prometheus, telemetry},
 };
 
 pub async fn start(state: SharedState) {
+    // Install the metrics recorder before the first request.
+    prometheus::install();
+
     // Build a CORS layer.
     // see https://docs.rs/tower-http/latest/tower_http/cors/index.html
     // for more details
+    // The allowed origins are read on each request, they change with the configuration reloads.
+    let config_handle = state.config.clone();
     let cors_layer = CorsLayer::new()
-        .allow_origin(Any)
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| config_handle.get().cors_allows(origin))
+        }))
         .allow_headers(Any)
         .allow_methods(Any);
 
@@ -43,6 +69,8 @@ pub async fn start(state: SharedState) {
         .route("/head", get(head_request_handler))
         .route("/any", any(any_request_handler))
         .route("/{version}/health", get(health_handler))
+        .route("/{version}/health/live", get(liveness_handler))
+        .route("/{version}/health/ready", get(readiness_handler))
         .route("/{version}/version", get(version_handler))
         // Nesting authentication routes.
         .nest("/{version}/auth", auth_routes::routes())
@@ -50,16 +78,49 @@ pub async fn start(state: SharedState) {
         .nest("/{version}/users", user_routes::routes())
         // Nesting note routes.
         .nest("/{version}/notes", note_routes::routes())
+        // Nesting reminder routes.
+        .nest("/{version}/reminders", reminder_routes::routes())
+        // Nesting template routes.
+        .nest("/{version}/templates", template_routes::routes())
         // Nesting stats routes.
         .nest("/{version}/stats", stats_routes::routes())
+        // Nesting job routes.
+        .nest("/{version}/jobs", job_routes::routes())
+        // Nesting task routes.
+        .nest("/{version}/tasks", task_routes::routes())
+        // Nesting webhook routes.
+        .nest("/{version}/webhooks", webhook_routes::routes())
+        // Record the metrics of the matched routes.
+        .route_layer(middleware::from_fn(metrics_middleware))
         // Add a fallback service for handling routes to unknown paths.
-        .fallback(error_404_handler)
+        .fallback(error_404_handler);
+
+    // The metrics are served by a separate listener, or by the API listener behind a token.
+    let config = state.config.get();
+    let metrics_addr = &config.metrics_addr;
+    let router = if !metrics_addr.is_empty() {
+        let metrics_router = Router::new()
+            .route("/metrics", get(metrics_handler))
+            .with_state(Arc::clone(&state));
+        let metrics_listener = TcpListener::bind(metrics_addr).await.unwrap();
+        tracing::info!("serving metrics on {}", metrics_addr);
+        tokio::spawn(async move { axum::serve(metrics_listener, metrics_router).await });
+        router
+    } else if !config.metrics_token.is_empty() {
+        router.route("/metrics", get(metrics_handler))
+    } else {
+        tracing::info!("metrics endpoint disabled, set METRICS_ADDR or METRICS_TOKEN to enable it");
+        router
+    };
+
+    let router = router
         .with_state(Arc::clone(&state))
         .layer(cors_layer)
-        .layer(middleware::from_fn(logging_middleware));
+        .layer(middleware::from_fn(logging_middleware))
+        .layer(middleware::from_fn(trace_middleware));
 
     // Build the listener.
-    let addr = state.config.service_socket_addr();
+    let addr = config.service_socket_addr();
     let listener = TcpListener::bind(&addr).await.unwrap();
     tracing::info!("listening on {}", addr);
 
@@ -98,7 +159,47 @@ async fn shutdown_signal() {
     tracing::info!("received termination signal, shutting down...");
 }
 
-#[tracing::instrument(level = tracing::Level::TRACE, name = "axum", skip_all, fields(method=request.method().to_string(), uri=request.uri().to_string()))]
+// Runs the request in a span continuing the trace context of the `traceparent` header,
+// and returns the trace context of the request in the response headers.
+// The fields of the span, request id, route and user id, are on every log line of the request.
+pub async fn trace_middleware(request: Request<Body>, next: Next) -> Response {
+    let method = request.method().to_string();
+    let request_id = request_id(request.headers());
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %method,
+        otel.kind = "server",
+        http.request.method = %method,
+        url.path = %request.uri().path(),
+        http.route = tracing::field::Empty,
+        http.response.status_code = tracing::field::Empty,
+        request_id = %request_id,
+        user_id = tracing::field::Empty,
+    );
+    telemetry::extract_parent(&span, request.headers());
+
+    let mut response = next.run(request).instrument(span.clone()).await;
+    span.record("http.response.status_code", response.status().as_u16());
+    telemetry::inject(&span, response.headers_mut());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+// Request id of the `x-request-id` header, a new one when missing or invalid.
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= REQUEST_ID_MAX_LENGTH
+                && id.chars().all(|c| c.is_ascii_graphic())
+        })
+        .map_or_else(|| Uuid::new_v4().to_string(), str::to_owned)
+}
+
 pub async fn logging_middleware(request: Request<Body>, next: Next) -> Response {
     tracing::trace!(
         "received a {} request to {}",
@@ -108,6 +209,30 @@ pub async fn logging_middleware(request: Request<Body>, next: Next) -> Response
     next.run(request).await
 }
 
+// Records the request count and latency per matched route and status.
+pub async fn metrics_middleware(request: Request<Body>, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(String::new, |path| path.as_str().to_owned());
+    // Name the request span after the matched route.
+    let span = tracing::Span::current();
+    span.record("http.route", route.as_str());
+    span.record("otel.name", format!("{} {}", method, route));
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(METRICS_HTTP_REQUESTS_TOTAL, &labels).increment(1);
+    histogram!(METRICS_HTTP_REQUEST_DURATION, &labels).record(started.elapsed());
+    response
+}
+
 // Root handler.
 pub async fn root_handler(access_claims: AccessClaims) -> Result<impl IntoResponse, APIError> {
     if tracing::enabled!(tracing::Level::TRACE) {
@@ -127,6 +252,51 @@ pub async fn health_handler() -> Result<impl IntoResponse, APIError> {
     Ok(Json(json!({"status": "healthy"})))
 }
 
+// Liveness probe, the process serves requests.
+pub async fn liveness_handler() -> impl IntoResponse {
+    Json(json!({"status": HealthStatus::Up}))
+}
+
+// Readiness probe, the required dependencies respond.
+pub async fn readiness_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let readiness = health_service::readiness(&state).await;
+    let status_code = match readiness.status {
+        HealthStatus::Up => StatusCode::OK,
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status_code, Json(readiness))
+}
+
+// Metrics request handler, in the Prometheus text format.
+pub async fn metrics_handler(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let config = state.config.get();
+    let token = &config.metrics_token;
+    if !token.is_empty() {
+        let authorized = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|bearer| bearer == token);
+        if !authorized {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+
+    // The pool gauges are sampled on scrape.
+    let pool = &state.db_pool;
+    let idle = pool.num_idle();
+    gauge!(METRICS_DB_POOL_CONNECTIONS, "state" => "idle").set(idle as f64);
+    gauge!(METRICS_DB_POOL_CONNECTIONS, "state" => "active")
+        .set(pool.size().saturating_sub(idle as u32) as f64);
+    gauge!(METRICS_DB_POOL_MAX_CONNECTIONS).set(pool.options().get_max_connections() as f64);
+
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus::render(),
+    )
+        .into_response()
+}
+
 // Version request handler.
 pub async fn version_handler() -> Result<impl IntoResponse, APIError> {
     let result = json!({
@@ -1,17 +1,99 @@
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
-
 use crate::{
     api::server,
-    application::{config, state::AppState},
-    infrastructure::{database::Database, redis},
+    application::{
+        config::{Config, ConfigHandle},
+        security::encryption::NoteCipher,
+        service::{
+            config_service, envelope_service, event_service, job_service,
+            notification_service::{self, Notifiers},
+            reminder_service, stats_service, task_service,
+        },
+        state::{AppState, SharedState},
+    },
+    infrastructure::{database::Database, mail::LogMailer, redis, token_store},
 };
 
-pub async fn run() {
-    // Load configuration.
-    let config = config::load();
+pub async fn run(config: Config, note_cipher: Option<NoteCipher>) {
+    let shared_state = build_state(config, note_cipher).await;
+
+    // Keep the revocations stored by the previous versions.
+    if let Err(e) = shared_state.token_store.migrate().await {
+        tracing::error!("failed to migrate the revoked tokens: {}", e);
+    }
+
+    // Reload the configuration on SIGHUP and on changes of the config file.
+    tokio::spawn(config_service::watch(Arc::clone(&shared_state)));
+
+    // Fire the due reminders in the background.
+    tokio::spawn(reminder_service::run_scheduler(Arc::clone(&shared_state)));
+
+    // Push the reminders fired by any instance to the event streams connected to this one.
+    tokio::spawn(notification_service::run_listener(Arc::clone(
+        &shared_state,
+    )));
+
+    // Run the maintenance jobs on their schedules.
+    if shared_state.config.get().jobs_enabled {
+        tokio::spawn(job_service::run_scheduler(Arc::clone(&shared_state)));
+    }
+
+    // Dispatch the events of the outbox to the webhooks.
+    tokio::spawn(event_service::run_dispatcher(Arc::clone(&shared_state)));
+
+    // Run the queued tasks, unless they are run by separate worker processes.
+    let workers = shared_state.config.get().task_workers;
+    if workers > 0 {
+        tokio::spawn(task_service::run_workers(
+            Arc::clone(&shared_state),
+            workers,
+        ));
+    }
+
+    // Re-wrap the data keys after a master key rotation and encrypt the notes written in plaintext.
+    if shared_state.note_cipher.is_some() {
+        let state = Arc::clone(&shared_state);
+        tokio::spawn(async move {
+            if let Err(e) = envelope_service::reencrypt(&state).await {
+                tracing::error!("could not re-encrypt the notes: {}", e);
+            }
+        });
+    }
+
+    server::start(shared_state).await;
+}
 
+// Runs the task workers without the API, used by the `worker` command.
+pub async fn run_worker(config: Config, note_cipher: Option<NoteCipher>) {
+    let shared_state = build_state(config, note_cipher).await;
+    let workers = shared_state.config.get().task_workers.max(1);
+    task_service::run_workers(shared_state, workers).await;
+}
+
+// Recomputes the note statistics of every user from the notes, used by the `rebuild-stats` command.
+pub async fn rebuild_stats(config: Config, note_cipher: Option<NoteCipher>) {
+    let shared_state = build_state(config, note_cipher).await;
+    let rows = stats_service::rebuild(&shared_state)
+        .await
+        .expect("Failed to rebuild the note statistics.");
+    tracing::info!("note statistics rebuilt, users: {}", rows);
+}
+
+// Re-wraps the data keys and encrypts the notes written in plaintext, used by the `reencrypt-notes`
+// command after a master key rotation.
+pub async fn reencrypt_notes(config: Config, note_cipher: Option<NoteCipher>) {
+    let shared_state = build_state(config, note_cipher).await;
+    if shared_state.note_cipher.is_none() {
+        tracing::warn!("note encryption at rest is disabled, nothing to re-encrypt");
+        return;
+    }
+    envelope_service::reencrypt(&shared_state)
+        .await
+        .expect("Failed to re-encrypt the notes.");
+}
+
+async fn build_state(config: Config, note_cipher: Option<NoteCipher>) -> SharedState {
     // Connect to Redis.
     let redis = redis::open(&config).await;
 
@@ -25,12 +107,16 @@ pub async fn run() {
         .await
         .expect("Failed to run database migrations.");
 
-    // Build the application state.
-    let shared_state = Arc::new(AppState {
-        config,
-        db_pool,
-        redis: Mutex::new(redis),
-    });
+    // Select the store of the revoked tokens.
+    let token_store = token_store::open(&config, &redis, &db_pool);
 
-    server::start(shared_state).await;
+    // Build the application state.
+    Arc::new(AppState {
+        config: ConfigHandle::new(config),
+        db_pool: db_pool.clone(),
+        redis,
+        token_store,
+        note_cipher,
+        notifiers: Notifiers::new(Box::new(LogMailer), db_pool),
+    })
 }
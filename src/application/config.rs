@@ -1,21 +1,63 @@
-use std::{fmt, net::SocketAddr};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
 use jsonwebtoken::{DecodingKey, EncodingKey};
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
 
-use crate::infrastructure::database::{DatabaseOptions, PostgresOptions};
+use crate::{
+    application::{
+        constants::{
+            EVENT_SINK_LOG, REDIS_MODE_CLUSTER, REDIS_MODE_SENTINEL, REDIS_MODE_STANDALONE,
+            TOKEN_STORE_MEMORY, TOKEN_STORE_POSTGRES, TOKEN_STORE_REDIS,
+        },
+        service::cron_service::CronSchedule,
+    },
+    infrastructure::database::{DatabaseOptions, PostgresOptions},
+};
 
-#[derive(Clone, Debug)]
+const REDACTED: &str = "[REDACTED]";
+const CONFIG_FILE: &str = "CONFIG_FILE";
+const FILE_SUFFIX: &str = "_FILE";
+
+// The settings applied by a reload, the other settings require a restart.
+const RELOADABLE: [&str; 6] = [
+    "jwt_expire_access_token_seconds",
+    "jwt_expire_refresh_token_seconds",
+    "jwt_validation_leeway_seconds",
+    "jwt_enable_revoked_tokens",
+    "cors_allowed_origins",
+    "log_level",
+];
+
+#[derive(Clone, Debug, Serialize)]
 pub struct Config {
     // REST API configuration.
     pub service_host: String,
     pub service_port: u16,
+    pub cors_allowed_origins: String,
 
     // Redis configuration.
     pub redis_host: String,
     pub redis_port: u16,
+    pub redis_mode: String,
+    pub redis_nodes: String,
+    pub redis_sentinel_master: String,
+    pub redis_username: String,
+    #[serde(serialize_with = "redact")]
+    pub redis_password: String,
+    pub redis_db: i64,
+    pub redis_tls: bool,
 
     // PostgreSQL configuration.
     pub postgres_user: String,
+    #[serde(serialize_with = "redact")]
     pub postgres_password: String,
     pub postgres_host: String,
     pub postgres_port: u16,
@@ -23,12 +65,51 @@ pub struct Config {
     pub postgres_connection_pool: u32,
 
     // JWT configuration.
+    #[serde(serialize_with = "redact")]
     pub jwt_secret: String,
+    #[serde(skip)]
     pub jwt_keys: JwtKeys,
     pub jwt_expire_access_token_seconds: i64,
     pub jwt_expire_refresh_token_seconds: i64,
     pub jwt_validation_leeway_seconds: i64,
     pub jwt_enable_revoked_tokens: bool,
+    pub token_store: String,
+
+    // Note encryption at rest configuration.
+    pub note_encryption_enabled: bool,
+    pub master_key_file: String,
+    pub master_key_id: String,
+
+    // Outgoing webhooks configuration.
+    pub webhook_allowed_hosts: String,
+
+    // Maintenance jobs configuration.
+    pub jobs_enabled: bool,
+    pub job_token_cleanup_schedule: String,
+    pub job_purge_schedule: String,
+    pub job_stats_rollup_schedule: String,
+    pub job_purge_retention_days: i64,
+
+    // Task queue configuration.
+    pub task_workers: usize,
+    pub task_max_attempts: i32,
+    pub task_visibility_timeout_seconds: i64,
+
+    // Domain events configuration.
+    pub event_sinks: String,
+
+    // Logging configuration.
+    pub log_format: String,
+    pub log_level: String,
+
+    // Metrics configuration.
+    pub metrics_addr: String,
+    #[serde(serialize_with = "redact")]
+    pub metrics_token: String,
+
+    // OpenTelemetry configuration.
+    pub otel_exporter_otlp_endpoint: String,
+    pub otel_service_name: String,
 }
 #[derive(Clone)]
 pub struct JwtKeys {
@@ -63,8 +144,19 @@ impl Config {
         SocketAddr::from_str(&format!("{}:{}", self.service_host, self.service_port)).unwrap()
     }
 
-    pub fn redis_url(&self) -> String {
-        format!("redis://{}:{}", self.redis_host, self.redis_port)
+    // The Redis servers, the sentinels in sentinel mode or the seed nodes in cluster mode.
+    // Defaults to the Redis host when no nodes are configured.
+    pub fn redis_node_addrs(&self) -> Vec<(String, u16)> {
+        if self.redis_nodes.trim().is_empty() {
+            return vec![(self.redis_host.clone(), self.redis_port)];
+        }
+        self.redis_nodes
+            .split(',')
+            .filter_map(|node| {
+                let (host, port) = node.trim().rsplit_once(':')?;
+                Some((host.to_owned(), port.parse().ok()?))
+            })
+            .collect()
     }
 
     pub fn postgres_url(&self) -> String {
@@ -77,46 +169,613 @@ impl Config {
             self.postgres_db
         )
     }
+
+    // Whether the CORS requests of the origin are allowed, `*` allows any origin.
+    pub fn cors_allows(&self, origin: &str) -> bool {
+        self.cors_allowed_origins
+            .split(',')
+            .map(str::trim)
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    // This configuration with the reloadable settings of the other configuration.
+    pub fn with_reloaded(&self, other: &Self) -> Self {
+        Self {
+            jwt_expire_access_token_seconds: other.jwt_expire_access_token_seconds,
+            jwt_expire_refresh_token_seconds: other.jwt_expire_refresh_token_seconds,
+            jwt_validation_leeway_seconds: other.jwt_validation_leeway_seconds,
+            jwt_enable_revoked_tokens: other.jwt_enable_revoked_tokens,
+            cors_allowed_origins: other.cors_allowed_origins.clone(),
+            log_level: other.log_level.clone(),
+            ..self.clone()
+        }
+    }
+
+    // The changed settings, the secrets are compared but redacted.
+    pub fn diff(&self, other: &Self) -> Vec<ConfigChange> {
+        let (old, new) = match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) => (old, new),
+            _ => return Vec::new(),
+        };
+        let secret_changed = |key: &str| match key {
+            "redis_password" => self.redis_password != other.redis_password,
+            "postgres_password" => self.postgres_password != other.postgres_password,
+            "jwt_secret" => self.jwt_secret != other.jwt_secret,
+            "metrics_token" => self.metrics_token != other.metrics_token,
+            _ => false,
+        };
+
+        old.into_iter()
+            .filter_map(|(key, old_value)| {
+                let new_value = new.get(&key).cloned().unwrap_or_default();
+                (old_value != new_value || secret_changed(&key)).then(|| ConfigChange {
+                    reloadable: RELOADABLE.contains(&key.as_str()),
+                    key,
+                    old: display_value(&old_value),
+                    new: display_value(&new_value),
+                })
+            })
+            .collect()
+    }
+
+    // The effective configuration as a TOML config file, with the secrets redacted.
+    pub fn to_redacted_toml(&self) -> String {
+        toml::to_string(self).expect("Failed to serialize the configuration.")
+    }
 }
 
-pub fn load() -> Config {
-    let env_file = if env_get_or("ENV_TEST", "0") == "1" {
-        ".env_test"
-    } else {
-        ".env"
-    };
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => format!("{:?}", s),
+        value => value.to_string(),
+    }
+}
 
-    // Try to load environment variables from file.
-    if dotenvy::from_filename(env_file).is_ok() {
-        tracing::info!("{} file loaded", env_file);
+fn redact<S: Serializer>(value: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if value.is_empty() {
+        serializer.serialize_str("")
     } else {
-        tracing::info!("{} file not found, using existing environment", env_file);
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+// A changed setting, applied by a reload when reloadable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+    pub reloadable: bool,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.key, self.old, self.new)
+    }
+}
+
+// Handle of the current configuration, swapped by the reloads.
+#[derive(Clone, Debug)]
+pub struct ConfigHandle(Arc<RwLock<Arc<Config>>>);
+
+impl ConfigHandle {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    // Snapshot of the current configuration.
+    pub fn get(&self) -> Arc<Config> {
+        Arc::clone(&self.0.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn swap(&self, config: Config) -> Arc<Config> {
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, Arc::new(config))
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("{0}: missing value")]
+    Missing(String),
+    #[error("{key}: invalid value, {reason}")]
+    Invalid { key: String, reason: String },
+    #[error("{0}: unknown key in the config file")]
+    UnknownKey(String),
+    #[error("config file {path}: {reason}")]
+    File { path: String, reason: String },
+}
+
+impl ConfigError {
+    fn key(&self) -> &str {
+        match self {
+            Self::Missing(key) | Self::UnknownKey(key) | Self::Invalid { key, .. } => key,
+            Self::File { .. } => CONFIG_FILE,
+        }
+    }
+}
+
+// All the errors of the configuration, reported at once.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.0 {
+            writeln!(f, "  {}", error)?;
+        }
+        Ok(())
     }
+}
+
+// Loads the configuration, panicking with all the errors when invalid.
+pub fn load() -> Config {
+    try_load().unwrap_or_else(|e| panic!("Invalid configuration:\n{e}"))
+}
 
-    let jwt_secret = env_get("JWT_SECRET");
+// Loads the configuration from the environment, the `.env` file and the `CONFIG_FILE` config file.
+pub fn try_load() -> Result<Config, ConfigErrors> {
+    ConfigSources::read().load()
+}
+
+// Builds the configuration from the environment variables and the config file of `CONFIG_FILE`.
+pub fn from_env(env: HashMap<String, String>) -> Result<Config, ConfigErrors> {
+    ConfigSources {
+        env,
+        env_file: HashMap::new(),
+    }
+    .load()
+}
+
+/// The variables the configuration is built from: the environment of the process and the `.env` file.
+///
+/// The `.env` file is read into a layer of its own instead of the environment, so the settings
+/// it sets can be changed by the config file and by a reload.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSources {
+    pub env: HashMap<String, String>,
+    pub env_file: HashMap<String, String>,
+}
+
+impl ConfigSources {
+    // Reads the environment and the `.env` file, `.env_test` in the tests.
+    pub fn read() -> Self {
+        let env_file = if std::env::var("ENV_TEST").is_ok_and(|v| v == "1") {
+            ".env_test"
+        } else {
+            ".env"
+        };
+        Self::with_env_file(env_file)
+    }
+
+    pub fn with_env_file(path: &str) -> Self {
+        let env_file = dotenvy::from_filename_iter(path).map_or_else(
+            |_| {
+                tracing::info!("{} file not found, using existing environment", path);
+                HashMap::new()
+            },
+            |vars| {
+                tracing::info!("{} file loaded", path);
+                vars.filter_map(Result::ok).collect()
+            },
+        );
+        Self {
+            env: std::env::vars().collect(),
+            env_file,
+        }
+    }
+
+    pub fn load(self) -> Result<Config, ConfigErrors> {
+        build(self)
+    }
+
+    // The path of the config file, empty when none.
+    pub fn config_file(&self) -> String {
+        self.var(CONFIG_FILE).unwrap_or_default()
+    }
+
+    fn var(&self, key: &str) -> Option<String> {
+        self.env
+            .get(key)
+            .or_else(|| self.env_file.get(key))
+            .cloned()
+    }
+}
+
+// Builds the configuration from the layers, by increasing priority: the defaults, the `.env` file,
+// the TOML or YAML config file of `CONFIG_FILE`, then the environment variables.
+// A `<KEY>_FILE` variable reads the value of `<KEY>` from a file, e.g. a mounted secret.
+fn build(sources: ConfigSources) -> Result<Config, ConfigErrors> {
+    // The `RUST_LOG` filter of the environment is the default log level.
+    let default_log_level = sources
+        .var("RUST_LOG")
+        .unwrap_or_else(|| "axum_web=trace".to_owned());
+    let mut source = Source::new(sources);
 
     // Parse configuration.
+    let jwt_secret = source.required("JWT_SECRET");
     let config = Config {
-        service_host: env_get("SERVICE_HOST"),
-        service_port: env_parse("SERVICE_PORT"),
-        redis_host: env_get("REDIS_HOST"),
-        redis_port: env_parse("REDIS_PORT"),
-        postgres_user: env_get("POSTGRES_USER"),
-        postgres_password: env_get("POSTGRES_PASSWORD"),
-        postgres_host: env_get("POSTGRES_HOST"),
-        postgres_port: env_parse("POSTGRES_PORT"),
-        postgres_db: env_get("POSTGRES_DB"),
-        postgres_connection_pool: env_parse("POSTGRES_CONNECTION_POOL"),
+        service_host: source.string("SERVICE_HOST", "127.0.0.1"),
+        service_port: source.parse("SERVICE_PORT", "8080"),
+        cors_allowed_origins: source.string("CORS_ALLOWED_ORIGINS", "*"),
+        redis_host: source.string("REDIS_HOST", "127.0.0.1"),
+        redis_port: source.parse("REDIS_PORT", "6379"),
+        redis_mode: source.string("REDIS_MODE", REDIS_MODE_STANDALONE),
+        redis_nodes: source.string("REDIS_NODES", ""),
+        redis_sentinel_master: source.string("REDIS_SENTINEL_MASTER", "mymaster"),
+        redis_username: source.string("REDIS_USERNAME", ""),
+        redis_password: source.string("REDIS_PASSWORD", ""),
+        redis_db: source.parse("REDIS_DB", "0"),
+        redis_tls: source.parse("REDIS_TLS", "false"),
+        postgres_user: source.required("POSTGRES_USER"),
+        postgres_password: source.required("POSTGRES_PASSWORD"),
+        postgres_host: source.string("POSTGRES_HOST", "127.0.0.1"),
+        postgres_port: source.parse("POSTGRES_PORT", "5432"),
+        postgres_db: source.required("POSTGRES_DB"),
+        postgres_connection_pool: source.parse("POSTGRES_CONNECTION_POOL", "5"),
         jwt_keys: JwtKeys::new(jwt_secret.as_bytes()),
         jwt_secret,
-        jwt_expire_access_token_seconds: env_parse("JWT_EXPIRE_ACCESS_TOKEN_SECONDS"),
-        jwt_expire_refresh_token_seconds: env_parse("JWT_EXPIRE_REFRESH_TOKEN_SECONDS"),
-        jwt_validation_leeway_seconds: env_parse("JWT_VALIDATION_LEEWAY_SECONDS"),
-        jwt_enable_revoked_tokens: env_parse("JWT_ENABLE_REVOKED_TOKENS"),
+        jwt_expire_access_token_seconds: source.parse("JWT_EXPIRE_ACCESS_TOKEN_SECONDS", "3600"),
+        jwt_expire_refresh_token_seconds: source
+            .parse("JWT_EXPIRE_REFRESH_TOKEN_SECONDS", "7776000"),
+        jwt_validation_leeway_seconds: source.parse("JWT_VALIDATION_LEEWAY_SECONDS", "60"),
+        jwt_enable_revoked_tokens: source.parse("JWT_ENABLE_REVOKED_TOKENS", "true"),
+        token_store: source.string("TOKEN_STORE", TOKEN_STORE_REDIS),
+        note_encryption_enabled: source.parse("NOTE_ENCRYPTION_ENABLED", "false"),
+        master_key_file: source.string("MASTER_KEY_FILE", ""),
+        master_key_id: source.string("MASTER_KEY_ID", ""),
+        webhook_allowed_hosts: source.string("WEBHOOK_ALLOWED_HOSTS", ""),
+        jobs_enabled: source.parse("JOBS_ENABLED", "true"),
+        job_token_cleanup_schedule: source.string("JOB_TOKEN_CLEANUP_SCHEDULE", "*/15 * * * *"),
+        job_purge_schedule: source.string("JOB_PURGE_SCHEDULE", "30 3 * * *"),
+        job_stats_rollup_schedule: source.string("JOB_STATS_ROLLUP_SCHEDULE", "0 4 * * *"),
+        job_purge_retention_days: source.parse("JOB_PURGE_RETENTION_DAYS", "30"),
+        task_workers: source.parse("TASK_WORKERS", "4"),
+        task_max_attempts: source.parse("TASK_MAX_ATTEMPTS", "5"),
+        task_visibility_timeout_seconds: source.parse("TASK_VISIBILITY_TIMEOUT_SECONDS", "300"),
+        event_sinks: source.string("EVENT_SINKS", ""),
+        log_format: source.string("LOG_FORMAT", "text"),
+        log_level: source.string("LOG_LEVEL", &default_log_level),
+        metrics_addr: source.string("METRICS_ADDR", ""),
+        metrics_token: source.string("METRICS_TOKEN", ""),
+        otel_exporter_otlp_endpoint: source.string("OTEL_EXPORTER_OTLP_ENDPOINT", ""),
+        otel_service_name: source.string("OTEL_SERVICE_NAME", env!("CARGO_PKG_NAME")),
     };
 
+    // The checks of the values that already failed to parse are skipped.
+    let mut errors = source.finish();
+    for error in validate(&config) {
+        if !errors.iter().any(|e| e.key() == error.key()) {
+            errors.push(error);
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ConfigErrors(errors));
+    }
+
     tracing::trace!("configuration: {:#?}", config);
-    config
+    Ok(config)
+}
+
+// Checks the values depending on each other or on a format.
+fn validate(config: &Config) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    let mut check = |valid: bool, key: &str, reason: &str| {
+        if !valid {
+            errors.push(ConfigError::Invalid {
+                key: key.to_owned(),
+                reason: reason.to_owned(),
+            });
+        }
+    };
+
+    let service_addr = format!("{}:{}", config.service_host, config.service_port);
+    check(
+        service_addr.parse::<SocketAddr>().is_ok(),
+        "SERVICE_HOST",
+        "expected an IP address",
+    );
+    check(
+        [
+            REDIS_MODE_STANDALONE,
+            REDIS_MODE_SENTINEL,
+            REDIS_MODE_CLUSTER,
+        ]
+        .iter()
+        .any(|mode| config.redis_mode.eq_ignore_ascii_case(mode)),
+        "REDIS_MODE",
+        "expected `standalone`, `sentinel` or `cluster`",
+    );
+    check(
+        config.redis_nodes.trim().is_empty()
+            || config.redis_node_addrs().len() == config.redis_nodes.split(',').count(),
+        "REDIS_NODES",
+        "expected comma-separated host:port addresses",
+    );
+    check(
+        config.redis_db >= 0,
+        "REDIS_DB",
+        "expected a non-negative database number",
+    );
+    check(
+        config.redis_db == 0 || !config.redis_mode.eq_ignore_ascii_case(REDIS_MODE_CLUSTER),
+        "REDIS_DB",
+        "expected 0 in cluster mode",
+    );
+    check(
+        !config.redis_sentinel_master.is_empty()
+            || !config.redis_mode.eq_ignore_ascii_case(REDIS_MODE_SENTINEL),
+        "REDIS_SENTINEL_MASTER",
+        "required in sentinel mode",
+    );
+    check(
+        config.postgres_connection_pool > 0,
+        "POSTGRES_CONNECTION_POOL",
+        "expected at least one connection",
+    );
+    check(
+        config.jwt_expire_access_token_seconds > 0,
+        "JWT_EXPIRE_ACCESS_TOKEN_SECONDS",
+        "expected a positive duration",
+    );
+    check(
+        config.jwt_expire_refresh_token_seconds > 0,
+        "JWT_EXPIRE_REFRESH_TOKEN_SECONDS",
+        "expected a positive duration",
+    );
+    check(
+        config.jwt_validation_leeway_seconds >= 0,
+        "JWT_VALIDATION_LEEWAY_SECONDS",
+        "expected a non-negative duration",
+    );
+    check(
+        [TOKEN_STORE_REDIS, TOKEN_STORE_POSTGRES, TOKEN_STORE_MEMORY]
+            .iter()
+            .any(|store| config.token_store.eq_ignore_ascii_case(store)),
+        "TOKEN_STORE",
+        "expected `redis`, `postgres` or `memory`",
+    );
+    check(
+        !config.note_encryption_enabled || !config.master_key_file.is_empty(),
+        "MASTER_KEY_FILE",
+        "required when the note encryption is enabled",
+    );
+    for (key, schedule) in [
+        (
+            "JOB_TOKEN_CLEANUP_SCHEDULE",
+            &config.job_token_cleanup_schedule,
+        ),
+        ("JOB_PURGE_SCHEDULE", &config.job_purge_schedule),
+        (
+            "JOB_STATS_ROLLUP_SCHEDULE",
+            &config.job_stats_rollup_schedule,
+        ),
+    ] {
+        check(
+            schedule.parse::<CronSchedule>().is_ok(),
+            key,
+            "expected a cron schedule, e.g. `*/15 * * * *`",
+        );
+    }
+    check(
+        config.job_purge_retention_days > 0,
+        "JOB_PURGE_RETENTION_DAYS",
+        "expected a positive number of days",
+    );
+    check(
+        config.task_max_attempts > 0,
+        "TASK_MAX_ATTEMPTS",
+        "expected at least one attempt",
+    );
+    check(
+        config.task_visibility_timeout_seconds > 0,
+        "TASK_VISIBILITY_TIMEOUT_SECONDS",
+        "expected a positive duration",
+    );
+    check(
+        config
+            .event_sinks
+            .split(',')
+            .map(str::trim)
+            .filter(|sink| !sink.is_empty())
+            .all(|sink| sink.eq_ignore_ascii_case(EVENT_SINK_LOG)),
+        "EVENT_SINKS",
+        "expected a comma separated list of `log`",
+    );
+    check(
+        ["text", "json"]
+            .iter()
+            .any(|format| config.log_format.eq_ignore_ascii_case(format)),
+        "LOG_FORMAT",
+        "expected `text` or `json`",
+    );
+    check(
+        EnvFilter::try_new(&config.log_level).is_ok(),
+        "LOG_LEVEL",
+        "expected a log filter, e.g. `axum_web=info`",
+    );
+    check(
+        config
+            .cors_allowed_origins
+            .split(',')
+            .map(str::trim)
+            .all(|origin| {
+                origin == "*" || origin.starts_with("http://") || origin.starts_with("https://")
+            }),
+        "CORS_ALLOWED_ORIGINS",
+        "expected `*` or comma-separated origins, e.g. https://example.com",
+    );
+    check(
+        config.metrics_addr.is_empty() || config.metrics_addr.parse::<SocketAddr>().is_ok(),
+        "METRICS_ADDR",
+        "expected a socket address, e.g. 127.0.0.1:9100",
+    );
+    let endpoint = &config.otel_exporter_otlp_endpoint;
+    check(
+        endpoint.is_empty() || endpoint.starts_with("http://") || endpoint.starts_with("https://"),
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "expected an http or https URL",
+    );
+    errors
+}
+
+// The configuration values by key, collecting the errors instead of failing on the first one.
+struct Source {
+    env: HashMap<String, String>,
+    file: BTreeMap<String, String>,
+    env_file: HashMap<String, String>,
+    keys: BTreeSet<String>,
+    errors: Vec<ConfigError>,
+}
+
+impl Source {
+    fn new(sources: ConfigSources) -> Self {
+        let config_file = sources.config_file();
+        let mut source = Self {
+            env: sources.env,
+            file: BTreeMap::new(),
+            env_file: sources.env_file,
+            keys: BTreeSet::new(),
+            errors: Vec::new(),
+        };
+        if !config_file.is_empty() {
+            match read_config_file(Path::new(&config_file)) {
+                Ok(values) => {
+                    tracing::info!("{} config file loaded", config_file);
+                    source.file = values;
+                }
+                Err(reason) => source.errors.push(ConfigError::File {
+                    path: config_file,
+                    reason,
+                }),
+            }
+        }
+        source
+    }
+
+    // Value of the key, from the environment first, then from the config file and the `.env` file.
+    fn get(&mut self, key: &str) -> Option<String> {
+        self.keys.insert(key.to_owned());
+        let file_key = format!("{}{}", key, FILE_SUFFIX);
+        if let Some(value) = self.env.get(key) {
+            return Some(value.clone());
+        }
+        if let Some(path) = self.env.get(&file_key).cloned() {
+            return self.read_secret(key, &path);
+        }
+        if let Some(value) = self.file.get(key) {
+            return Some(value.clone());
+        }
+        if let Some(path) = self.file.get(&file_key).cloned() {
+            return self.read_secret(key, &path);
+        }
+        if let Some(value) = self.env_file.get(key) {
+            return Some(value.clone());
+        }
+        if let Some(path) = self.env_file.get(&file_key).cloned() {
+            return self.read_secret(key, &path);
+        }
+        None
+    }
+
+    fn read_secret(&mut self, key: &str, path: &str) -> Option<String> {
+        match fs::read_to_string(path) {
+            Ok(value) => Some(value.trim_end_matches(['\r', '\n']).to_owned()),
+            Err(e) => {
+                self.errors.push(ConfigError::Invalid {
+                    key: format!("{}{}", key, FILE_SUFFIX),
+                    reason: format!("cannot read {}: {}", path, e),
+                });
+                None
+            }
+        }
+    }
+
+    fn string(&mut self, key: &str, default: &str) -> String {
+        self.get(key).unwrap_or_else(|| default.to_owned())
+    }
+
+    fn required(&mut self, key: &str) -> String {
+        match self.get(key).filter(|value| !value.is_empty()) {
+            Some(value) => value,
+            None => {
+                self.errors.push(ConfigError::Missing(key.to_owned()));
+                String::new()
+            }
+        }
+    }
+
+    fn parse<T>(&mut self, key: &str, default: &str) -> T
+    where
+        T: std::str::FromStr + Default,
+        T::Err: fmt::Display,
+    {
+        let value = self
+            .get(key)
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| default.to_owned());
+        value.trim().parse().unwrap_or_else(|e: T::Err| {
+            self.errors.push(ConfigError::Invalid {
+                key: key.to_owned(),
+                reason: e.to_string(),
+            });
+            T::default()
+        })
+    }
+
+    // The errors, including the keys of the config file that are not settings.
+    fn finish(mut self) -> Vec<ConfigError> {
+        for key in self.file.keys() {
+            let setting = key.strip_suffix(FILE_SUFFIX).unwrap_or(key);
+            if !self.keys.contains(key) && !self.keys.contains(setting) {
+                self.errors.push(ConfigError::UnknownKey(key.clone()));
+            }
+        }
+        self.errors
+    }
+}
+
+// Reads a TOML or YAML config file into keys named like the environment variables.
+// The tables are flattened, `[postgres] host` is the `POSTGRES_HOST` key.
+fn read_config_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| e.to_string())?,
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).map_err(|e| e.to_string())?,
+        _ => return Err("expected a .toml, .yaml or .yml file".to_owned()),
+    };
+
+    let mut values = BTreeMap::new();
+    flatten("", &value, &mut values)?;
+    Ok(values)
+}
+
+fn flatten(
+    prefix: &str,
+    value: &serde_json::Value,
+    values: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    use serde_json::Value;
+    let scalar = match value {
+        Value::Object(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.to_uppercase()
+                } else {
+                    format!("{}_{}", prefix, key.to_uppercase())
+                };
+                flatten(&key, value, values)?;
+            }
+            return Ok(());
+        }
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(_) => return Err(format!("{}: arrays are not supported", prefix)),
+    };
+    values.insert(prefix.to_owned(), scalar);
+    Ok(())
 }
 
 impl From<Config> for PostgresOptions {
@@ -139,35 +798,3 @@ impl From<Config> for DatabaseOptions {
         }
     }
 }
-
-#[inline]
-fn env_get(key: &str) -> String {
-    match std::env::var(key) {
-        Ok(v) => v,
-        Err(e) => {
-            let msg = format!("{} {}", key, e);
-            tracing::error!(msg);
-            panic!("{msg}");
-        }
-    }
-}
-
-#[inline]
-fn env_get_or(key: &str, default: &str) -> String {
-    if let Ok(v) = std::env::var(key) {
-        return v;
-    }
-    default.to_owned()
-}
-
-#[inline]
-fn env_parse<T: std::str::FromStr>(key: &str) -> T {
-    env_get(key).parse().map_or_else(
-        |_| {
-            let msg = format!("Failed to parse: {}", key);
-            tracing::error!(msg);
-            panic!("{msg}");
-        },
-        |v| v,
-    )
-}
@@ -3,6 +3,126 @@ pub const USER_ROLE_ADMIN: &str = "admin";
 pub const USER_ROLE_USER: &str = "user";
 
 // JWT related constants.
-pub const JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY: &str = "jwt.revoke.global.before";
-pub const JWT_REDIS_REVOKE_USER_BEFORE_KEY: &str = "jwt.revoke.user.before";
-pub const JWT_REDIS_REVOKED_TOKENS_KEY: &str = "jwt.revoked.tokens";
+// The revocation keys share the `{jwt.revoke}` hash tag to be in the same Redis Cluster slot.
+pub const JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY: &str = "{jwt.revoke}.global.before";
+pub const JWT_REDIS_REVOKE_USER_BEFORE_KEY: &str = "{jwt.revoke}.user.before";
+// The revoked tokens, one key per JWT ID expiring with the token.
+pub const JWT_REDIS_REVOKED_TOKEN_KEY_PREFIX: &str = "{jwt.revoke}.token";
+// The hash of the revoked tokens of the previous versions, migrated to the keys at startup.
+pub const JWT_REDIS_REVOKED_TOKENS_KEY: &str = "{jwt.revoke}.tokens";
+// The revocation keys before the hash tags, renamed at startup.
+pub const JWT_REDIS_LEGACY_KEYS: [(&str, &str); 3] = [
+    (
+        "jwt.revoke.global.before",
+        JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY,
+    ),
+    ("jwt.revoke.user.before", JWT_REDIS_REVOKE_USER_BEFORE_KEY),
+    ("jwt.revoked.tokens", JWT_REDIS_REVOKED_TOKENS_KEY),
+];
+
+// Token store related constants.
+pub const TOKEN_STORE_REDIS: &str = "redis";
+pub const TOKEN_STORE_POSTGRES: &str = "postgres";
+pub const TOKEN_STORE_MEMORY: &str = "memory";
+
+// Note rendering related constants.
+pub const NOTE_RENDER_REDIS_KEY_PREFIX: &str = "note.render";
+pub const NOTE_RENDER_CACHE_TTL_SECONDS: u64 = 86400;
+
+// Note import related constants.
+pub const NOTE_IMPORT_BACKGROUND_THRESHOLD: usize = 100;
+pub const NOTE_IMPORT_MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+// Note encryption at rest related constants.
+pub const NOTE_TEXT_ENCRYPTED_PREFIX: &str = "enc:v1:";
+pub const NOTE_REENCRYPTION_BATCH_SIZE: i64 = 100;
+
+// Reminder related constants.
+pub const REMINDER_POLL_INTERVAL_SECONDS: u64 = 1;
+pub const REMINDER_BATCH_SIZE: i64 = 100;
+pub const REMINDER_LOCK_SECONDS: i64 = 300;
+pub const REMINDER_DELIVERY_CONCURRENCY: usize = 10;
+pub const REMINDER_MAX_ATTEMPTS: i32 = 5;
+pub const REMINDER_RETRY_DELAY_SECONDS: i64 = 30;
+pub const REMINDER_DEFAULT_SNOOZE_MINUTES: i64 = 10;
+pub const REMINDER_WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+pub const REMINDER_EVENTS_CHANNEL_CAPACITY: usize = 256;
+pub const REMINDER_EVENTS_NOTIFY_CHANNEL: &str = "reminder_events";
+pub const REMINDER_EVENTS_LISTEN_RETRY_SECONDS: u64 = 1;
+
+// Note comment related constants.
+pub const COMMENT_DEFAULT_PAGE_SIZE: i64 = 20;
+pub const COMMENT_MAX_PAGE_SIZE: i64 = 100;
+pub const COMMENT_MAX_LENGTH: usize = 10_000;
+
+// Maintenance job related constants.
+pub const JOB_POLL_INTERVAL_SECONDS: u64 = 10;
+// The first key of the advisory locks of the jobs, the second key is the hash of the job name.
+pub const JOB_ADVISORY_LOCK_CLASS: i32 = 0x4a4f42;
+pub const JOB_RUNS_DEFAULT_LIMIT: i64 = 20;
+pub const JOB_RUNS_MAX_LIMIT: i64 = 100;
+
+// Task queue related constants.
+pub const TASK_POLL_INTERVAL_MILLIS: u64 = 500;
+// The delay before the retry of a failed task doubles with every attempt, up to the maximum.
+pub const TASK_RETRY_BASE_DELAY_SECONDS: i64 = 2;
+pub const TASK_RETRY_MAX_DELAY_SECONDS: i64 = 3600;
+pub const TASK_DEFAULT_LIMIT: i64 = 20;
+pub const TASK_MAX_LIMIT: i64 = 100;
+
+// Domain event related constants.
+pub const EVENT_OUTBOX_POLL_INTERVAL_MILLIS: u64 = 500;
+pub const EVENT_OUTBOX_BATCH_SIZE: i64 = 100;
+// The delay before the next dispatch of a failed event doubles with every attempt, up to the maximum.
+pub const EVENT_RETRY_BASE_DELAY_SECONDS: i64 = 1;
+pub const EVENT_RETRY_MAX_DELAY_SECONDS: i64 = 300;
+// The event failing its last attempt is failed, it is not dispatched again.
+pub const EVENT_MAX_ATTEMPTS: i32 = 10;
+pub const EVENT_SINK_LOG: &str = "log";
+
+// Webhook related constants.
+pub const WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+// The response bodies are kept in the delivery log up to this length, in characters.
+pub const WEBHOOK_RESPONSE_BODY_MAX_LENGTH: usize = 1024;
+pub const WEBHOOK_SECRET_MIN_LENGTH: usize = 16;
+pub const WEBHOOK_DELIVERIES_DEFAULT_LIMIT: i64 = 20;
+pub const WEBHOOK_DELIVERIES_MAX_LIMIT: i64 = 100;
+pub const WEBHOOK_HEADER_ID: &str = "X-Webhook-Id";
+pub const WEBHOOK_HEADER_EVENT: &str = "X-Webhook-Event";
+pub const WEBHOOK_HEADER_TIMESTAMP: &str = "X-Webhook-Timestamp";
+pub const WEBHOOK_HEADER_SIGNATURE: &str = "X-Webhook-Signature";
+
+// Stats related constants.
+pub const STATS_TIMESERIES_MAX_POINTS: i64 = 366;
+
+// Metrics related constants.
+pub const METRICS_HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+pub const METRICS_HTTP_REQUEST_DURATION: &str = "http_request_duration_seconds";
+pub const METRICS_AUTH_LOGINS_TOTAL: &str = "auth_logins_total";
+pub const METRICS_AUTH_TOKENS_ISSUED_TOTAL: &str = "auth_tokens_issued_total";
+pub const METRICS_AUTH_TOKENS_REVOKED_TOTAL: &str = "auth_tokens_revoked_total";
+pub const METRICS_DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+pub const METRICS_DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+pub const METRICS_REDIS_COMMAND_DURATION: &str = "redis_command_duration_seconds";
+
+// Logging related constants.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const REQUEST_ID_MAX_LENGTH: usize = 128;
+
+// Health related constants.
+pub const HEALTH_CHECK_TIMEOUT_MILLIS: u64 = 2000;
+
+// Redis related constants.
+pub const REDIS_MODE_STANDALONE: &str = "standalone";
+pub const REDIS_MODE_SENTINEL: &str = "sentinel";
+pub const REDIS_MODE_CLUSTER: &str = "cluster";
+pub const REDIS_CONNECTION_TIMEOUT_MILLIS: u64 = 1000;
+pub const REDIS_RESPONSE_TIMEOUT_MILLIS: u64 = 1000;
+pub const REDIS_RECONNECT_RETRIES: usize = 3;
+pub const REDIS_RECONNECT_FACTOR: u64 = 2;
+pub const REDIS_RECONNECT_MAX_DELAY_MILLIS: u64 = 500;
+pub const REDIS_RECONNECT_INITIAL_DELAY_MILLIS: u64 = 100;
+pub const REDIS_RECONNECT_INTERVAL_MAX_MILLIS: u64 = 5000;
+
+// Configuration related constants.
+pub const CONFIG_WATCH_INTERVAL_SECONDS: u64 = 5;
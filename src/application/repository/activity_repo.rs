@@ -0,0 +1,70 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::stats::{ActivityKind, TimeseriesPoint, TimeseriesQuery},
+    infrastructure::database::DatabaseConnection,
+};
+
+// Records the activity of the user, in the transaction of the change.
+#[tracing::instrument(name = "activity_repo.record", skip_all, fields(db.system = "postgresql"))]
+pub async fn record(
+    user_id: Uuid,
+    kind: ActivityKind,
+    occurred_at: NaiveDateTime,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    tracing::trace!("user_id: {}, activity: {:?}", user_id, kind);
+    sqlx::query(
+        r#"INSERT INTO activity_events (id, user_id, kind, occurred_at)
+         VALUES ($1,$2,$3,$4)"#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(kind)
+    .bind(occurred_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// Counts the events of the metric per bucket. The range is widened to whole buckets
+// and the buckets without events are filled with zeros.
+#[tracing::instrument(name = "activity_repo.timeseries", skip_all, fields(db.system = "postgresql"))]
+pub async fn timeseries(
+    query: &TimeseriesQuery,
+    state: &SharedState,
+) -> RepositoryResult<Vec<TimeseriesPoint>> {
+    let points = sqlx::query_as::<_, TimeseriesPoint>(
+        r#"WITH series AS (
+             SELECT generate_series(
+               date_trunc($1, $2::timestamp),
+               date_trunc($1, $3::timestamp),
+               ('1 ' || $1)::interval) AS bucket
+         ),
+         counts AS (
+             SELECT date_trunc($1, occurred_at) AS bucket,
+             CASE WHEN $4 THEN COUNT(DISTINCT user_id) ELSE COUNT(*) END AS value
+             FROM activity_events
+             WHERE kind = ANY($5)
+             AND occurred_at >= date_trunc($1, $2::timestamp)
+             AND occurred_at < date_trunc($1, $3::timestamp) + ('1 ' || $1)::interval
+             AND ($6::uuid IS NULL OR user_id = $6)
+             GROUP BY 1
+         )
+         SELECT series.bucket::date AS bucket, COALESCE(counts.value, 0) AS value
+         FROM series
+         LEFT JOIN counts ON counts.bucket = series.bucket
+         ORDER BY series.bucket"#,
+    )
+    .bind(query.bucket.as_str())
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.metric.is_distinct_users())
+    .bind(query.metric.kinds())
+    .bind(query.user_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(points)
+}
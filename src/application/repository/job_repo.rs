@@ -0,0 +1,139 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        constants::JOB_ADVISORY_LOCK_CLASS, repository::RepositoryResult, state::SharedState,
+    },
+    domain::models::job::{JobName, JobRun, JobRunStatus},
+    infrastructure::database::DatabaseConnection,
+};
+
+// Takes the advisory lock of the job until the end of the transaction,
+// false when another instance holds it.
+#[tracing::instrument(name = "job_repo.try_lock", skip_all, fields(db.system = "postgresql"))]
+pub async fn try_lock(job: JobName, conn: &mut DatabaseConnection) -> RepositoryResult<bool> {
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1, hashtext($2))")
+        .bind(JOB_ADVISORY_LOCK_CLASS)
+        .bind(job.as_str())
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(locked)
+}
+
+// Records the start of a run, none when the scheduled occurrence already ran.
+#[tracing::instrument(name = "job_repo.start", skip_all, fields(db.system = "postgresql"))]
+pub async fn start(run: JobRun, state: &SharedState) -> RepositoryResult<Option<JobRun>> {
+    let run = sqlx::query_as::<_, JobRun>(
+        r#"INSERT INTO job_runs (id,
+         job,
+         trigger,
+         status,
+         scheduled_at,
+         instance,
+         started_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7)
+         ON CONFLICT (job, scheduled_at) DO NOTHING
+         RETURNING job_runs.*"#,
+    )
+    .bind(run.id)
+    .bind(run.job)
+    .bind(run.trigger)
+    .bind(run.status)
+    .bind(run.scheduled_at)
+    .bind(run.instance)
+    .bind(run.started_at)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(run)
+}
+
+#[tracing::instrument(name = "job_repo.finish", skip_all, fields(db.system = "postgresql"))]
+pub async fn finish(run: &JobRun, state: &SharedState) -> RepositoryResult<JobRun> {
+    let run = sqlx::query_as::<_, JobRun>(
+        r#"UPDATE job_runs
+         SET
+         status = $1,
+         processed = $2,
+         error = $3,
+         finished_at = $4
+         WHERE id = $5
+         RETURNING job_runs.*"#,
+    )
+    .bind(run.status)
+    .bind(run.processed)
+    .bind(&run.error)
+    .bind(run.finished_at)
+    .bind(run.id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(run)
+}
+
+// Fails the runs left running by a stopped instance, called with the lock of the job held.
+#[tracing::instrument(name = "job_repo.fail_interrupted", skip_all, fields(db.system = "postgresql"))]
+pub async fn fail_interrupted(job: JobName, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result = sqlx::query(
+        r#"UPDATE job_runs
+         SET
+         status = $1,
+         error = $2,
+         finished_at = $3
+         WHERE job = $4 AND status = $5"#,
+    )
+    .bind(JobRunStatus::Failed)
+    .bind("interrupted")
+    .bind(Utc::now().naive_utc())
+    .bind(job)
+    .bind(JobRunStatus::Running)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(query_result.rows_affected())
+}
+
+#[tracing::instrument(name = "job_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<JobRun> {
+    let run = sqlx::query_as::<_, JobRun>("SELECT * FROM job_runs WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(run)
+}
+
+// The last run of every job that ran.
+#[tracing::instrument(name = "job_repo.last_runs", skip_all, fields(db.system = "postgresql"))]
+pub async fn last_runs(state: &SharedState) -> RepositoryResult<Vec<JobRun>> {
+    let runs = sqlx::query_as::<_, JobRun>(
+        "SELECT DISTINCT ON (job) * FROM job_runs ORDER BY job, started_at DESC",
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(runs)
+}
+
+// The last runs of the job, the most recent first.
+#[tracing::instrument(name = "job_repo.list_by_job", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_job(
+    job: JobName,
+    limit: i64,
+    state: &SharedState,
+) -> RepositoryResult<Vec<JobRun>> {
+    let runs = sqlx::query_as::<_, JobRun>(
+        "SELECT * FROM job_runs WHERE job = $1 ORDER BY started_at DESC LIMIT $2",
+    )
+    .bind(job)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(runs)
+}
+
+// Deletes the runs finished before the given time.
+#[tracing::instrument(name = "job_repo.purge_finished", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_finished(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result = sqlx::query("DELETE FROM job_runs WHERE finished_at < $1")
+        .bind(before)
+        .execute(&state.db_pool)
+        .await?;
+    Ok(query_result.rows_affected())
+}
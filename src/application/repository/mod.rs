@@ -1,5 +1,20 @@
+pub mod activity_repo;
+pub mod job_repo;
+pub mod note_comment_repo;
+pub mod note_flags_repo;
+pub mod note_import_repo;
+pub mod note_key_repo;
+pub mod note_link_repo;
 pub mod note_repo;
+pub mod note_template_repo;
+pub mod outbox_repo;
+pub mod reminder_repo;
 pub mod stats_repo;
+pub mod task_repo;
+pub mod tenant_key_repo;
+pub mod user_key_repo;
 pub mod user_repo;
+pub mod webhook_delivery_repo;
+pub mod webhook_repo;
 
 pub type RepositoryResult<T> = Result<T, sqlx::Error>;
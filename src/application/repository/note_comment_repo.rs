@@ -0,0 +1,177 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_comment::NoteComment,
+};
+
+#[tracing::instrument(name = "note_comment_repo.count_threads", skip_all, fields(db.system = "postgresql"))]
+pub async fn count_threads(
+    note_id: Uuid,
+    resolved: Option<bool>,
+    state: &SharedState,
+) -> RepositoryResult<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM note_comments
+         WHERE note_id = $1 AND parent_id IS NULL
+         AND ($2::BOOLEAN IS NULL OR (resolved_at IS NOT NULL) = $2)"#,
+    )
+    .bind(note_id)
+    .bind(resolved)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(count)
+}
+
+// Lists a page of the root comments of the note, oldest first.
+#[tracing::instrument(name = "note_comment_repo.list_threads", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_threads(
+    note_id: Uuid,
+    resolved: Option<bool>,
+    limit: i64,
+    offset: i64,
+    state: &SharedState,
+) -> RepositoryResult<Vec<NoteComment>> {
+    let comments = sqlx::query_as::<_, NoteComment>(
+        r#"SELECT * FROM note_comments
+         WHERE note_id = $1 AND parent_id IS NULL
+         AND ($2::BOOLEAN IS NULL OR (resolved_at IS NOT NULL) = $2)
+         ORDER BY created_at, id
+         LIMIT $3 OFFSET $4"#,
+    )
+    .bind(note_id)
+    .bind(resolved)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(comments)
+}
+
+#[tracing::instrument(name = "note_comment_repo.list_replies", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_replies(
+    thread_ids: &[Uuid],
+    state: &SharedState,
+) -> RepositoryResult<Vec<NoteComment>> {
+    let comments = sqlx::query_as::<_, NoteComment>(
+        r#"SELECT * FROM note_comments
+         WHERE thread_id = ANY($1) AND parent_id IS NOT NULL
+         ORDER BY created_at, id"#,
+    )
+    .bind(thread_ids)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(comments)
+}
+
+#[tracing::instrument(name = "note_comment_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(
+    note_id: Uuid,
+    id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<NoteComment> {
+    let comment = sqlx::query_as::<_, NoteComment>(
+        "SELECT * FROM note_comments WHERE id = $1 AND note_id = $2",
+    )
+    .bind(id)
+    .bind(note_id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(comment)
+}
+
+#[tracing::instrument(name = "note_comment_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(comment: NoteComment, state: &SharedState) -> RepositoryResult<NoteComment> {
+    let time_now = Utc::now().naive_utc();
+    let comment = sqlx::query_as::<_, NoteComment>(
+        r#"INSERT INTO note_comments (id,
+         note_id,
+         thread_id,
+         parent_id,
+         user_id,
+         text,
+         mentions,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+         RETURNING note_comments.*"#,
+    )
+    .bind(comment.id)
+    .bind(comment.note_id)
+    .bind(comment.thread_id)
+    .bind(comment.parent_id)
+    .bind(comment.user_id)
+    .bind(comment.text)
+    .bind(comment.mentions)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(comment)
+}
+
+#[tracing::instrument(name = "note_comment_repo.update_text", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_text(
+    id: Uuid,
+    text: &str,
+    mentions: &[Uuid],
+    state: &SharedState,
+) -> RepositoryResult<NoteComment> {
+    let time_now = Utc::now().naive_utc();
+    let comment = sqlx::query_as::<_, NoteComment>(
+        r#"UPDATE note_comments
+         SET
+         text = $1,
+         mentions = $2,
+         edited_at = $3,
+         updated_at = $3
+         WHERE id = $4
+         RETURNING note_comments.*"#,
+    )
+    .bind(text)
+    .bind(mentions)
+    .bind(time_now)
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(comment)
+}
+
+// Resolves the thread by the given user, or reopens it without a user.
+#[tracing::instrument(name = "note_comment_repo.set_resolved", skip_all, fields(db.system = "postgresql"))]
+pub async fn set_resolved(
+    id: Uuid,
+    resolved_by: Option<Uuid>,
+    state: &SharedState,
+) -> RepositoryResult<NoteComment> {
+    let time_now = Utc::now().naive_utc();
+    let resolved_at: Option<NaiveDateTime> = resolved_by.map(|_| time_now);
+    let comment = sqlx::query_as::<_, NoteComment>(
+        r#"UPDATE note_comments
+         SET
+         resolved_at = $1,
+         resolved_by = $2,
+         updated_at = $3
+         WHERE id = $4
+         RETURNING note_comments.*"#,
+    )
+    .bind(resolved_at)
+    .bind(resolved_by)
+    .bind(time_now)
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(comment)
+}
+
+// Deletes the comment with its replies.
+#[tracing::instrument(name = "note_comment_repo.delete", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query("DELETE FROM note_comments WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
@@ -0,0 +1,59 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_flags::{NoteFlags, NoteFlagsRequest},
+};
+
+// Gets the flags of the note set by the user, all flags are unset by default.
+#[tracing::instrument(name = "note_flags_repo.get", skip_all, fields(db.system = "postgresql"))]
+pub async fn get(note_id: Uuid, user_id: Uuid, state: &SharedState) -> RepositoryResult<NoteFlags> {
+    let note_flags = sqlx::query_as::<_, NoteFlags>(
+        "SELECT * FROM note_flags WHERE note_id = $1 AND user_id = $2",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(note_flags.unwrap_or_else(|| NoteFlags {
+        note_id,
+        user_id,
+        ..NoteFlags::default()
+    }))
+}
+
+// Sets the given flags of the note for the user, the flags not given are kept.
+#[tracing::instrument(name = "note_flags_repo.upsert", skip_all, fields(db.system = "postgresql"))]
+pub async fn upsert(
+    note_id: Uuid,
+    user_id: Uuid,
+    request: &NoteFlagsRequest,
+    state: &SharedState,
+) -> RepositoryResult<NoteFlags> {
+    let time_now = Utc::now().naive_utc();
+    let note_flags = sqlx::query_as::<_, NoteFlags>(
+        r#"INSERT INTO note_flags (note_id,
+         user_id,
+         pinned,
+         archived,
+         favorite,
+         updated_at)
+         VALUES ($1,$2,COALESCE($3,FALSE),COALESCE($4,FALSE),COALESCE($5,FALSE),$6)
+         ON CONFLICT (note_id, user_id) DO UPDATE
+         SET pinned = COALESCE($3, note_flags.pinned),
+         archived = COALESCE($4, note_flags.archived),
+         favorite = COALESCE($5, note_flags.favorite),
+         updated_at = EXCLUDED.updated_at
+         RETURNING note_flags.*"#,
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .bind(request.pinned)
+    .bind(request.archived)
+    .bind(request.favorite)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(note_flags)
+}
@@ -0,0 +1,143 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_import::{
+        NoteImport, NoteImportArchive, NoteImportItem, NoteImportStatus,
+    },
+    infrastructure::database::DatabaseConnection,
+};
+
+#[tracing::instrument(name = "note_import_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<NoteImport> {
+    let note_import = sqlx::query_as::<_, NoteImport>("SELECT * FROM note_imports WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(note_import)
+}
+
+#[tracing::instrument(name = "note_import_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(user_id: Uuid, total: usize, state: &SharedState) -> RepositoryResult<NoteImport> {
+    let mut conn = state.db_pool.acquire().await?;
+    add_with(Uuid::new_v4(), user_id, total, &mut conn).await
+}
+
+// Adds the import in the transaction of the caller.
+#[tracing::instrument(name = "note_import_repo.add_with", skip_all, fields(db.system = "postgresql"))]
+pub async fn add_with(
+    id: Uuid,
+    user_id: Uuid,
+    total: usize,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<NoteImport> {
+    let time_now = Utc::now().naive_utc();
+    let note_import = sqlx::query_as::<_, NoteImport>(
+        r#"INSERT INTO note_imports (id,
+         user_id,
+         status,
+         total,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6)
+         RETURNING note_imports.*"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(NoteImportStatus::Pending)
+    .bind(total as i32)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(note_import)
+}
+
+#[tracing::instrument(name = "note_import_repo.add_archive", skip_all, fields(db.system = "postgresql"))]
+pub async fn add_archive(
+    archive: NoteImportArchive,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query(
+        r#"INSERT INTO note_import_archives (import_id,
+         notes,
+         encrypted_at_rest,
+         created_at)
+         VALUES ($1,$2,$3,$4)"#,
+    )
+    .bind(archive.import_id)
+    .bind(archive.notes)
+    .bind(archive.encrypted_at_rest)
+    .bind(archive.created_at.unwrap_or_else(|| Utc::now().naive_utc()))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "note_import_repo.get_archive", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_archive(
+    import_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Option<NoteImportArchive>> {
+    let archive = sqlx::query_as::<_, NoteImportArchive>(
+        "SELECT * FROM note_import_archives WHERE import_id = $1",
+    )
+    .bind(import_id)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(archive)
+}
+
+#[tracing::instrument(name = "note_import_repo.delete_archive", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete_archive(import_id: Uuid, state: &SharedState) -> RepositoryResult<()> {
+    sqlx::query("DELETE FROM note_import_archives WHERE import_id = $1")
+        .bind(import_id)
+        .execute(&state.db_pool)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "note_import_repo.update_status", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_status(
+    id: Uuid,
+    status: NoteImportStatus,
+    results: Vec<NoteImportItem>,
+    state: &SharedState,
+) -> RepositoryResult<NoteImport> {
+    let time_now = Utc::now().naive_utc();
+    let note_import = sqlx::query_as::<_, NoteImport>(
+        r#"UPDATE note_imports
+         SET
+         status = $1,
+         results = $2,
+         updated_at = $3
+         WHERE id = $4
+         RETURNING note_imports.*"#,
+    )
+    .bind(status)
+    .bind(Json(results))
+    .bind(time_now)
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    Ok(note_import)
+}
+
+// Deletes the imports completed or failed before the given time.
+#[tracing::instrument(name = "note_import_repo.purge_finished", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_finished(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result =
+        sqlx::query("DELETE FROM note_imports WHERE status IN ($1, $2) AND updated_at < $3")
+            .bind(NoteImportStatus::Completed)
+            .bind(NoteImportStatus::Failed)
+            .bind(before)
+            .execute(&state.db_pool)
+            .await?;
+
+    Ok(query_result.rows_affected())
+}
@@ -0,0 +1,85 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_key::NoteKey,
+};
+
+#[tracing::instrument(name = "note_key_repo.list_by_note", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_note(note_id: Uuid, state: &SharedState) -> RepositoryResult<Vec<NoteKey>> {
+    let note_keys = sqlx::query_as::<_, NoteKey>(
+        "SELECT * FROM note_keys WHERE note_id = $1 ORDER BY created_at",
+    )
+    .bind(note_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(note_keys)
+}
+
+#[tracing::instrument(name = "note_key_repo.get", skip_all, fields(db.system = "postgresql"))]
+pub async fn get(note_id: Uuid, user_id: Uuid, state: &SharedState) -> RepositoryResult<NoteKey> {
+    let note_key =
+        sqlx::query_as::<_, NoteKey>("SELECT * FROM note_keys WHERE note_id = $1 AND user_id = $2")
+            .bind(note_id)
+            .bind(user_id)
+            .fetch_one(&state.db_pool)
+            .await?;
+    Ok(note_key)
+}
+
+#[tracing::instrument(name = "note_key_repo.exists", skip_all, fields(db.system = "postgresql"))]
+pub async fn exists(note_id: Uuid, user_id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM note_keys WHERE note_id = $1 AND user_id = $2)",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(exists)
+}
+
+// Stores the data key of the note wrapped for the user, replacing a previous wrap.
+#[tracing::instrument(name = "note_key_repo.upsert", skip_all, fields(db.system = "postgresql"))]
+pub async fn upsert(note_key: NoteKey, state: &SharedState) -> RepositoryResult<NoteKey> {
+    let time_now = Utc::now().naive_utc();
+    let note_key = sqlx::query_as::<_, NoteKey>(
+        r#"INSERT INTO note_keys (note_id,
+         user_id,
+         wrapped_key,
+         algorithm,
+         wrapped_by,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7)
+         ON CONFLICT (note_id, user_id) DO UPDATE
+         SET wrapped_key = EXCLUDED.wrapped_key,
+         algorithm = EXCLUDED.algorithm,
+         wrapped_by = EXCLUDED.wrapped_by,
+         updated_at = EXCLUDED.updated_at
+         RETURNING note_keys.*"#,
+    )
+    .bind(note_key.note_id)
+    .bind(note_key.user_id)
+    .bind(note_key.wrapped_key)
+    .bind(note_key.algorithm)
+    .bind(note_key.wrapped_by)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    Ok(note_key)
+}
+
+#[tracing::instrument(name = "note_key_repo.delete", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete(note_id: Uuid, user_id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query("DELETE FROM note_keys WHERE note_id = $1 AND user_id = $2")
+        .bind(note_id)
+        .bind(user_id)
+        .execute(&state.db_pool)
+        .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{FromRow, query_as, query_scalar};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repository::RepositoryResult,
+        security::encryption::NoteField,
+        service::{
+            envelope_service::{self, NoteKey},
+            link_service::{self, NOTE_LINK_ID_PREFIX, WikiLink},
+        },
+        state::SharedState,
+    },
+    domain::models::{
+        note::{Note, StoredNote},
+        note_link::{NoteGraph, NoteGraphEdge, NoteGraphNode, NoteLink},
+    },
+    infrastructure::database::DatabaseConnection,
+};
+
+#[tracing::instrument(name = "note_link_repo.list_by_source", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_source(
+    source_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Vec<NoteLink>> {
+    let stored = query_as::<_, StoredNoteLink>(
+        r#"SELECT note_links.*, notes.user_id FROM note_links
+         JOIN notes ON notes.id = note_links.source_id
+         WHERE note_links.source_id = $1
+         ORDER BY note_links.target_ref"#,
+    )
+    .bind(source_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    // The targets encrypted at rest are ordered once decrypted.
+    let mut links = Vec::with_capacity(stored.len());
+    for StoredNoteLink {
+        mut link,
+        target_digest,
+        user_id,
+    } in stored
+    {
+        if target_digest.is_some() {
+            link.target_ref = envelope_service::open_field(
+                user_id,
+                link.source_id,
+                NoteField::LinkTarget,
+                &link.target_ref,
+                state,
+            )
+            .await?;
+        }
+        links.push(link);
+    }
+    links.sort_by(|a, b| a.target_ref.cmp(&b.target_ref));
+    Ok(links)
+}
+
+// A link as read from the database with the owner of its source, its target is ciphertext when
+// it has a digest.
+#[derive(FromRow)]
+struct StoredNoteLink {
+    #[sqlx(flatten)]
+    link: NoteLink,
+    target_digest: Option<String>,
+    user_id: Uuid,
+}
+
+#[tracing::instrument(name = "note_link_repo.list_backlinks", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_backlinks(target_id: Uuid, state: &SharedState) -> RepositoryResult<Vec<Note>> {
+    let notes = query_as::<_, StoredNote>(
+        r#"SELECT notes.* FROM notes
+         JOIN note_links ON note_links.source_id = notes.id
+         WHERE note_links.target_id = $1
+         ORDER BY notes.created_at"#,
+    )
+    .bind(target_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    envelope_service::open_all(notes, state).await
+}
+
+#[tracing::instrument(name = "note_link_repo.graph_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn graph_by_user(user_id: Uuid, state: &SharedState) -> RepositoryResult<NoteGraph> {
+    let stored = query_as::<_, (Uuid, Option<String>, Option<String>)>(
+        "SELECT id, title, title_digest FROM notes WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let mut nodes = Vec::with_capacity(stored.len());
+    for (id, title, title_digest) in stored {
+        let title = match (title, title_digest) {
+            (Some(title), Some(_)) => Some(
+                envelope_service::open_field(user_id, id, NoteField::Title, &title, state).await?,
+            ),
+            (title, _) => title,
+        };
+        nodes.push(NoteGraphNode { id, title });
+    }
+
+    let edges = query_as::<_, NoteGraphEdge>(
+        r#"SELECT note_links.source_id, note_links.target_id FROM note_links
+         JOIN notes source ON source.id = note_links.source_id
+         JOIN notes target ON target.id = note_links.target_id
+         WHERE source.user_id = $1 AND target.user_id = $1
+         ORDER BY note_links.source_id, note_links.target_id"#,
+    )
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(NoteGraph { nodes, edges })
+}
+
+// Replaces the outgoing links of the note with the links parsed out of its text.
+// Must be called in the same transaction as the note write. The link targets are encrypted
+// with the key of the note owner, when the encryption at rest is enabled.
+#[tracing::instrument(name = "note_link_repo.save_links", skip_all, fields(db.system = "postgresql"))]
+pub async fn save_links(
+    note: &Note,
+    key: Option<&NoteKey<'_>>,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    let time_now = Utc::now().naive_utc();
+    // The text of an encrypted note is ciphertext, it has no links.
+    let links = if note.is_encrypted() {
+        vec![]
+    } else {
+        link_service::parse_links(&note.text)
+    };
+    tracing::trace!("note links: {:?}", links);
+
+    // Links are stored by the target id, and previously resolved targets are kept,
+    // so renaming the target note does not break the links pointing to it.
+    // The targets encrypted at rest are matched by their digest.
+    let resolved: HashMap<String, Uuid> = query_as::<_, (String, Uuid)>(
+        r#"SELECT COALESCE(target_digest, target_ref), target_id FROM note_links
+         WHERE source_id = $1 AND target_id IS NOT NULL"#,
+    )
+    .bind(note.id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    sqlx::query("DELETE FROM note_links WHERE source_id = $1")
+        .bind(note.id)
+        .execute(&mut *conn)
+        .await?;
+
+    for link in links {
+        let target_ref = link.target_ref();
+        let target_digest = key.map(|key| key.ref_digest(&target_ref));
+        let resolved_id = target_digest
+            .as_ref()
+            .and_then(|digest| resolved.get(digest))
+            .or_else(|| resolved.get(&target_ref));
+        let target_id = match resolved_id {
+            Some(target_id) => Some(*target_id),
+            None => resolve_target(&link, target_digest.as_deref(), note.user_id, conn).await?,
+        };
+        let stored_ref = match key {
+            Some(key) => key.encrypt(note.id, NoteField::LinkTarget, &target_ref)?,
+            None => target_ref,
+        };
+
+        sqlx::query(
+            r#"INSERT INTO note_links (source_id,
+             target_id,
+             target_ref,
+             target_digest,
+             created_at)
+             VALUES ($1,$2,$3,$4,$5)"#,
+        )
+        .bind(note.id)
+        .bind(target_id)
+        .bind(stored_ref)
+        .bind(target_digest)
+        .bind(time_now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    resolve_dangling(note, key, conn).await
+}
+
+async fn resolve_target(
+    link: &WikiLink,
+    target_digest: Option<&str>,
+    user_id: Uuid,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Option<Uuid>> {
+    match link {
+        WikiLink::Id(id) => {
+            query_scalar::<_, Uuid>("SELECT id FROM notes WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .fetch_optional(&mut *conn)
+                .await
+        }
+        WikiLink::Title(title) => {
+            query_scalar::<_, Uuid>(
+                r#"SELECT id FROM notes
+                 WHERE user_id = $1
+                 AND ((title_digest IS NULL AND lower(title) = lower($2)) OR title_digest = $3)
+                 ORDER BY created_at
+                 LIMIT 1"#,
+            )
+            .bind(user_id)
+            .bind(title)
+            .bind(target_digest)
+            .fetch_optional(&mut *conn)
+            .await
+        }
+    }
+}
+
+// Resolves the links that were pointing to the note before it existed or got its title.
+async fn resolve_dangling(
+    note: &Note,
+    key: Option<&NoteKey<'_>>,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    let id_ref = format!("{}{}", NOTE_LINK_ID_PREFIX, note.id);
+    let title = note.title.as_deref();
+    sqlx::query(
+        r#"UPDATE note_links
+         SET target_id = $1
+         WHERE target_id IS NULL
+         AND source_id IN (SELECT id FROM notes WHERE user_id = $4)
+         AND ((target_digest IS NULL AND target_ref = $2)
+            OR target_digest = $5
+            OR (target_digest IS NULL AND lower(target_ref) = lower($3))
+            OR target_digest = $6)"#,
+    )
+    .bind(note.id)
+    .bind(&id_ref)
+    .bind(title)
+    .bind(note.user_id)
+    .bind(key.map(|key| key.ref_digest(&id_ref)))
+    .bind(key.zip(title).map(|(key, title)| key.ref_digest(title)))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -1,87 +1,430 @@
 use chrono::Utc;
-use sqlx::query_as;
+use futures::{StreamExt, TryStreamExt, stream::BoxStream};
+use sqlx::{FromRow, query_as, query_scalar};
 use uuid::Uuid;
 
 use crate::{
-    application::{repository::RepositoryResult, state::SharedState},
-    domain::models::note::Note,
+    application::{
+        repository::{RepositoryResult, activity_repo, note_link_repo, outbox_repo, stats_repo},
+        security::encryption::NoteField,
+        service::{
+            envelope_service::{self, NoteKey, SealedNote},
+            render_service,
+        },
+        state::SharedState,
+    },
+    domain::models::{
+        event::{DomainEvent, NoteEvent},
+        note::{Note, StoredNote},
+        note_flags::{NoteFilter, ViewedNote},
+        stats::{ActivityKind, StatsDelta},
+    },
+    infrastructure::database::DatabaseConnection,
 };
 
-pub async fn list(state: &SharedState) -> RepositoryResult<Vec<Note>> {
-    let users = query_as::<_, Note>("SELECT * FROM notes")
-        .fetch_all(&state.db_pool)
-        .await?;
+// Lists the notes with the flags of the viewer, pinned notes first.
+#[tracing::instrument(name = "note_repo.list", skip_all, fields(db.system = "postgresql"))]
+pub async fn list(
+    viewer_id: Uuid,
+    filter: &NoteFilter,
+    state: &SharedState,
+) -> RepositoryResult<Vec<ViewedNote>> {
+    list_viewed(viewer_id, None, filter, state).await
+}
+
+// Lists the notes of the user with the flags of the viewer, pinned notes first.
+#[tracing::instrument(name = "note_repo.list_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_user(
+    user_id: Uuid,
+    viewer_id: Uuid,
+    filter: &NoteFilter,
+    state: &SharedState,
+) -> RepositoryResult<Vec<ViewedNote>> {
+    list_viewed(viewer_id, Some(user_id), filter, state).await
+}
+
+async fn list_viewed(
+    viewer_id: Uuid,
+    user_id: Option<Uuid>,
+    filter: &NoteFilter,
+    state: &SharedState,
+) -> RepositoryResult<Vec<ViewedNote>> {
+    let notes = query_as::<_, StoredViewedNote>(
+        r#"SELECT notes.*,
+         COALESCE(f.pinned, FALSE) AS pinned,
+         COALESCE(f.archived, FALSE) AS archived,
+         COALESCE(f.favorite, FALSE) AS favorite
+         FROM notes
+         LEFT JOIN note_flags f ON f.note_id = notes.id AND f.user_id = $1
+         WHERE ($2::UUID IS NULL OR notes.user_id = $2)
+         AND ($3::BOOLEAN IS NULL OR COALESCE(f.pinned, FALSE) = $3)
+         AND COALESCE(f.archived, FALSE) = $4
+         AND ($5::BOOLEAN IS NULL OR COALESCE(f.favorite, FALSE) = $5)
+         ORDER BY pinned DESC, notes.updated_at DESC, notes.id"#,
+    )
+    .bind(viewer_id)
+    .bind(user_id)
+    .bind(filter.pinned)
+    .bind(filter.archived.unwrap_or(false))
+    .bind(filter.favorite)
+    .fetch_all(&state.db_pool)
+    .await?;
 
-    Ok(users)
+    let mut opened = Vec::with_capacity(notes.len());
+    for viewed in notes {
+        opened.push(ViewedNote {
+            note: envelope_service::open(viewed.stored, state).await?,
+            pinned: viewed.pinned,
+            archived: viewed.archived,
+            favorite: viewed.favorite,
+        });
+    }
+    Ok(opened)
 }
 
-pub async fn list_by_user(user_id: Uuid, state: &SharedState) -> RepositoryResult<Vec<Note>> {
-    let users = query_as::<_, Note>("SELECT * FROM notes WHERE user_id = $1")
+// A note as read from the database with the flags of the viewer.
+#[derive(FromRow)]
+struct StoredViewedNote {
+    #[sqlx(flatten)]
+    stored: StoredNote,
+    pinned: bool,
+    archived: bool,
+    favorite: bool,
+}
+
+// Streams the notes of the user without loading them all into memory.
+pub fn stream_by_user(user_id: Uuid, state: &SharedState) -> BoxStream<'_, RepositoryResult<Note>> {
+    query_as::<_, StoredNote>("SELECT * FROM notes WHERE user_id = $1 ORDER BY created_at")
         .bind(user_id)
-        .fetch_all(&state.db_pool)
+        .fetch(&state.db_pool)
+        .and_then(move |note| envelope_service::open(note, state))
+        .boxed()
+}
+
+// Finds the owner of the note, the IDs are unique across the users.
+#[tracing::instrument(name = "note_repo.find_owner", skip_all, fields(db.system = "postgresql"))]
+pub async fn find_owner(id: Uuid, state: &SharedState) -> RepositoryResult<Option<Uuid>> {
+    let user_id = query_scalar::<_, Uuid>("SELECT user_id FROM notes WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db_pool)
         .await?;
+    Ok(user_id)
+}
 
-    Ok(users)
+// Finds a note of the user with the same text. Encrypted texts are compared by their keyed digest,
+// only the texts stored in plaintext are compared as is.
+#[tracing::instrument(name = "note_repo.find_by_content", skip_all, fields(db.system = "postgresql"))]
+pub async fn find_by_content(
+    user_id: Uuid,
+    text: &str,
+    state: &SharedState,
+) -> RepositoryResult<Option<Uuid>> {
+    let digest = envelope_service::digest(user_id, text, state).await?;
+    let id = query_scalar::<_, Uuid>(
+        r#"SELECT id FROM notes
+         WHERE user_id = $1
+         AND (text_digest = $3 OR (NOT encrypted_at_rest AND text = $2))
+         LIMIT 1"#,
+    )
+    .bind(user_id)
+    .bind(text)
+    .bind(digest)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(id)
 }
 
+#[tracing::instrument(name = "note_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
 pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<Note> {
-    let user = sqlx::query_as::<_, Note>("SELECT * FROM notes WHERE id = $1")
+    let note = sqlx::query_as::<_, StoredNote>("SELECT * FROM notes WHERE id = $1")
         .bind(id)
         .fetch_one(&state.db_pool)
         .await?;
-    Ok(user)
+    envelope_service::open(note, state).await
 }
 
+// The title of the note, without reading and decrypting its text.
+#[tracing::instrument(name = "note_repo.get_title", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_title(id: Uuid, state: &SharedState) -> RepositoryResult<Option<String>> {
+    let (user_id, title, title_digest) = query_as::<_, (Uuid, Option<String>, Option<String>)>(
+        "SELECT user_id, title, title_digest FROM notes WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    match (title, title_digest) {
+        (Some(title), Some(_)) => {
+            let title =
+                envelope_service::open_field(user_id, id, NoteField::Title, &title, state).await?;
+            Ok(Some(title))
+        }
+        (title, _) => Ok(title),
+    }
+}
+
+#[tracing::instrument(name = "note_repo.add", skip_all, fields(db.system = "postgresql"))]
 pub async fn add(note: Note, state: &SharedState) -> RepositoryResult<Note> {
     let time_now = Utc::now().naive_utc();
     tracing::trace!("note: {:#?}", note);
-    let note = sqlx::query_as::<_, Note>(
+    let note = Note {
+        title: derive_title(&note),
+        ..note
+    };
+    let text_length = text_length(&note);
+    let key = envelope_service::note_key(note.user_id, state).await?;
+    let sealed = envelope_service::seal(&note, key.as_ref())?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let mut stored = sqlx::query_as::<_, Note>(
         r#"INSERT INTO notes (id,
          user_id,
+         title,
+         title_digest,
          text,
+         text_digest,
+         text_length,
+         encryption,
+         encrypted_at_rest,
          created_at,
          updated_at)
-         VALUES ($1,$2,$3,$4,$5)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
          RETURNING notes.*"#,
     )
     .bind(note.id)
     .bind(note.user_id)
-    .bind(note.text)
-    .bind(time_now)
-    .bind(time_now)
-    .fetch_one(&state.db_pool)
+    .bind(sealed.title)
+    .bind(sealed.title_digest)
+    .bind(sealed.text)
+    .bind(sealed.text_digest)
+    .bind(text_length)
+    .bind(note.encryption)
+    .bind(sealed.encrypted_at_rest)
+    .bind(note.created_at.unwrap_or(time_now))
+    .bind(note.updated_at.unwrap_or(time_now))
+    .fetch_one(&mut *tx)
     .await?;
+    stored.text = note.text;
+    stored.title = note.title;
+
+    note_link_repo::save_links(&stored, key.as_ref(), &mut tx).await?;
+    let delta = StatsDelta {
+        notes: 1,
+        notes_created: 1,
+        total_chars: i64::from(text_length),
+        ..StatsDelta::default()
+    };
+    stats_repo::apply(stored.user_id, delta, time_now, &mut tx).await?;
+    activity_repo::record(stored.user_id, ActivityKind::NoteCreated, time_now, &mut tx).await?;
+    let event = DomainEvent::NoteCreated(note_event(&stored, key.as_ref()));
+    outbox_repo::add(&event, &mut tx).await?;
+    tx.commit().await?;
 
-    Ok(note)
+    Ok(stored)
 }
 
+#[tracing::instrument(name = "note_repo.update", skip_all, fields(db.system = "postgresql"))]
 pub async fn update(note: Note, state: &SharedState) -> RepositoryResult<Note> {
     tracing::trace!("note: {:#?}", note);
     let time_now = Utc::now().naive_utc();
-    let note = sqlx::query_as::<_, Note>(
+    let note = Note {
+        title: derive_title(&note),
+        ..note
+    };
+    let text_length = text_length(&note);
+    let key = envelope_service::note_key(note.user_id, state).await?;
+    let sealed = envelope_service::seal(&note, key.as_ref())?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let (previous_user_id, previous_length) = query_as::<_, (Uuid, Option<i32>)>(
+        "SELECT user_id, text_length FROM notes WHERE id = $1 FOR UPDATE",
+    )
+    .bind(note.id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let mut stored = sqlx::query_as::<_, Note>(
         r#"UPDATE notes
          SET 
          user_id = $1,
-         text = $2,
-         updated_at = $3
-         WHERE id = $4
+         title = $2,
+         title_digest = $3,
+         text = $4,
+         text_digest = $5,
+         text_length = $6,
+         encryption = $7,
+         encrypted_at_rest = $8,
+         updated_at = $9
+         WHERE id = $10
          RETURNING notes.*"#,
     )
     .bind(note.user_id)
-    .bind(note.text)
+    .bind(sealed.title)
+    .bind(sealed.title_digest)
+    .bind(sealed.text)
+    .bind(sealed.text_digest)
+    .bind(text_length)
+    .bind(note.encryption)
+    .bind(sealed.encrypted_at_rest)
     .bind(time_now)
     .bind(note.id)
-    .fetch_one(&state.db_pool)
+    .fetch_one(&mut *tx)
+    .await?;
+    stored.text = note.text;
+    stored.title = note.title;
+
+    note_link_repo::save_links(&stored, key.as_ref(), &mut tx).await?;
+    // A note given to another user moves to the stats of the new owner.
+    let previous_length = i64::from(previous_length.unwrap_or_default());
+    if previous_user_id != stored.user_id {
+        let delta = StatsDelta {
+            notes: -1,
+            total_chars: -previous_length,
+            ..StatsDelta::default()
+        };
+        stats_repo::apply(previous_user_id, delta, time_now, &mut tx).await?;
+    }
+    let delta = StatsDelta {
+        notes: i32::from(previous_user_id != stored.user_id),
+        notes_edited: 1,
+        total_chars: if previous_user_id == stored.user_id {
+            i64::from(text_length) - previous_length
+        } else {
+            i64::from(text_length)
+        },
+        ..StatsDelta::default()
+    };
+    stats_repo::apply(stored.user_id, delta, time_now, &mut tx).await?;
+    activity_repo::record(stored.user_id, ActivityKind::NoteEdited, time_now, &mut tx).await?;
+    let event = DomainEvent::NoteUpdated(note_event(&stored, key.as_ref()));
+    outbox_repo::add(&event, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(stored)
+}
+
+// The note of the events, without the title of a note encrypted at rest as the events are stored
+// and sent in plaintext.
+fn note_event(note: &Note, key: Option<&NoteKey<'_>>) -> NoteEvent {
+    let note_event = NoteEvent::from(note);
+    if key.is_some() {
+        NoteEvent {
+            title: None,
+            ..note_event
+        }
+    } else {
+        note_event
+    }
+}
+
+// Locks a batch of notes stored in plaintext, or whose title or links were stored in plaintext,
+// skipping the ones locked by other instances.
+#[tracing::instrument(name = "note_repo.lock_unsealed", skip_all, fields(db.system = "postgresql"))]
+pub async fn lock_unsealed(
+    limit: i64,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<StoredNote>> {
+    let notes = query_as::<_, StoredNote>(
+        r#"SELECT * FROM notes
+         WHERE NOT encrypted_at_rest
+         OR (title IS NOT NULL AND title_digest IS NULL)
+         OR EXISTS (SELECT 1 FROM note_links
+            WHERE note_links.source_id = notes.id AND note_links.target_digest IS NULL)
+         LIMIT $1
+         FOR UPDATE SKIP LOCKED"#,
+    )
+    .bind(limit)
+    .fetch_all(&mut *conn)
     .await?;
+    Ok(notes)
+}
 
-    Ok(note)
+// Replaces the stored text and title without changing the note version.
+#[tracing::instrument(name = "note_repo.update_sealed", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_sealed(
+    id: Uuid,
+    sealed: SealedNote,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query(
+        r#"UPDATE notes
+         SET text = $1,
+         text_digest = $2,
+         title = $3,
+         title_digest = $4,
+         encrypted_at_rest = $5
+         WHERE id = $6"#,
+    )
+    .bind(sealed.text)
+    .bind(sealed.text_digest)
+    .bind(sealed.title)
+    .bind(sealed.title_digest)
+    .bind(sealed.encrypted_at_rest)
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
 }
 
+#[tracing::instrument(name = "note_repo.delete", skip_all, fields(db.system = "postgresql"))]
 pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
-    let query_result = sqlx::query("DELETE FROM notes WHERE id = $1")
-        .bind(id)
+    let time_now = Utc::now().naive_utc();
+    let mut tx = state.db_pool.begin().await?;
+    let deleted = query_as::<_, (Uuid, Option<i32>)>(
+        "DELETE FROM notes WHERE id = $1 RETURNING user_id, text_length",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((user_id, text_length)) = deleted else {
+        return Ok(false);
+    };
+    let delta = StatsDelta {
+        notes: -1,
+        notes_deleted: 1,
+        total_chars: -i64::from(text_length.unwrap_or_default()),
+        ..StatsDelta::default()
+    };
+    stats_repo::apply(user_id, delta, time_now, &mut tx).await?;
+    activity_repo::record(user_id, ActivityKind::NoteDeleted, time_now, &mut tx).await?;
+    let event = DomainEvent::NoteDeleted { id, user_id };
+    outbox_repo::add(&event, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+// Streams the notes whose text length was not measured, the notes encrypted at rest before the length was stored.
+pub fn stream_unmeasured(state: &SharedState) -> BoxStream<'_, RepositoryResult<Note>> {
+    query_as::<_, StoredNote>("SELECT * FROM notes WHERE text_length IS NULL")
+        .fetch(&state.db_pool)
+        .and_then(move |note| envelope_service::open(note, state))
+        .boxed()
+}
+
+#[tracing::instrument(name = "note_repo.update_text_length", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_text_length(note: &Note, state: &SharedState) -> RepositoryResult<()> {
+    sqlx::query("UPDATE notes SET text_length = $1 WHERE id = $2")
+        .bind(text_length(note))
+        .bind(note.id)
         .execute(&state.db_pool)
         .await?;
+    Ok(())
+}
+
+// Length in characters of the note text, the server cannot read the text of an encrypted note.
+fn text_length(note: &Note) -> i32 {
+    if note.is_encrypted() {
+        return 0;
+    }
+    i32::try_from(note.text.chars().count()).unwrap_or(i32::MAX)
+}
 
-    Ok(query_result.rows_affected() == 1)
+// The title of an encrypted note is not derived, the server cannot read its text.
+fn derive_title(note: &Note) -> Option<String> {
+    if note.is_encrypted() {
+        return None;
+    }
+    note.title
+        .clone()
+        .or_else(|| render_service::extract_title(&note.text))
 }
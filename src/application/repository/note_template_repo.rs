@@ -0,0 +1,93 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_template::NoteTemplate,
+};
+
+// Lists the templates of the user and the global templates.
+#[tracing::instrument(name = "note_template_repo.list_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_user(
+    user_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Vec<NoteTemplate>> {
+    let templates = sqlx::query_as::<_, NoteTemplate>(
+        "SELECT * FROM note_templates WHERE user_id = $1 OR user_id IS NULL ORDER BY name",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(templates)
+}
+
+#[tracing::instrument(name = "note_template_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<NoteTemplate> {
+    let template = sqlx::query_as::<_, NoteTemplate>("SELECT * FROM note_templates WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(template)
+}
+
+#[tracing::instrument(name = "note_template_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(template: NoteTemplate, state: &SharedState) -> RepositoryResult<NoteTemplate> {
+    let time_now = Utc::now().naive_utc();
+    let template = sqlx::query_as::<_, NoteTemplate>(
+        r#"INSERT INTO note_templates (id,
+         user_id,
+         name,
+         title,
+         text,
+         prompts,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+         RETURNING note_templates.*"#,
+    )
+    .bind(template.id)
+    .bind(template.user_id)
+    .bind(template.name)
+    .bind(template.title)
+    .bind(template.text)
+    .bind(template.prompts)
+    .bind(template.created_at.unwrap_or(time_now))
+    .bind(template.updated_at.unwrap_or(time_now))
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(template)
+}
+
+#[tracing::instrument(name = "note_template_repo.update", skip_all, fields(db.system = "postgresql"))]
+pub async fn update(template: NoteTemplate, state: &SharedState) -> RepositoryResult<NoteTemplate> {
+    let time_now = Utc::now().naive_utc();
+    let template = sqlx::query_as::<_, NoteTemplate>(
+        r#"UPDATE note_templates
+         SET
+         name = $1,
+         title = $2,
+         text = $3,
+         prompts = $4,
+         updated_at = $5
+         WHERE id = $6
+         RETURNING note_templates.*"#,
+    )
+    .bind(template.name)
+    .bind(template.title)
+    .bind(template.text)
+    .bind(template.prompts)
+    .bind(time_now)
+    .bind(template.id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(template)
+}
+
+#[tracing::instrument(name = "note_template_repo.delete", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query("DELETE FROM note_templates WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await?;
+    Ok(query_result.rows_affected() == 1)
+}
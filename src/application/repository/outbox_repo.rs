@@ -0,0 +1,139 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::event::{DomainEvent, OutboxEvent},
+    infrastructure::database::DatabaseConnection,
+};
+
+// Writes the event in the transaction of the change, it is dispatched once the change is committed.
+#[tracing::instrument(name = "outbox_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(event: &DomainEvent, conn: &mut DatabaseConnection) -> RepositoryResult<()> {
+    sqlx::query("INSERT INTO outbox_events (id, event, data, created_at) VALUES ($1,$2,$3,$4)")
+        .bind(Uuid::new_v4())
+        .bind(event.kind())
+        .bind(event.data())
+        .bind(Utc::now().naive_utc())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// Locks the oldest events due for dispatch, skipping the ones locked by other instances.
+#[tracing::instrument(name = "outbox_repo.lock_pending", skip_all, fields(db.system = "postgresql"))]
+pub async fn lock_pending(
+    now: NaiveDateTime,
+    limit: i64,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<OutboxEvent>> {
+    let events = sqlx::query_as::<_, OutboxEvent>(
+        r#"SELECT * FROM outbox_events
+         WHERE dispatched_at IS NULL
+         AND failed_at IS NULL
+         AND (next_attempt_at IS NULL OR next_attempt_at <= $1)
+         ORDER BY created_at
+         LIMIT $2
+         FOR UPDATE SKIP LOCKED"#,
+    )
+    .bind(now)
+    .bind(limit)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(events)
+}
+
+// The consumers which handled the events, as pairs of event ID and consumer name.
+#[tracing::instrument(name = "outbox_repo.list_consumers", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_consumers(
+    event_ids: &[Uuid],
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<(Uuid, String)>> {
+    let consumers = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT event_id, consumer FROM outbox_consumers WHERE event_id = ANY($1)",
+    )
+    .bind(event_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(consumers)
+}
+
+// Records the event handled by the consumer, in the transaction of its handling.
+#[tracing::instrument(name = "outbox_repo.add_consumer", skip_all, fields(db.system = "postgresql"))]
+pub async fn add_consumer(
+    event_id: Uuid,
+    consumer: &str,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query("INSERT INTO outbox_consumers (event_id, consumer, handled_at) VALUES ($1,$2,$3)")
+        .bind(event_id)
+        .bind(consumer)
+        .bind(Utc::now().naive_utc())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "outbox_repo.mark_dispatched", skip_all, fields(db.system = "postgresql"))]
+pub async fn mark_dispatched(id: Uuid, conn: &mut DatabaseConnection) -> RepositoryResult<()> {
+    sqlx::query("UPDATE outbox_events SET dispatched_at = $1 WHERE id = $2")
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// Records a failed dispatch, the event is dispatched again at the time.
+#[tracing::instrument(name = "outbox_repo.retry_at", skip_all, fields(db.system = "postgresql"))]
+pub async fn retry_at(
+    id: Uuid,
+    next_attempt_at: NaiveDateTime,
+    error: String,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query(
+        r#"UPDATE outbox_events
+         SET
+         attempts = attempts + 1,
+         next_attempt_at = $1,
+         last_error = $2
+         WHERE id = $3"#,
+    )
+    .bind(next_attempt_at)
+    .bind(error)
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// Records the last failed dispatch, the event is not dispatched again.
+#[tracing::instrument(name = "outbox_repo.fail", skip_all, fields(db.system = "postgresql"))]
+pub async fn fail(id: Uuid, error: String, conn: &mut DatabaseConnection) -> RepositoryResult<()> {
+    sqlx::query(
+        r#"UPDATE outbox_events
+         SET
+         attempts = attempts + 1,
+         failed_at = $1,
+         last_error = $2
+         WHERE id = $3"#,
+    )
+    .bind(Utc::now().naive_utc())
+    .bind(error)
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// Deletes the events dispatched or failed before the time, their webhook deliveries keep their payloads.
+#[tracing::instrument(name = "outbox_repo.purge_dispatched", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_dispatched(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result =
+        sqlx::query("DELETE FROM outbox_events WHERE dispatched_at < $1 OR failed_at < $1")
+            .bind(before)
+            .execute(&state.db_pool)
+            .await?;
+    Ok(query_result.rows_affected())
+}
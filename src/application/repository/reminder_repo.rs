@@ -0,0 +1,223 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        constants::REMINDER_EVENTS_NOTIFY_CHANNEL, repository::RepositoryResult, state::SharedState,
+    },
+    domain::models::reminder::{Reminder, ReminderNotification, ReminderStatus},
+    infrastructure::database::DatabaseConnection,
+};
+
+#[tracing::instrument(name = "reminder_repo.list_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_user(user_id: Uuid, state: &SharedState) -> RepositoryResult<Vec<Reminder>> {
+    let reminders =
+        sqlx::query_as::<_, Reminder>("SELECT * FROM reminders WHERE user_id = $1 ORDER BY due_at")
+            .bind(user_id)
+            .fetch_all(&state.db_pool)
+            .await?;
+    Ok(reminders)
+}
+
+#[tracing::instrument(name = "reminder_repo.list_by_note", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_note(
+    note_id: Uuid,
+    user_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Vec<Reminder>> {
+    let reminders = sqlx::query_as::<_, Reminder>(
+        "SELECT * FROM reminders WHERE note_id = $1 AND user_id = $2 ORDER BY due_at",
+    )
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(reminders)
+}
+
+#[tracing::instrument(name = "reminder_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<Reminder> {
+    let reminder = sqlx::query_as::<_, Reminder>("SELECT * FROM reminders WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(reminder)
+}
+
+#[tracing::instrument(name = "reminder_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(reminder: Reminder, state: &SharedState) -> RepositoryResult<Reminder> {
+    let time_now = Utc::now().naive_utc();
+    let reminder = sqlx::query_as::<_, Reminder>(
+        r#"INSERT INTO reminders (id,
+         note_id,
+         user_id,
+         starts_at,
+         due_at,
+         rrule,
+         channel,
+         webhook_url,
+         status,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+         RETURNING reminders.*"#,
+    )
+    .bind(reminder.id)
+    .bind(reminder.note_id)
+    .bind(reminder.user_id)
+    .bind(reminder.starts_at)
+    .bind(reminder.due_at)
+    .bind(reminder.rrule)
+    .bind(reminder.channel)
+    .bind(reminder.webhook_url)
+    .bind(reminder.status)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(reminder)
+}
+
+// Claims a batch of due reminders until the given time, skipping the ones locked by other
+// instances, so every reminder is fired by a single instance. A reminder whose instance stopped
+// is claimed again once its lock expires.
+#[tracing::instrument(name = "reminder_repo.claim_due", skip_all, fields(db.system = "postgresql"))]
+pub async fn claim_due(
+    now: NaiveDateTime,
+    locked_until: NaiveDateTime,
+    limit: i64,
+    state: &SharedState,
+) -> RepositoryResult<Vec<Reminder>> {
+    let reminders = sqlx::query_as::<_, Reminder>(
+        r#"UPDATE reminders
+         SET locked_until = $1
+         WHERE id IN (
+             SELECT id FROM reminders
+             WHERE status = $2 AND due_at <= $3
+             AND (locked_until IS NULL OR locked_until <= $3)
+             ORDER BY due_at
+             LIMIT $4
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING reminders.*"#,
+    )
+    .bind(locked_until)
+    .bind(ReminderStatus::Pending)
+    .bind(now)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(reminders)
+}
+
+// Records the delivery of a claimed reminder and releases it. False when the claim was lost,
+// the reminder was snoozed, dismissed or claimed again meanwhile.
+#[tracing::instrument(name = "reminder_repo.update_schedule", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_schedule(reminder: &Reminder, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query(
+        r#"UPDATE reminders
+         SET
+         due_at = $1,
+         status = $2,
+         occurrences = $3,
+         attempts = $4,
+         last_fired_at = $5,
+         last_error = $6,
+         locked_until = NULL,
+         updated_at = $7
+         WHERE id = $8 AND locked_until = $9"#,
+    )
+    .bind(reminder.due_at)
+    .bind(reminder.status)
+    .bind(reminder.occurrences)
+    .bind(reminder.attempts)
+    .bind(reminder.last_fired_at)
+    .bind(&reminder.last_error)
+    .bind(Utc::now().naive_utc())
+    .bind(reminder.id)
+    .bind(reminder.locked_until)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(query_result.rows_affected() == 1)
+}
+
+#[tracing::instrument(name = "reminder_repo.snooze", skip_all, fields(db.system = "postgresql"))]
+pub async fn snooze(
+    id: Uuid,
+    due_at: NaiveDateTime,
+    state: &SharedState,
+) -> RepositoryResult<Reminder> {
+    let reminder = sqlx::query_as::<_, Reminder>(
+        r#"UPDATE reminders
+         SET
+         due_at = $1,
+         status = $2,
+         attempts = 0,
+         locked_until = NULL,
+         updated_at = $3
+         WHERE id = $4
+         RETURNING reminders.*"#,
+    )
+    .bind(due_at)
+    .bind(ReminderStatus::Pending)
+    .bind(Utc::now().naive_utc())
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(reminder)
+}
+
+#[tracing::instrument(name = "reminder_repo.dismiss", skip_all, fields(db.system = "postgresql"))]
+pub async fn dismiss(id: Uuid, state: &SharedState) -> RepositoryResult<Reminder> {
+    let reminder = sqlx::query_as::<_, Reminder>(
+        r#"UPDATE reminders
+         SET
+         status = $1,
+         locked_until = NULL,
+         updated_at = $2
+         WHERE id = $3
+         RETURNING reminders.*"#,
+    )
+    .bind(ReminderStatus::Dismissed)
+    .bind(Utc::now().naive_utc())
+    .bind(id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(reminder)
+}
+
+#[tracing::instrument(name = "reminder_repo.delete", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query("DELETE FROM reminders WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+// Deletes the reminders fired, dismissed or failed before the given time.
+#[tracing::instrument(name = "reminder_repo.purge_finished", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_finished(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result = sqlx::query("DELETE FROM reminders WHERE status <> $1 AND updated_at < $2")
+        .bind(ReminderStatus::Pending)
+        .bind(before)
+        .execute(&state.db_pool)
+        .await?;
+
+    Ok(query_result.rows_affected())
+}
+
+// Publishes the notification to the listeners of every instance.
+#[tracing::instrument(name = "reminder_repo.notify", skip_all, fields(db.system = "postgresql"))]
+pub async fn notify(
+    notification: &ReminderNotification,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query("SELECT pg_notify($1, $2::text)")
+        .bind(REMINDER_EVENTS_NOTIFY_CHANNEL)
+        .bind(sqlx::types::Json(notification))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -1,77 +1,105 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use uuid::Uuid;
 
 use crate::{
     application::{repository::RepositoryResult, state::SharedState},
-    domain::models::stats::{StatRequest, StatResponse},
+    domain::models::stats::{StatResponse, StatsDelta},
+    infrastructure::database::DatabaseConnection,
 };
 
-pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<StatResponse> {
-    let user = sqlx::query_as::<_, StatResponse>("SELECT * FROM stats WHERE id = $1")
-        .bind(id)
+#[tracing::instrument(name = "stats_repo.get_by_user_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_user_id(user_id: Uuid, state: &SharedState) -> RepositoryResult<StatResponse> {
+    let stats = sqlx::query_as::<_, StatResponse>("SELECT * FROM stats WHERE user_id = $1")
+        .bind(user_id)
         .fetch_one(&state.db_pool)
         .await?;
-    Ok(user)
+    Ok(stats)
 }
 
-pub async fn update(stat: StatRequest, state: &SharedState) -> RepositoryResult<StatResponse> {
-    tracing::trace!("stat: {:#?}", stat);
-    let time_now = Utc::now().naive_utc();
-
-    let stat_rep = sqlx::query_as::<_, StatResponse>("SELECT * FROM stats WHERE id = $1")
-        .bind(stat.user_id)
-        .fetch_all(&state.db_pool)
-        .await?;
+// Applies the change to the stats of the user, in the transaction of the note change.
+#[tracing::instrument(name = "stats_repo.apply", skip_all, fields(db.system = "postgresql"))]
+pub async fn apply(
+    user_id: Uuid,
+    delta: StatsDelta,
+    activity_at: NaiveDateTime,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    tracing::trace!("user_id: {}, stats delta: {:?}", user_id, delta);
+    sqlx::query(
+        r#"INSERT INTO stats (id,
+         user_id,
+         notes,
+         notes_created,
+         notes_deleted,
+         notes_edited,
+         total_chars,
+         last_activity_at,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8,$8)
+         ON CONFLICT (user_id) DO UPDATE
+         SET notes = stats.notes + EXCLUDED.notes,
+         notes_created = stats.notes_created + EXCLUDED.notes_created,
+         notes_deleted = stats.notes_deleted + EXCLUDED.notes_deleted,
+         notes_edited = stats.notes_edited + EXCLUDED.notes_edited,
+         total_chars = stats.total_chars + EXCLUDED.total_chars,
+         last_activity_at = GREATEST(stats.last_activity_at, EXCLUDED.last_activity_at),
+         updated_at = EXCLUDED.updated_at"#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(delta.notes)
+    .bind(delta.notes_created)
+    .bind(delta.notes_deleted)
+    .bind(delta.notes_edited)
+    .bind(delta.total_chars)
+    .bind(activity_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
 
-    if stat_rep.len() == 0 {
-        let stat_id = Uuid::new_v4();
-        let stat: StatResponse = sqlx::query_as::<_, StatResponse>(
-            r#"INSERT INTO stats (id,
-             user_id,
-             notes,
-             created_at,
-             updated_at)
-             VALUES ($1,$2,$3,$4,$5)
-             RETURNING stats.*"#,
-        )
-        .bind(stat_id)
-        .bind(stat.user_id)
-        .bind(stat.value)
-        .bind(time_now)
-        .bind(time_now)
-        .fetch_one(&state.db_pool)
+// Recomputes the stats of every user from the notes. The deleted and edited counters
+// are not recorded in the notes and are kept, the created counter includes the deleted notes.
+#[tracing::instrument(name = "stats_repo.rebuild", skip_all, fields(db.system = "postgresql"))]
+pub async fn rebuild(state: &SharedState) -> RepositoryResult<u64> {
+    let time_now = Utc::now().naive_utc();
+    let mut tx = state.db_pool.begin().await?;
+    // Note changes wait for the rebuild, so none of their stats changes is lost.
+    sqlx::query("LOCK TABLE notes IN SHARE MODE")
+        .execute(&mut *tx)
         .await?;
+    let query_result = sqlx::query(
+        r#"INSERT INTO stats (id,
+         user_id,
+         notes,
+         notes_created,
+         total_chars,
+         last_activity_at,
+         created_at,
+         updated_at)
+         SELECT gen_random_uuid(),
+         users.id,
+         COUNT(notes.id),
+         COUNT(notes.id),
+         COALESCE(SUM(notes.text_length), 0),
+         MAX(notes.updated_at),
+         $1,
+         $1
+         FROM users
+         LEFT JOIN notes ON notes.user_id = users.id
+         GROUP BY users.id
+         ON CONFLICT (user_id) DO UPDATE
+         SET notes = EXCLUDED.notes,
+         notes_created = EXCLUDED.notes + stats.notes_deleted,
+         total_chars = EXCLUDED.total_chars,
+         last_activity_at = GREATEST(stats.last_activity_at, EXCLUDED.last_activity_at),
+         updated_at = EXCLUDED.updated_at"#,
+    )
+    .bind(time_now)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
-        Ok(stat)
-    } else {
-        if stat.value < 0 {
-            let stat = sqlx::query_as::<_, StatResponse>(
-                r#"UPDATE stats
-                SET 
-                notes = notes - 1,
-                updated_at = $1
-                WHERE user_id = $2
-                RETURNING stats.*"#,
-            )
-            .bind(time_now)
-            .bind(stat.user_id)
-            .fetch_one(&state.db_pool)
-            .await?;
-            Ok(stat)
-        } else {
-            let stat = sqlx::query_as::<_, StatResponse>(
-                r#"UPDATE stats
-                SET 
-                notes = notes + 1,
-                updated_at = $1
-                WHERE user_id = $2
-                RETURNING stats.*"#,
-            )
-            .bind(time_now)
-            .bind(stat.user_id)
-            .fetch_one(&state.db_pool)
-            .await?;
-            Ok(stat)
-        }
-    }
+    Ok(query_result.rows_affected())
 }
@@ -0,0 +1,192 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::task::{Task, TaskStatus, TaskSummary},
+    infrastructure::database::DatabaseConnection,
+};
+
+// Queues the task, in the transaction of the caller to queue it with a change.
+#[tracing::instrument(name = "task_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(task: Task, conn: &mut DatabaseConnection) -> RepositoryResult<Task> {
+    let time_now = Utc::now().naive_utc();
+    let task = sqlx::query_as::<_, Task>(
+        r#"INSERT INTO tasks (id,
+         kind,
+         payload,
+         status,
+         max_attempts,
+         run_at,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+         RETURNING tasks.*"#,
+    )
+    .bind(task.id)
+    .bind(task.kind)
+    .bind(task.payload)
+    .bind(task.status)
+    .bind(task.max_attempts)
+    .bind(task.run_at)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(task)
+}
+
+// Takes the next due task, or a running task whose worker stopped before its visibility timeout.
+// The tasks locked by the other workers are skipped, a task is taken by a single worker.
+#[tracing::instrument(name = "task_repo.dequeue", skip_all, fields(db.system = "postgresql"))]
+pub async fn dequeue(
+    now: NaiveDateTime,
+    locked_until: NaiveDateTime,
+    state: &SharedState,
+) -> RepositoryResult<Option<Task>> {
+    let task = sqlx::query_as::<_, Task>(
+        r#"UPDATE tasks
+         SET
+         status = $1,
+         attempts = attempts + 1,
+         locked_until = $2,
+         updated_at = $3
+         WHERE id = (
+             SELECT id FROM tasks
+             WHERE (status = $4 AND run_at <= $3) OR (status = $1 AND locked_until <= $3)
+             ORDER BY run_at
+             LIMIT 1
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING tasks.*"#,
+    )
+    .bind(TaskStatus::Running)
+    .bind(locked_until)
+    .bind(now)
+    .bind(TaskStatus::Queued)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(task)
+}
+
+// Extends the visibility timeout of the attempt, false when the task was taken by another worker.
+#[tracing::instrument(name = "task_repo.extend", skip_all, fields(db.system = "postgresql"))]
+pub async fn extend(
+    task: &Task,
+    locked_until: NaiveDateTime,
+    state: &SharedState,
+) -> RepositoryResult<bool> {
+    let query_result = sqlx::query(
+        r#"UPDATE tasks
+         SET
+         locked_until = $1
+         WHERE id = $2 AND attempts = $3 AND status = $4"#,
+    )
+    .bind(locked_until)
+    .bind(task.id)
+    .bind(task.attempts)
+    .bind(TaskStatus::Running)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(query_result.rows_affected() == 1)
+}
+
+// Records the end of the attempt, unless the task was taken by another worker meanwhile.
+// A queued task runs again at `run_at`.
+#[tracing::instrument(name = "task_repo.finish", skip_all, fields(db.system = "postgresql"))]
+pub async fn finish(
+    task: &Task,
+    status: TaskStatus,
+    run_at: NaiveDateTime,
+    error: Option<String>,
+    state: &SharedState,
+) -> RepositoryResult<bool> {
+    let query_result = sqlx::query(
+        r#"UPDATE tasks
+         SET
+         status = $1,
+         run_at = $2,
+         last_error = $3,
+         locked_until = NULL,
+         updated_at = $4
+         WHERE id = $5 AND attempts = $6 AND status = $7"#,
+    )
+    .bind(status)
+    .bind(run_at)
+    .bind(error)
+    .bind(Utc::now().naive_utc())
+    .bind(task.id)
+    .bind(task.attempts)
+    .bind(TaskStatus::Running)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(query_result.rows_affected() == 1)
+}
+
+#[tracing::instrument(name = "task_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<Task> {
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(task)
+}
+
+// The last updated tasks, of the given status or all.
+#[tracing::instrument(name = "task_repo.list", skip_all, fields(db.system = "postgresql"))]
+pub async fn list(
+    status: Option<TaskStatus>,
+    limit: i64,
+    state: &SharedState,
+) -> RepositoryResult<Vec<TaskSummary>> {
+    let tasks = sqlx::query_as::<_, TaskSummary>(
+        r#"SELECT id, kind, status, attempts, max_attempts, run_at, locked_until, last_error,
+         created_at, updated_at
+         FROM tasks
+         WHERE $1::TEXT IS NULL OR status = $1
+         ORDER BY updated_at DESC
+         LIMIT $2"#,
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(tasks)
+}
+
+// Queues a dead task again with all its attempts.
+#[tracing::instrument(name = "task_repo.requeue_dead", skip_all, fields(db.system = "postgresql"))]
+pub async fn requeue_dead(id: Uuid, state: &SharedState) -> RepositoryResult<Option<TaskSummary>> {
+    let time_now = Utc::now().naive_utc();
+    let task = sqlx::query_as::<_, TaskSummary>(
+        r#"UPDATE tasks
+         SET
+         status = $1,
+         attempts = 0,
+         run_at = $2,
+         updated_at = $2
+         WHERE id = $3 AND status = $4
+         RETURNING id, kind, status, attempts, max_attempts, run_at, locked_until, last_error,
+         created_at, updated_at"#,
+    )
+    .bind(TaskStatus::Queued)
+    .bind(time_now)
+    .bind(id)
+    .bind(TaskStatus::Dead)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    Ok(task)
+}
+
+// Deletes the tasks succeeded or dead before the given time.
+#[tracing::instrument(name = "task_repo.purge_finished", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_finished(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result =
+        sqlx::query("DELETE FROM tasks WHERE status IN ($1, $2) AND updated_at < $3")
+            .bind(TaskStatus::Succeeded)
+            .bind(TaskStatus::Dead)
+            .bind(before)
+            .execute(&state.db_pool)
+            .await?;
+    Ok(query_result.rows_affected())
+}
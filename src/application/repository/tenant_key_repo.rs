@@ -0,0 +1,89 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::tenant_key::TenantKey,
+    infrastructure::database::DatabaseConnection,
+};
+
+#[tracing::instrument(name = "tenant_key_repo.get_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_user(
+    user_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Option<TenantKey>> {
+    let tenant_key = sqlx::query_as::<_, TenantKey>("SELECT * FROM tenant_keys WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.db_pool)
+        .await?;
+    Ok(tenant_key)
+}
+
+// Stores the data key of the tenant, the key stored first wins when created concurrently.
+#[tracing::instrument(name = "tenant_key_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(tenant_key: TenantKey, state: &SharedState) -> RepositoryResult<TenantKey> {
+    let time_now = Utc::now().naive_utc();
+    sqlx::query(
+        r#"INSERT INTO tenant_keys (user_id,
+         wrapped_key,
+         master_key_id,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5)
+         ON CONFLICT (user_id) DO NOTHING"#,
+    )
+    .bind(tenant_key.user_id)
+    .bind(tenant_key.wrapped_key)
+    .bind(tenant_key.master_key_id)
+    .bind(time_now)
+    .bind(time_now)
+    .execute(&state.db_pool)
+    .await?;
+
+    let tenant_key = sqlx::query_as::<_, TenantKey>("SELECT * FROM tenant_keys WHERE user_id = $1")
+        .bind(tenant_key.user_id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(tenant_key)
+}
+
+// Locks a batch of data keys not wrapped by the given master key, skipping the ones locked by other instances.
+#[tracing::instrument(name = "tenant_key_repo.lock_stale", skip_all, fields(db.system = "postgresql"))]
+pub async fn lock_stale(
+    master_key_id: &str,
+    limit: i64,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<TenantKey>> {
+    let tenant_keys = sqlx::query_as::<_, TenantKey>(
+        r#"SELECT * FROM tenant_keys
+         WHERE master_key_id <> $1
+         LIMIT $2
+         FOR UPDATE SKIP LOCKED"#,
+    )
+    .bind(master_key_id)
+    .bind(limit)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(tenant_keys)
+}
+
+#[tracing::instrument(name = "tenant_key_repo.update_wrapped_key", skip_all, fields(db.system = "postgresql"))]
+pub async fn update_wrapped_key(
+    tenant_key: TenantKey,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<()> {
+    sqlx::query(
+        r#"UPDATE tenant_keys
+         SET wrapped_key = $1,
+         master_key_id = $2,
+         updated_at = $3
+         WHERE user_id = $4"#,
+    )
+    .bind(tenant_key.wrapped_key)
+    .bind(tenant_key.master_key_id)
+    .bind(Utc::now().naive_utc())
+    .bind(tenant_key.user_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
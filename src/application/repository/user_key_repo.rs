@@ -0,0 +1,54 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::note_key::UserKey,
+};
+
+#[tracing::instrument(name = "user_key_repo.get_by_user", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_user(user_id: Uuid, state: &SharedState) -> RepositoryResult<UserKey> {
+    let user_key = sqlx::query_as::<_, UserKey>("SELECT * FROM user_keys WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(user_key)
+}
+
+#[tracing::instrument(name = "user_key_repo.exists", skip_all, fields(db.system = "postgresql"))]
+pub async fn exists(user_id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM user_keys WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&state.db_pool)
+            .await?;
+    Ok(exists)
+}
+
+// Registers or replaces the public key of the user.
+#[tracing::instrument(name = "user_key_repo.upsert", skip_all, fields(db.system = "postgresql"))]
+pub async fn upsert(user_key: UserKey, state: &SharedState) -> RepositoryResult<UserKey> {
+    let time_now = Utc::now().naive_utc();
+    let user_key = sqlx::query_as::<_, UserKey>(
+        r#"INSERT INTO user_keys (user_id,
+         public_key,
+         algorithm,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5)
+         ON CONFLICT (user_id) DO UPDATE
+         SET public_key = EXCLUDED.public_key,
+         algorithm = EXCLUDED.algorithm,
+         updated_at = EXCLUDED.updated_at
+         RETURNING user_keys.*"#,
+    )
+    .bind(user_key.user_id)
+    .bind(user_key.public_key)
+    .bind(user_key.algorithm)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    Ok(user_key)
+}
@@ -1,12 +1,19 @@
 use chrono::Utc;
-use sqlx::query_as;
+use sqlx::{query_as, query_scalar};
 use uuid::Uuid;
 
 use crate::{
-    application::{repository::RepositoryResult, state::SharedState},
-    domain::models::user::User,
+    application::{
+        repository::{RepositoryResult, outbox_repo},
+        state::SharedState,
+    },
+    domain::models::{
+        event::{DomainEvent, UserEvent},
+        user::User,
+    },
 };
 
+#[tracing::instrument(name = "user_repo.list", skip_all, fields(db.system = "postgresql"))]
 pub async fn list(state: &SharedState) -> RepositoryResult<Vec<User>> {
     let users = query_as::<_, User>("SELECT * FROM users")
         .fetch_all(&state.db_pool)
@@ -15,9 +22,11 @@ pub async fn list(state: &SharedState) -> RepositoryResult<Vec<User>> {
     Ok(users)
 }
 
+#[tracing::instrument(name = "user_repo.add", skip_all, fields(db.system = "postgresql"))]
 pub async fn add(user: User, state: &SharedState) -> RepositoryResult<User> {
     let time_now = Utc::now().naive_utc();
     tracing::trace!("user: {:#?}", user);
+    let mut tx = state.db_pool.begin().await?;
     let user = sqlx::query_as::<_, User>(
         r#"INSERT INTO users (id,
          username,
@@ -40,12 +49,16 @@ pub async fn add(user: User, state: &SharedState) -> RepositoryResult<User> {
     .bind(user.roles)
     .bind(time_now)
     .bind(time_now)
-    .fetch_one(&state.db_pool)
+    .fetch_one(&mut *tx)
     .await?;
+    let event = DomainEvent::UserCreated(UserEvent::from(&user));
+    outbox_repo::add(&event, &mut tx).await?;
+    tx.commit().await?;
 
     Ok(user)
 }
 
+#[tracing::instrument(name = "user_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
 pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<User> {
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
         .bind(id)
@@ -54,6 +67,7 @@ pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<User>
     Ok(user)
 }
 
+#[tracing::instrument(name = "user_repo.get_by_username", skip_all, fields(db.system = "postgresql"))]
 pub async fn get_by_username(username: &str, state: &SharedState) -> RepositoryResult<User> {
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
         .bind(username)
@@ -63,9 +77,15 @@ pub async fn get_by_username(username: &str, state: &SharedState) -> RepositoryR
     Ok(user)
 }
 
+#[tracing::instrument(name = "user_repo.update", skip_all, fields(db.system = "postgresql"))]
 pub async fn update(user: User, state: &SharedState) -> RepositoryResult<User> {
     tracing::trace!("user: {:#?}", user);
     let time_now = Utc::now().naive_utc();
+    let mut tx = state.db_pool.begin().await?;
+    let was_active = query_scalar::<_, bool>("SELECT active FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user.id)
+        .fetch_one(&mut *tx)
+        .await?;
     let user = sqlx::query_as::<_, User>(
         r#"UPDATE users
          SET 
@@ -87,17 +107,31 @@ pub async fn update(user: User, state: &SharedState) -> RepositoryResult<User> {
     .bind(user.roles)
     .bind(time_now)
     .bind(user.id)
-    .fetch_one(&state.db_pool)
+    .fetch_one(&mut *tx)
     .await?;
+    let event = DomainEvent::UserUpdated(UserEvent::from(&user));
+    outbox_repo::add(&event, &mut tx).await?;
+    if was_active && !user.active {
+        let event = DomainEvent::UserDeactivated { id: user.id };
+        outbox_repo::add(&event, &mut tx).await?;
+    }
+    tx.commit().await?;
 
     Ok(user)
 }
 
+#[tracing::instrument(name = "user_repo.delete", skip_all, fields(db.system = "postgresql"))]
 pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let mut tx = state.db_pool.begin().await?;
     let query_result = sqlx::query("DELETE FROM users WHERE id = $1")
         .bind(id)
-        .execute(&state.db_pool)
+        .execute(&mut *tx)
         .await?;
+    if query_result.rows_affected() != 1 {
+        return Ok(false);
+    }
+    outbox_repo::add(&DomainEvent::UserDeleted { id }, &mut tx).await?;
+    tx.commit().await?;
 
-    Ok(query_result.rows_affected() == 1)
+    Ok(true)
 }
@@ -0,0 +1,129 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::webhook::{WebhookDelivery, WebhookDeliveryStatus},
+    infrastructure::database::DatabaseConnection,
+};
+
+// Records the delivery, in the transaction queuing its task.
+#[tracing::instrument(name = "webhook_delivery_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(
+    delivery: WebhookDelivery,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<WebhookDelivery> {
+    let time_now = Utc::now().naive_utc();
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"INSERT INTO webhook_deliveries (id,
+         webhook_id,
+         event_id,
+         event,
+         payload,
+         status,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+         RETURNING webhook_deliveries.*"#,
+    )
+    .bind(delivery.id)
+    .bind(delivery.webhook_id)
+    .bind(delivery.event_id)
+    .bind(delivery.event)
+    .bind(delivery.payload)
+    .bind(delivery.status)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(delivery)
+}
+
+#[tracing::instrument(name = "webhook_delivery_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<WebhookDelivery> {
+    let delivery =
+        sqlx::query_as::<_, WebhookDelivery>("SELECT * FROM webhook_deliveries WHERE id = $1")
+            .bind(id)
+            .fetch_one(&state.db_pool)
+            .await?;
+    Ok(delivery)
+}
+
+// The last deliveries of the webhook, the most recent first.
+#[tracing::instrument(name = "webhook_delivery_repo.list_by_webhook", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_by_webhook(
+    webhook_id: Uuid,
+    limit: i64,
+    state: &SharedState,
+) -> RepositoryResult<Vec<WebhookDelivery>> {
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"SELECT * FROM webhook_deliveries
+         WHERE webhook_id = $1
+         ORDER BY created_at DESC
+         LIMIT $2"#,
+    )
+    .bind(webhook_id)
+    .bind(limit)
+    .fetch_all(&state.db_pool)
+    .await?;
+    Ok(deliveries)
+}
+
+// Records the result of an attempt, the delivery is pending until it succeeds or is given up.
+#[tracing::instrument(name = "webhook_delivery_repo.record_attempt", skip_all, fields(db.system = "postgresql"))]
+pub async fn record_attempt(
+    id: Uuid,
+    status: WebhookDeliveryStatus,
+    response_status: Option<i32>,
+    response_body: Option<String>,
+    error: Option<String>,
+    state: &SharedState,
+) -> RepositoryResult<()> {
+    let time_now = Utc::now().naive_utc();
+    sqlx::query(
+        r#"UPDATE webhook_deliveries
+         SET
+         status = $1,
+         attempts = attempts + 1,
+         response_status = $2,
+         response_body = $3,
+         error = $4,
+         updated_at = $5,
+         delivered_at = CASE WHEN $1 = $6 THEN $5 END
+         WHERE id = $7"#,
+    )
+    .bind(status)
+    .bind(response_status)
+    .bind(response_body)
+    .bind(error)
+    .bind(time_now)
+    .bind(WebhookDeliveryStatus::Succeeded)
+    .bind(id)
+    .execute(&state.db_pool)
+    .await?;
+    Ok(())
+}
+
+// Gives up the delivery after its last attempt.
+#[tracing::instrument(name = "webhook_delivery_repo.fail", skip_all, fields(db.system = "postgresql"))]
+pub async fn fail(id: Uuid, state: &SharedState) -> RepositoryResult<()> {
+    sqlx::query("UPDATE webhook_deliveries SET status = $1, updated_at = $2 WHERE id = $3")
+        .bind(WebhookDeliveryStatus::Failed)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&state.db_pool)
+        .await?;
+    Ok(())
+}
+
+// Deletes the deliveries succeeded or failed before the time.
+#[tracing::instrument(name = "webhook_delivery_repo.purge_finished", skip_all, fields(db.system = "postgresql"))]
+pub async fn purge_finished(before: NaiveDateTime, state: &SharedState) -> RepositoryResult<u64> {
+    let query_result =
+        sqlx::query("DELETE FROM webhook_deliveries WHERE status <> $1 AND updated_at < $2")
+            .bind(WebhookDeliveryStatus::Pending)
+            .bind(before)
+            .execute(&state.db_pool)
+            .await?;
+    Ok(query_result.rows_affected())
+}
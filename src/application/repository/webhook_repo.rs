@@ -0,0 +1,99 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    application::{repository::RepositoryResult, state::SharedState},
+    domain::models::{event::EventKind, webhook::Webhook},
+    infrastructure::database::DatabaseConnection,
+};
+
+#[tracing::instrument(name = "webhook_repo.list", skip_all, fields(db.system = "postgresql"))]
+pub async fn list(state: &SharedState) -> RepositoryResult<Vec<Webhook>> {
+    let webhooks = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks ORDER BY created_at")
+        .fetch_all(&state.db_pool)
+        .await?;
+    Ok(webhooks)
+}
+
+// The active webhooks subscribed to the event.
+#[tracing::instrument(name = "webhook_repo.list_subscribed", skip_all, fields(db.system = "postgresql"))]
+pub async fn list_subscribed(
+    event: EventKind,
+    conn: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<Webhook>> {
+    let webhooks =
+        sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE active AND $1 = ANY(events)")
+            .bind(event)
+            .fetch_all(&mut *conn)
+            .await?;
+    Ok(webhooks)
+}
+
+#[tracing::instrument(name = "webhook_repo.get_by_id", skip_all, fields(db.system = "postgresql"))]
+pub async fn get_by_id(id: Uuid, state: &SharedState) -> RepositoryResult<Webhook> {
+    let webhook = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1")
+        .bind(id)
+        .fetch_one(&state.db_pool)
+        .await?;
+    Ok(webhook)
+}
+
+#[tracing::instrument(name = "webhook_repo.add", skip_all, fields(db.system = "postgresql"))]
+pub async fn add(webhook: Webhook, state: &SharedState) -> RepositoryResult<Webhook> {
+    let time_now = Utc::now().naive_utc();
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"INSERT INTO webhooks (id,
+         url,
+         secret,
+         events,
+         active,
+         created_at,
+         updated_at)
+         VALUES ($1,$2,$3,$4,$5,$6,$7)
+         RETURNING webhooks.*"#,
+    )
+    .bind(webhook.id)
+    .bind(webhook.url)
+    .bind(webhook.secret)
+    .bind(webhook.events)
+    .bind(webhook.active)
+    .bind(time_now)
+    .bind(time_now)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(webhook)
+}
+
+#[tracing::instrument(name = "webhook_repo.update", skip_all, fields(db.system = "postgresql"))]
+pub async fn update(webhook: Webhook, state: &SharedState) -> RepositoryResult<Webhook> {
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"UPDATE webhooks
+         SET
+         url = $1,
+         secret = $2,
+         events = $3,
+         active = $4,
+         updated_at = $5
+         WHERE id = $6
+         RETURNING webhooks.*"#,
+    )
+    .bind(webhook.url)
+    .bind(webhook.secret)
+    .bind(webhook.events)
+    .bind(webhook.active)
+    .bind(Utc::now().naive_utc())
+    .bind(webhook.id)
+    .fetch_one(&state.db_pool)
+    .await?;
+    Ok(webhook)
+}
+
+// Deletes the webhook with its deliveries.
+#[tracing::instrument(name = "webhook_repo.delete", skip_all, fields(db.system = "postgresql"))]
+pub async fn delete(id: Uuid, state: &SharedState) -> RepositoryResult<bool> {
+    let query_result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await?;
+    Ok(query_result.rows_affected() == 1)
+}
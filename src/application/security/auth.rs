@@ -1,12 +1,14 @@
+use metrics::counter;
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
     application::{
-        config::Config, repository::user_repo, security::jwt::*, service::token_service,
-        state::SharedState,
+        config::Config, constants::METRICS_AUTH_TOKENS_ISSUED_TOTAL, repository::user_repo,
+        security::jwt::*, service::token_service, state::SharedState,
     },
     domain::models::user::User,
+    infrastructure::token_store::TokenStoreError,
 };
 
 pub struct JwtTokens {
@@ -16,7 +18,7 @@ pub struct JwtTokens {
 
 pub async fn logout(refresh_claims: RefreshClaims, state: SharedState) -> Result<(), AuthError> {
     // Check if revoked tokens are enabled.
-    if !state.config.jwt_enable_revoked_tokens {
+    if !state.config.get().jwt_enable_revoked_tokens {
         Err(AuthError::RevokedTokensInactive)?
     }
 
@@ -38,13 +40,13 @@ pub async fn refresh(
     }
 
     // Check if revoked tokens are enabled.
-    if state.config.jwt_enable_revoked_tokens {
+    if state.config.get().jwt_enable_revoked_tokens {
         revoke_refresh_token(&refresh_claims, &state).await?;
     }
 
     let user_id = refresh_claims.sub.parse().unwrap();
     let user = user_repo::get_by_id(user_id, &state).await?;
-    let tokens = generate_tokens(user, &state.config);
+    let tokens = generate_tokens(user, &state.config.get());
     Ok(tokens)
 }
 
@@ -53,7 +55,7 @@ pub async fn cleanup_revoked_and_expired(
     state: &SharedState,
 ) -> Result<usize, AuthError> {
     // Check if revoked tokens are enabled.
-    if !state.config.jwt_enable_revoked_tokens {
+    if !state.config.get().jwt_enable_revoked_tokens {
         Err(AuthError::RevokedTokensInactive)?
     }
 
@@ -117,10 +119,10 @@ pub fn generate_tokens(user: User, config: &Config) -> JwtTokens {
         roles: user.roles,
     };
 
-    tracing::info!(
-        "JWT: generated claims\naccess {:#?}\nrefresh {:#?}",
-        access_claims,
-        refresh_claims
+    tracing::debug!(
+        "JWT: generated tokens, access jti: {}, refresh jti: {}",
+        access_claims.jti,
+        refresh_claims.jti
     );
 
     let access_token = jsonwebtoken::encode(
@@ -137,11 +139,8 @@ pub fn generate_tokens(user: User, config: &Config) -> JwtTokens {
     )
     .unwrap();
 
-    tracing::info!(
-        "JWT: generated tokens\naccess {:#?}\nrefresh {:#?}",
-        access_token,
-        refresh_token
-    );
+    counter!(METRICS_AUTH_TOKENS_ISSUED_TOTAL, "type" => "access").increment(1);
+    counter!(METRICS_AUTH_TOKENS_ISSUED_TOTAL, "type" => "refresh").increment(1);
 
     JwtTokens {
         access_token,
@@ -179,3 +178,12 @@ pub enum AuthError {
     #[error(transparent)]
     SQLxError(#[from] sqlx::Error),
 }
+
+impl From<TokenStoreError> for AuthError {
+    fn from(error: TokenStoreError) -> Self {
+        match error {
+            TokenStoreError::Redis(e) => Self::RedisError(e),
+            TokenStoreError::Database(e) => Self::SQLxError(e),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    hkdf, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Config, constants::NOTE_TEXT_ENCRYPTED_PREFIX},
+    infrastructure::kms::{KeyManagementService, KmsError, LocalKms},
+};
+
+const DATA_KEY_LEN: usize = 32;
+const HKDF_INFO_TEXT: &[u8] = b"note.text";
+const HKDF_INFO_DIGEST: &[u8] = b"note.text.digest";
+const REF_DIGEST_PREFIX: &[u8] = b"ref\0";
+
+pub type EncryptionResult<T> = Result<T, EncryptionError>;
+
+/// Envelope encryption of the note text: every tenant has its own data key,
+/// wrapped by the master key of the key management service.
+pub struct NoteCipher {
+    kms: Box<dyn KeyManagementService>,
+    data_keys: RwLock<HashMap<Uuid, Arc<DataKey>>>,
+    random: SystemRandom,
+}
+
+/// The encrypted fields of a note, bound to the note and to the field as associated data.
+/// The archive of an import is bound to the import ID the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteField {
+    Text,
+    Title,
+    LinkTarget,
+    ImportArchive,
+}
+
+impl NoteField {
+    // The text is bound to the note ID only, as it was before the other fields were encrypted.
+    fn aad(self, note_id: Uuid) -> Vec<u8> {
+        let field: &[u8] = match self {
+            Self::Text => b"",
+            Self::Title => b"title",
+            Self::LinkTarget => b"link",
+            Self::ImportArchive => b"import",
+        };
+        [note_id.as_bytes().as_slice(), field].concat()
+    }
+}
+
+/// Unwrapped data key of a tenant, with separate subkeys for the text and its digest.
+pub struct DataKey {
+    text: LessSafeKey,
+    digest: hmac::Key,
+}
+
+impl DataKey {
+    pub fn new(key: &[u8]) -> EncryptionResult<Self> {
+        if key.len() != DATA_KEY_LEN {
+            return Err(EncryptionError::InvalidDataKey);
+        }
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(key);
+        let text: UnboundKey = prk
+            .expand(&[HKDF_INFO_TEXT], &AES_256_GCM)
+            .map_err(|_| EncryptionError::InvalidDataKey)?
+            .into();
+        let digest: hmac::Key = prk
+            .expand(&[HKDF_INFO_DIGEST], hmac::HMAC_SHA256)
+            .map_err(|_| EncryptionError::InvalidDataKey)?
+            .into();
+
+        Ok(Self {
+            text: LessSafeKey::new(text),
+            digest,
+        })
+    }
+}
+
+impl NoteCipher {
+    pub fn new(kms: Box<dyn KeyManagementService>) -> Self {
+        Self {
+            kms,
+            data_keys: RwLock::new(HashMap::new()),
+            random: SystemRandom::new(),
+        }
+    }
+
+    pub fn kms(&self) -> &dyn KeyManagementService {
+        self.kms.as_ref()
+    }
+
+    pub fn generate_data_key(&self) -> EncryptionResult<Vec<u8>> {
+        let mut key = vec![0u8; DATA_KEY_LEN];
+        self.random
+            .fill(&mut key)
+            .map_err(|_| EncryptionError::Random)?;
+        Ok(key)
+    }
+
+    pub fn cached_data_key(&self, user_id: Uuid) -> Option<Arc<DataKey>> {
+        self.data_keys
+            .read()
+            .ok()
+            .and_then(|keys| keys.get(&user_id).cloned())
+    }
+
+    pub fn cache_data_key(&self, user_id: Uuid, data_key: DataKey) -> Arc<DataKey> {
+        let data_key = Arc::new(data_key);
+        if let Ok(mut keys) = self.data_keys.write() {
+            keys.insert(user_id, Arc::clone(&data_key));
+        }
+        data_key
+    }
+
+    // The stored text is the prefix followed by the base64 encoded nonce and sealed text, the note ID
+    // and the field are authenticated as associated data so the text cannot be moved to another note
+    // or field.
+    pub fn encrypt(
+        &self,
+        data_key: &DataKey,
+        note_id: Uuid,
+        field: NoteField,
+        text: &str,
+    ) -> EncryptionResult<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random
+            .fill(&mut nonce)
+            .map_err(|_| EncryptionError::Random)?;
+        let mut ciphertext = text.as_bytes().to_vec();
+        data_key
+            .text
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(field.aad(note_id)),
+                &mut ciphertext,
+            )
+            .map_err(|_| EncryptionError::Seal)?;
+
+        let sealed = [nonce.as_slice(), &ciphertext].concat();
+        Ok(format!(
+            "{}{}",
+            NOTE_TEXT_ENCRYPTED_PREFIX,
+            STANDARD.encode(sealed)
+        ))
+    }
+
+    pub fn decrypt(
+        &self,
+        data_key: &DataKey,
+        note_id: Uuid,
+        field: NoteField,
+        text: &str,
+    ) -> EncryptionResult<String> {
+        let sealed = text
+            .strip_prefix(NOTE_TEXT_ENCRYPTED_PREFIX)
+            .ok_or(EncryptionError::Open)?;
+        let sealed = STANDARD.decode(sealed).map_err(|_| EncryptionError::Open)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(EncryptionError::Open);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| EncryptionError::Open)?;
+        let mut ciphertext = ciphertext.to_vec();
+        let plaintext = data_key
+            .text
+            .open_in_place(nonce, Aad::from(field.aad(note_id)), &mut ciphertext)
+            .map_err(|_| EncryptionError::Open)?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|_| EncryptionError::Open)
+    }
+
+    // Keyed digest of the text, finds duplicates without revealing equal texts across tenants.
+    pub fn digest(&self, data_key: &DataKey, text: &str) -> String {
+        STANDARD.encode(hmac::sign(&data_key.digest, text.as_bytes()))
+    }
+
+    // Keyed digest of a note title or link target, compared case-insensitively. Separated from
+    // the digests of the texts by a NUL byte, which a stored text cannot hold.
+    pub fn ref_digest(&self, data_key: &DataKey, reference: &str) -> String {
+        let reference = [REF_DIGEST_PREFIX, reference.to_lowercase().as_bytes()].concat();
+        STANDARD.encode(hmac::sign(&data_key.digest, &reference))
+    }
+}
+
+// Loads the master keys of the note encryption at rest, none when the encryption is disabled.
+pub fn open(config: &Config) -> EncryptionResult<Option<NoteCipher>> {
+    if !config.note_encryption_enabled {
+        return Ok(None);
+    }
+    let current_key_id = Some(config.master_key_id.as_str()).filter(|id| !id.is_empty());
+    let kms = LocalKms::from_file(&config.master_key_file, current_key_id)?;
+    tracing::info!(
+        "Note encryption at rest enabled, master key: {}",
+        kms.current_key_id()
+    );
+    Ok(Some(NoteCipher::new(Box::new(kms))))
+}
+
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    #[error(transparent)]
+    Kms(#[from] KmsError),
+    #[error("invalid data key")]
+    InvalidDataKey,
+    #[error("could not generate random bytes")]
+    Random,
+    #[error("could not encrypt the note text")]
+    Seal,
+    #[error("could not decrypt the note text")]
+    Open,
+    #[error("the note is encrypted at rest, but the encryption is disabled")]
+    Disabled,
+}
+
+impl From<EncryptionError> for sqlx::Error {
+    fn from(e: EncryptionError) -> Self {
+        Self::Decode(Box::new(e))
+    }
+}
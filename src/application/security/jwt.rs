@@ -119,8 +119,8 @@ pub fn decode_token<T: for<'de> serde::Deserialize<'de>>(
     let mut validation = jsonwebtoken::Validation::default();
     validation.leeway = config.jwt_validation_leeway_seconds as u64;
     let token_data = jsonwebtoken::decode::<T>(token, &config.jwt_keys.decoding, &validation)
-        .map_err(|_| {
-            tracing::error!("Invalid token: {}", token);
+        .map_err(|e| {
+            tracing::error!("Invalid token: {}", e);
             AuthError::WrongCredentials
         })?;
 
@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod encryption;
 pub mod jwt;
+pub mod outbound;
 pub mod roles;
@@ -0,0 +1,135 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, LazyLock},
+};
+
+use reqwest::{
+    Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect,
+};
+
+// Client of the URLs targeting public addresses, it never connects to an internal address even
+// when the records of a host change after its validation.
+static PUBLIC_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(redirect::Policy::none())
+        .build()
+        .unwrap_or_default()
+});
+
+// Client of the hosts allowed by `WEBHOOK_ALLOWED_HOSTS`.
+static ALLOWED_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .redirect(redirect::Policy::none())
+        .build()
+        .unwrap_or_default()
+});
+
+/// Resolves the hosts to their public addresses only.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("the host {} has no public address", name.as_str()).into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+// Checks the URL of an outgoing request given by a user: an HTTP(S) URL whose host resolves to
+// public addresses only, unless the host is allowed.
+pub async fn validate_url(url: &str, allowed_hosts: &str) -> Result<(), String> {
+    let url = parse(url)?;
+    let host = host(&url)?;
+    if is_allowed(host, allowed_hosts) {
+        return Ok(());
+    }
+    let port = url.port_or_known_default().unwrap_or_default();
+    let addrs: Vec<IpAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| format!("the host {} does not resolve", host))?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    if addrs.is_empty() || !addrs.into_iter().all(is_public) {
+        return Err("the URL targets a loopback, private or link-local address".to_owned());
+    }
+    Ok(())
+}
+
+// The client sending the requests to the URL, the redirects are not followed. Fails for the URL
+// of an internal address which is not allowed, the hosts are resolved to public addresses only.
+pub fn client(url: &str, allowed_hosts: &str) -> Result<&'static reqwest::Client, String> {
+    let url = parse(url)?;
+    let host = host(&url)?;
+    if is_allowed(host, allowed_hosts) {
+        return Ok(&ALLOWED_CLIENT);
+    }
+    if host.parse().is_ok_and(|ip| !is_public(ip)) {
+        return Err("the URL targets a loopback, private or link-local address".to_owned());
+    }
+    Ok(&PUBLIC_CLIENT)
+}
+
+fn parse(url: &str) -> Result<Url, String> {
+    Url::parse(url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| "expected an HTTP(S) URL".to_owned())
+}
+
+// The host of the URL, the IPv6 addresses without their brackets.
+fn host(url: &Url) -> Result<&str, String> {
+    url.host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+        .ok_or_else(|| "expected an URL with a host".to_owned())
+}
+
+// The allowed hosts are comma separated names or IP addresses, compared case-insensitively.
+fn is_allowed(host: &str, allowed_hosts: &str) -> bool {
+    allowed_hosts
+        .split(',')
+        .map(|allowed| allowed.trim().trim_start_matches('[').trim_end_matches(']'))
+        .any(|allowed| !allowed.is_empty() && allowed.eq_ignore_ascii_case(host))
+}
+
+// Whether the address is reachable on the internet: not a loopback, private, link-local,
+// shared, multicast or reserved address.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(a == 0
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || (a == 100 && (b & 0xc0) == 64)
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or_else(
+            || {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            },
+            |ip| is_public(IpAddr::V4(ip)),
+        ),
+    }
+}
@@ -0,0 +1,331 @@
+use std::io::{self, Read};
+
+use bytes::Bytes;
+use chrono::{NaiveDateTime, Utc};
+use futures::TryStreamExt;
+use serde_json::{Map, Value};
+use tokio::sync::mpsc::Sender;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repository::{RepositoryResult, note_import_repo, note_repo},
+        security::encryption::NoteField,
+        service::{encryption_service, envelope_service, task_service},
+        state::SharedState,
+    },
+    domain::models::{
+        note::Note,
+        note_import::{
+            ArchivedNote, NoteArchiveFormat, NoteImport, NoteImportArchive, NoteImportItem,
+            NoteImportItemStatus, NoteImportStatus,
+        },
+        task::TaskPayload,
+    },
+};
+
+const FRONT_MATTER_DELIMITER: &str = "---";
+const MARKDOWN_EXTENSION: &str = ".md";
+
+// Streams the notes of the user into the sender, encoded in the requested format.
+pub async fn export(
+    user_id: Uuid,
+    format: NoteArchiveFormat,
+    state: SharedState,
+    sender: Sender<io::Result<Bytes>>,
+) {
+    let mut notes = note_repo::stream_by_user(user_id, &state);
+    let mut archive = tar::Builder::new(Vec::new());
+
+    loop {
+        let note = match notes.try_next().await {
+            Ok(Some(note)) => note,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::error!("could not export notes: {}", e);
+                let _ = sender.send(Err(io::Error::other(e))).await;
+                return;
+            }
+        };
+
+        let chunk = match format {
+            NoteArchiveFormat::Ndjson => to_ndjson(&note),
+            NoteArchiveFormat::Tar => {
+                append_to_tar(&mut archive, &note).map(|_| std::mem::take(archive.get_mut()))
+            }
+        };
+        if sender.send(chunk.map(Bytes::from)).await.is_err() {
+            tracing::debug!("notes export cancelled by the client");
+            return;
+        }
+    }
+
+    if format == NoteArchiveFormat::Tar {
+        // Write the end of the archive.
+        let _ = sender.send(archive.into_inner().map(Bytes::from)).await;
+    }
+}
+
+// Parses the archive into notes, unreadable items are kept as failures to be reported.
+pub fn parse(
+    format: NoteArchiveFormat,
+    body: &[u8],
+) -> io::Result<Vec<Result<ArchivedNote, String>>> {
+    match format {
+        NoteArchiveFormat::Ndjson => Ok(parse_ndjson(body)),
+        NoteArchiveFormat::Tar => parse_tar(body),
+    }
+}
+
+// Adds the import and its task in one transaction, the notes are kept in the archive of the import
+// until the import finished, encrypted when the encryption at rest is enabled.
+pub async fn enqueue_import(
+    user_id: Uuid,
+    notes: Vec<Result<ArchivedNote, String>>,
+    state: &SharedState,
+) -> RepositoryResult<NoteImport> {
+    let import_id = Uuid::new_v4();
+    let json = serde_json::to_string(&notes).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let key = envelope_service::note_key(user_id, state).await?;
+    let archive = NoteImportArchive {
+        import_id,
+        notes: match &key {
+            Some(key) => key.encrypt(import_id, NoteField::ImportArchive, &json)?,
+            None => json,
+        },
+        encrypted_at_rest: key.is_some(),
+        created_at: None,
+    };
+
+    let mut tx = state.db_pool.begin().await?;
+    let note_import = note_import_repo::add_with(import_id, user_id, notes.len(), &mut tx).await?;
+    note_import_repo::add_archive(archive, &mut tx).await?;
+    task_service::enqueue_with(TaskPayload::ImportNotes { import_id }, &mut tx, state).await?;
+    tx.commit().await?;
+    Ok(note_import)
+}
+
+// Reads the notes of the archive of the import, none when the archive is gone.
+pub async fn open_archive(
+    note_import: &NoteImport,
+    state: &SharedState,
+) -> RepositoryResult<Option<Vec<Result<ArchivedNote, String>>>> {
+    let Some(archive) = note_import_repo::get_archive(note_import.id, state).await? else {
+        return Ok(None);
+    };
+    let json = if archive.encrypted_at_rest {
+        envelope_service::open_field(
+            note_import.user_id,
+            archive.import_id,
+            NoteField::ImportArchive,
+            &archive.notes,
+            state,
+        )
+        .await?
+    } else {
+        archive.notes
+    };
+    let notes = serde_json::from_str(&json).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    Ok(Some(notes))
+}
+
+// Imports the notes, skipping the ones already present by ID or by content.
+pub async fn import(
+    note_import: NoteImport,
+    notes: Vec<Result<ArchivedNote, String>>,
+    state: SharedState,
+) -> RepositoryResult<NoteImport> {
+    note_import_repo::update_status(note_import.id, NoteImportStatus::Running, vec![], &state)
+        .await?;
+
+    let mut results = Vec::with_capacity(notes.len());
+    for (index, note) in notes.into_iter().enumerate() {
+        let result = match note {
+            Ok(note) => import_note(note, note_import.user_id, &state).await,
+            Err(reason) => Err(reason),
+        };
+        let item = match result {
+            Ok((id, status)) => NoteImportItem {
+                index,
+                id: Some(id),
+                status,
+                reason: None,
+            },
+            Err(reason) => NoteImportItem {
+                index,
+                id: None,
+                status: NoteImportItemStatus::Failed,
+                reason: Some(reason),
+            },
+        };
+        results.push(item);
+    }
+
+    let note_import = note_import_repo::update_status(
+        note_import.id,
+        NoteImportStatus::Completed,
+        results,
+        &state,
+    )
+    .await?;
+    note_import_repo::delete_archive(note_import.id, &state).await?;
+    Ok(note_import)
+}
+
+async fn import_note(
+    note: ArchivedNote,
+    user_id: Uuid,
+    state: &SharedState,
+) -> Result<(Uuid, NoteImportItemStatus), String> {
+    // A note of another user with the same ID is not a duplicate, the note is imported with a new ID.
+    let mut id = note.id.unwrap_or_else(Uuid::new_v4);
+    match note_repo::find_owner(id, state)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        Some(owner) if owner == user_id => return Ok((id, NoteImportItemStatus::Duplicate)),
+        Some(_) => id = Uuid::new_v4(),
+        None => {}
+    }
+    if let Some(id) = note_repo::find_by_content(user_id, &note.text, state)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok((id, NoteImportItemStatus::Duplicate));
+    }
+
+    let note = Note {
+        id,
+        user_id,
+        title: note.title,
+        text: note.text,
+        encryption: note.encryption,
+        created_at: note.created_at,
+        updated_at: note.updated_at,
+    };
+    // The end-to-end encrypted notes are checked as when they are added through the API.
+    encryption_service::validate_note(&note)?;
+    let note = note_repo::add(note, state)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((note.id, NoteImportItemStatus::Created))
+}
+
+fn to_ndjson(note: &Note) -> io::Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(note)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+fn parse_ndjson(body: &[u8]) -> Vec<Result<ArchivedNote, String>> {
+    body.split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| serde_json::from_slice::<ArchivedNote>(line).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn append_to_tar(archive: &mut tar::Builder<Vec<u8>>, note: &Note) -> io::Result<()> {
+    let markdown = to_markdown(note);
+    let mtime = note.updated_at.unwrap_or_else(|| Utc::now().naive_utc());
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(markdown.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime.and_utc().timestamp().max(0) as u64);
+    archive.append_data(
+        &mut header,
+        format!("{}{}", note.id, MARKDOWN_EXTENSION),
+        markdown.as_bytes(),
+    )
+}
+
+fn parse_tar(body: &[u8]) -> io::Result<Vec<Result<ArchivedNote, String>>> {
+    let mut archive = tar::Archive::new(body);
+    let mut notes = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let is_markdown = entry.header().entry_type().is_file()
+            && entry
+                .path()
+                .is_ok_and(|p| p.to_string_lossy().ends_with(MARKDOWN_EXTENSION));
+        if !is_markdown {
+            continue;
+        }
+
+        let mut markdown = String::new();
+        let note = entry
+            .read_to_string(&mut markdown)
+            .map(|_| from_markdown(&markdown))
+            .map_err(|e| e.to_string())
+            .and_then(|note| note);
+        notes.push(note);
+    }
+    Ok(notes)
+}
+
+// Markdown file with a front-matter, values are written as JSON scalars (valid YAML).
+fn to_markdown(note: &Note) -> String {
+    let mut markdown = String::new();
+    markdown.push_str(FRONT_MATTER_DELIMITER);
+    markdown.push('\n');
+    markdown.push_str(&front_matter_line("id", &Value::from(note.id.to_string())));
+    if let Some(title) = note.title.as_deref() {
+        markdown.push_str(&front_matter_line("title", &Value::from(title)));
+    }
+    if let Some(encryption) = note.encryption.as_ref() {
+        let encryption = serde_json::to_value(encryption).unwrap_or_default();
+        markdown.push_str(&front_matter_line("encryption", &encryption));
+    }
+    for (key, timestamp) in [
+        ("created_at", note.created_at),
+        ("updated_at", note.updated_at),
+    ] {
+        if let Some(timestamp) = timestamp {
+            markdown.push_str(&front_matter_line(key, &timestamp_value(timestamp)));
+        }
+    }
+    markdown.push_str(FRONT_MATTER_DELIMITER);
+    markdown.push_str("\n\n");
+    markdown.push_str(&note.text);
+    markdown
+}
+
+fn from_markdown(markdown: &str) -> Result<ArchivedNote, String> {
+    let (mut fields, text) = match markdown
+        .strip_prefix(FRONT_MATTER_DELIMITER)
+        .and_then(|rest| rest.strip_prefix('\n'))
+    {
+        Some(rest) => parse_front_matter(rest)?,
+        None => (Map::new(), markdown),
+    };
+
+    fields.insert("text".to_owned(), Value::from(text));
+    serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())
+}
+
+// Returns the front-matter fields and the remaining text.
+fn parse_front_matter(content: &str) -> Result<(Map<String, Value>, &str), String> {
+    let (front_matter, body) = content
+        .split_once(&format!("\n{}\n", FRONT_MATTER_DELIMITER))
+        .ok_or_else(|| "front-matter is not closed".to_owned())?;
+
+    let mut fields = Map::new();
+    for line in front_matter.lines().filter(|l| !l.trim().is_empty()) {
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("invalid front-matter line: {}", line))?;
+        let value = value.trim();
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+        fields.insert(key.trim().to_owned(), value);
+    }
+
+    Ok((fields, body.strip_prefix('\n').unwrap_or(body)))
+}
+
+fn front_matter_line(key: &str, value: &Value) -> String {
+    format!("{}: {}\n", key, value)
+}
+
+fn timestamp_value(timestamp: NaiveDateTime) -> Value {
+    serde_json::to_value(timestamp).unwrap_or_default()
+}
@@ -0,0 +1,47 @@
+use uuid::Uuid;
+
+use crate::application::{
+    repository::{RepositoryResult, user_repo},
+    state::SharedState,
+};
+
+const MENTION_PREFIX: char = '@';
+
+// Parses the `@username` mentions of the comment text. A mention starts the text
+// or follows a character which cannot be part of a username, so emails are not mentions.
+pub fn parse_mentions(text: &str) -> Vec<&str> {
+    let mut usernames: Vec<&str> = Vec::new();
+    let mut previous = None;
+    for (index, c) in text.char_indices() {
+        if c == MENTION_PREFIX && !previous.is_some_and(is_username_char) {
+            let start = index + c.len_utf8();
+            let end = text[start..]
+                .find(|c: char| !is_username_char(c))
+                .map_or(text.len(), |offset| start + offset);
+            // A trailing dot ends the sentence.
+            let username = text[start..end].trim_end_matches('.');
+            if !username.is_empty() && !usernames.contains(&username) {
+                usernames.push(username);
+            }
+        }
+        previous = Some(c);
+    }
+    usernames
+}
+
+// Resolves the mentions to user IDs, unknown usernames are not mentions.
+pub async fn resolve_mentions(text: &str, state: &SharedState) -> RepositoryResult<Vec<Uuid>> {
+    let mut user_ids = Vec::new();
+    for username in parse_mentions(text) {
+        match user_repo::get_by_username(username, state).await {
+            Ok(user) => user_ids.push(user.id),
+            Err(sqlx::Error::RowNotFound) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(user_ids)
+}
+
+const fn is_username_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
@@ -0,0 +1,93 @@
+use std::{path::Path, time::SystemTime};
+
+use tokio::{
+    signal::unix::{self, SignalKind},
+    time::{Duration, interval},
+};
+
+use crate::{
+    application::{
+        config::{ConfigChange, ConfigErrors, ConfigSources},
+        constants::CONFIG_WATCH_INTERVAL_SECONDS,
+        state::SharedState,
+    },
+    infrastructure::logging,
+};
+
+// Reloads the configuration on SIGHUP and when the `CONFIG_FILE` config file changes.
+pub async fn watch(state: SharedState) {
+    let mut hangup = match unix::signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::error!("failed to install the SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    let config_file = ConfigSources::read().config_file();
+    let mut modified = modified_at(&config_file);
+    let mut ticker = interval(Duration::from_secs(CONFIG_WATCH_INTERVAL_SECONDS));
+
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => tracing::info!("received SIGHUP, reloading the configuration"),
+            _ = ticker.tick() => {
+                let current = modified_at(&config_file);
+                if current == modified {
+                    continue;
+                }
+                modified = current;
+                tracing::info!("config file {} changed, reloading the configuration", config_file);
+            }
+        }
+        // The errors are logged by the reload, the current configuration is kept.
+        let _ = reload(&state);
+    }
+}
+
+fn modified_at(path: &str) -> Option<SystemTime> {
+    if path.is_empty() {
+        return None;
+    }
+    Path::new(path).metadata().and_then(|m| m.modified()).ok()
+}
+
+// Reloads the configuration from the environment, and the `.env` and config files read again.
+pub fn reload(state: &SharedState) -> Result<Vec<ConfigChange>, ConfigErrors> {
+    reload_from(ConfigSources::read(), state)
+}
+
+// Loads and validates the configuration, then swaps in its reloadable settings.
+// The changes of the other settings are reported as requiring a restart.
+pub fn reload_from(
+    sources: ConfigSources,
+    state: &SharedState,
+) -> Result<Vec<ConfigChange>, ConfigErrors> {
+    let new_config = sources.load().inspect_err(|errors| {
+        tracing::error!(
+            "configuration reload rejected, keeping the current configuration:\n{}",
+            errors
+        );
+    })?;
+
+    let current = state.config.get();
+    let changes = current.diff(&new_config);
+    if changes.is_empty() {
+        tracing::info!("configuration reloaded, no changes");
+        return Ok(changes);
+    }
+    for change in &changes {
+        if change.reloadable {
+            tracing::info!("configuration reloaded, {}", change);
+        } else {
+            tracing::warn!("configuration changed, requires a restart, {}", change);
+        }
+    }
+
+    if current.log_level != new_config.log_level {
+        if let Err(e) = logging::set_level(&new_config.log_level) {
+            tracing::error!("failed to apply the log level: {}", e);
+        }
+    }
+    state.config.swap(current.with_reloaded(&new_config));
+    Ok(changes)
+}
@@ -0,0 +1,151 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, Months, NaiveDateTime, TimeDelta, Timelike};
+
+// Days of four years, a valid schedule has an occurrence within them.
+const SEARCH_DAYS: i64 = 4 * 366;
+
+// The fields of a schedule with their ranges.
+const FIELDS: [(&str, u32, u32); 5] = [
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day of month", 1, 31),
+    ("month", 1, 12),
+    ("day of week", 0, 7),
+];
+
+/// Cron schedule with the five fields `minute hour day-of-month month day-of-week`, in UTC.
+///
+/// The fields accept `*`, values, ranges `a-b`, steps `*/n` or `a-b/n` and comma-separated lists.
+/// Sunday is 0 or 7. When both days are restricted, a day matching either of them matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != FIELDS.len() {
+            return Err(format!(
+                "expected 5 fields, minute hour day-of-month month day-of-week: {}",
+                expression
+            ));
+        }
+        let mut sets = [0; 5];
+        for (set, (field, (name, min, max))) in sets.iter_mut().zip(fields.iter().zip(FIELDS)) {
+            *set = parse_field(field, name, min, max)?;
+        }
+        // Sunday is both 0 and 7.
+        let weekdays = if sets[4] & (1 << 7) != 0 {
+            (sets[4] | 1) & !(1 << 7)
+        } else {
+            sets[4]
+        };
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: sets[0],
+            hours: sets[1],
+            days: sets[2],
+            months: sets[3],
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl CronSchedule {
+    // Returns the first occurrence strictly after the given time, at the start of a minute.
+    // A schedule without occurrence, e.g. on February 30, returns none.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        let end = time.checked_add_signed(TimeDelta::days(SEARCH_DAYS))?;
+        while time < end {
+            if !contains(self.months, time.month()) {
+                let month_start = time.date().with_day(1)?.and_hms_opt(0, 0, 0)?;
+                time = month_start.checked_add_months(Months::new(1))?;
+            } else if !self.matches_day(time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !contains(self.hours, time.hour()) {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + TimeDelta::hours(1);
+            } else if !contains(self.minutes, time.minute()) {
+                time += TimeDelta::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, time: NaiveDateTime) -> bool {
+        let day = contains(self.days, time.day());
+        let weekday = contains(self.weekdays, time.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+const fn contains(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+// The values of a field as a bit set.
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_value(step, name, 1, max)?),
+            None => (part, 1),
+        };
+        let (first, last) = if range == "*" {
+            (min, max)
+        } else if let Some((first, last)) = range.split_once('-') {
+            (
+                parse_value(first, name, min, max)?,
+                parse_value(last, name, min, max)?,
+            )
+        } else {
+            let value = parse_value(range, name, min, max)?;
+            // A value with a step runs to the end of the range, e.g. `5/15`.
+            (value, if part.contains('/') { max } else { value })
+        };
+        if first > last {
+            return Err(format!("invalid {} range: {}", name, range));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, name: &str, min: u32, max: u32) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("{} must be between {} and {}: {}", name, min, max, value))
+}
@@ -0,0 +1,38 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::domain::models::note::Note;
+
+// Validates the envelope of an encrypted note, the server never sees the plaintext
+// so only the shape of the ciphertext and its metadata can be checked.
+pub fn validate_note(note: &Note) -> Result<(), String> {
+    let Some(encryption) = note.encryption.as_ref() else {
+        return Ok(());
+    };
+    if encryption.algorithm.trim().is_empty() {
+        return Err("the encryption algorithm must not be empty".to_owned());
+    }
+    if !is_base64(&encryption.nonce) {
+        return Err("the nonce must be base64 encoded".to_owned());
+    }
+    if !is_base64(&note.text) {
+        return Err("the text of an encrypted note must be base64 encoded ciphertext".to_owned());
+    }
+    if note.title.is_some() {
+        return Err("an encrypted note must not have a plaintext title".to_owned());
+    }
+    Ok(())
+}
+
+pub fn validate_key(key: &str, algorithm: &str) -> Result<(), String> {
+    if algorithm.trim().is_empty() {
+        return Err("the key algorithm must not be empty".to_owned());
+    }
+    if !is_base64(key) {
+        return Err("the key must be base64 encoded".to_owned());
+    }
+    Ok(())
+}
+
+fn is_base64(value: &str) -> bool {
+    !value.is_empty() && STANDARD.decode(value).is_ok()
+}
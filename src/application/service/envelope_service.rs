@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        constants::NOTE_REENCRYPTION_BATCH_SIZE,
+        repository::{RepositoryResult, note_link_repo, note_repo, tenant_key_repo},
+        security::encryption::{DataKey, EncryptionError, EncryptionResult, NoteCipher, NoteField},
+        state::SharedState,
+    },
+    domain::models::{
+        note::{Note, StoredNote},
+        tenant_key::TenantKey,
+    },
+    infrastructure::kms::WrappedKey,
+};
+
+/// The data key of a tenant, encrypting the notes of the tenant at rest.
+pub struct NoteKey<'a> {
+    cipher: &'a NoteCipher,
+    data_key: Arc<DataKey>,
+}
+
+impl NoteKey<'_> {
+    pub fn encrypt(&self, note_id: Uuid, field: NoteField, text: &str) -> EncryptionResult<String> {
+        self.cipher.encrypt(&self.data_key, note_id, field, text)
+    }
+
+    pub fn decrypt(&self, note_id: Uuid, field: NoteField, text: &str) -> EncryptionResult<String> {
+        self.cipher.decrypt(&self.data_key, note_id, field, text)
+    }
+
+    pub fn digest(&self, text: &str) -> String {
+        self.cipher.digest(&self.data_key, text)
+    }
+
+    pub fn ref_digest(&self, reference: &str) -> String {
+        self.cipher.ref_digest(&self.data_key, reference)
+    }
+}
+
+/// The text and the title of a note as written to the database.
+pub struct SealedNote {
+    pub text: String,
+    pub text_digest: Option<String>,
+    pub title: Option<String>,
+    pub title_digest: Option<String>,
+    pub encrypted_at_rest: bool,
+}
+
+// The data key of the tenant, none when the encryption at rest is disabled.
+pub async fn note_key(user_id: Uuid, state: &SharedState) -> RepositoryResult<Option<NoteKey<'_>>> {
+    let Some(cipher) = state.note_cipher.as_ref() else {
+        return Ok(None);
+    };
+    let data_key = data_key(cipher, user_id, state).await?;
+    Ok(Some(NoteKey { cipher, data_key }))
+}
+
+// Encrypts the text and the title of the note with the data key of its tenant, returns them with
+// their digests. Both are stored in plaintext when the encryption at rest is disabled.
+pub fn seal(note: &Note, key: Option<&NoteKey>) -> EncryptionResult<SealedNote> {
+    let Some(key) = key else {
+        return Ok(SealedNote {
+            text: note.text.clone(),
+            text_digest: None,
+            title: note.title.clone(),
+            title_digest: None,
+            encrypted_at_rest: false,
+        });
+    };
+    let title = note.title.as_deref();
+    Ok(SealedNote {
+        text: key.encrypt(note.id, NoteField::Text, &note.text)?,
+        text_digest: Some(key.digest(&note.text)),
+        title: title
+            .map(|title| key.encrypt(note.id, NoteField::Title, title))
+            .transpose()?,
+        title_digest: title.map(|title| key.ref_digest(title)),
+        encrypted_at_rest: true,
+    })
+}
+
+// Decrypts the text and the title of the note read from the database, the notes written before the
+// encryption was enabled are returned as is. A title is encrypted when it has a digest.
+pub async fn open(stored: StoredNote, state: &SharedState) -> RepositoryResult<Note> {
+    let mut note = stored.note;
+    if !stored.encrypted_at_rest {
+        return Ok(note);
+    }
+    let key = note_key(note.user_id, state)
+        .await?
+        .ok_or(EncryptionError::Disabled)?;
+    note.text = key.decrypt(note.id, NoteField::Text, &note.text)?;
+    if let (Some(title), Some(_)) = (note.title.as_deref(), stored.title_digest) {
+        note.title = Some(key.decrypt(note.id, NoteField::Title, title)?);
+    }
+    Ok(note)
+}
+
+pub async fn open_all(notes: Vec<StoredNote>, state: &SharedState) -> RepositoryResult<Vec<Note>> {
+    let mut opened = Vec::with_capacity(notes.len());
+    for note in notes {
+        opened.push(open(note, state).await?);
+    }
+    Ok(opened)
+}
+
+// Decrypts a field of a note read without the note, e.g. its title or the target of its link.
+pub async fn open_field(
+    user_id: Uuid,
+    note_id: Uuid,
+    field: NoteField,
+    value: &str,
+    state: &SharedState,
+) -> RepositoryResult<String> {
+    let key = note_key(user_id, state)
+        .await?
+        .ok_or(EncryptionError::Disabled)?;
+    Ok(key.decrypt(note_id, field, value)?)
+}
+
+// Digest of the text for the duplicate lookup, none when the encryption at rest is disabled.
+pub async fn digest(
+    user_id: Uuid,
+    text: &str,
+    state: &SharedState,
+) -> RepositoryResult<Option<String>> {
+    let key = note_key(user_id, state).await?;
+    Ok(key.map(|key| key.digest(text)))
+}
+
+// Re-wraps the data keys after a master key rotation, then encrypts the notes written in plaintext.
+// Rows are locked in batches with SKIP LOCKED, so the job can run on every instance at once, and
+// again after every rotation. Returns the count of the data keys re-wrapped and of the notes encrypted.
+pub async fn reencrypt(state: &SharedState) -> RepositoryResult<(usize, usize)> {
+    let data_keys = rewrap_data_keys(state).await?;
+    tracing::info!("re-wrapped {} data keys", data_keys);
+    let notes = encrypt_plaintext_notes(state).await?;
+    tracing::info!("encrypted {} plaintext notes", notes);
+    Ok((data_keys, notes))
+}
+
+async fn rewrap_data_keys(state: &SharedState) -> RepositoryResult<usize> {
+    let Some(cipher) = state.note_cipher.as_ref() else {
+        return Ok(0);
+    };
+    let master_key_id = cipher.kms().current_key_id().to_owned();
+
+    let mut count = 0;
+    loop {
+        let mut tx = state.db_pool.begin().await?;
+        let tenant_keys =
+            tenant_key_repo::lock_stale(&master_key_id, NOTE_REENCRYPTION_BATCH_SIZE, &mut tx)
+                .await?;
+        if tenant_keys.is_empty() {
+            break;
+        }
+        for mut tenant_key in tenant_keys {
+            tracing::debug!(
+                "re-wrapping data key: {}, master key: {}",
+                tenant_key.user_id,
+                tenant_key.master_key_id
+            );
+            let key = unwrap_data_key(cipher, &tenant_key).await?;
+            let wrapped_key = cipher
+                .kms()
+                .wrap(&key)
+                .await
+                .map_err(EncryptionError::from)?;
+            tenant_key.wrapped_key = STANDARD.encode(wrapped_key.ciphertext);
+            tenant_key.master_key_id = wrapped_key.master_key_id;
+            tenant_key_repo::update_wrapped_key(tenant_key, &mut tx).await?;
+            count += 1;
+        }
+        tx.commit().await?;
+    }
+    Ok(count)
+}
+
+// Encrypts the notes written in plaintext, and the titles and the link targets of the notes whose
+// text only was encrypted.
+async fn encrypt_plaintext_notes(state: &SharedState) -> RepositoryResult<usize> {
+    if state.note_cipher.is_none() {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    loop {
+        let mut tx = state.db_pool.begin().await?;
+        let notes = note_repo::lock_unsealed(NOTE_REENCRYPTION_BATCH_SIZE, &mut tx).await?;
+        if notes.is_empty() {
+            break;
+        }
+        for stored in notes {
+            let note = open(stored, state).await?;
+            let key = note_key(note.user_id, state).await?;
+            let sealed = seal(&note, key.as_ref())?;
+            note_repo::update_sealed(note.id, sealed, &mut tx).await?;
+            note_link_repo::save_links(&note, key.as_ref(), &mut tx).await?;
+            count += 1;
+        }
+        tx.commit().await?;
+    }
+    Ok(count)
+}
+
+// Returns the data key of the tenant, a new key is generated and wrapped on first use.
+async fn data_key(
+    cipher: &NoteCipher,
+    user_id: Uuid,
+    state: &SharedState,
+) -> RepositoryResult<Arc<DataKey>> {
+    if let Some(data_key) = cipher.cached_data_key(user_id) {
+        return Ok(data_key);
+    }
+
+    let tenant_key = match tenant_key_repo::get_by_user(user_id, state).await? {
+        Some(tenant_key) => tenant_key,
+        None => {
+            let key = cipher.generate_data_key()?;
+            let wrapped_key = cipher
+                .kms()
+                .wrap(&key)
+                .await
+                .map_err(EncryptionError::from)?;
+            let tenant_key = TenantKey {
+                user_id,
+                wrapped_key: STANDARD.encode(wrapped_key.ciphertext),
+                master_key_id: wrapped_key.master_key_id,
+                created_at: None,
+                updated_at: None,
+            };
+            // Another instance may have created the key meanwhile, the stored key is used.
+            tenant_key_repo::add(tenant_key, state).await?
+        }
+    };
+
+    let key = unwrap_data_key(cipher, &tenant_key).await?;
+    Ok(cipher.cache_data_key(user_id, DataKey::new(&key)?))
+}
+
+async fn unwrap_data_key(cipher: &NoteCipher, tenant_key: &TenantKey) -> EncryptionResult<Vec<u8>> {
+    let wrapped_key = WrappedKey {
+        master_key_id: tenant_key.master_key_id.clone(),
+        ciphertext: STANDARD
+            .decode(&tenant_key.wrapped_key)
+            .map_err(|_| EncryptionError::InvalidDataKey)?,
+    };
+    Ok(cipher.kms().unwrap(&wrapped_key).await?)
+}
@@ -0,0 +1,240 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{TimeDelta, Utc};
+use futures::future::{self, BoxFuture};
+use sqlx::Connection;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Config,
+        constants::{
+            EVENT_MAX_ATTEMPTS, EVENT_OUTBOX_BATCH_SIZE, EVENT_OUTBOX_POLL_INTERVAL_MILLIS,
+            EVENT_RETRY_BASE_DELAY_SECONDS, EVENT_RETRY_MAX_DELAY_SECONDS, EVENT_SINK_LOG,
+        },
+        repository::{RepositoryResult, outbox_repo},
+        service::{
+            retry_service, token_service::RevocationSubscriber, webhook_service::WebhookSubscriber,
+        },
+        state::SharedState,
+    },
+    domain::models::event::{DomainEvent, EventKind, OutboxEvent, PublishedEvent},
+    infrastructure::{database::DatabaseConnection, token_store::TokenStoreError},
+};
+
+pub type EventResult<T> = Result<T, EventError>;
+
+/// In-process subscriber of the domain events.
+///
+/// The subscriber handles an event in a transaction committed with the record of the event handled,
+/// so its changes in the database are made once. A failed event is handled again after a delay.
+pub trait EventSubscriber: Send + Sync {
+    /// The name recording the events handled by the subscriber, unique among the consumers.
+    fn name(&self) -> &'static str;
+
+    fn accepts(&self, kind: EventKind) -> bool;
+
+    fn handle<'a>(
+        &'a self,
+        event: &'a PublishedEvent,
+        conn: &'a mut DatabaseConnection,
+        state: &'a SharedState,
+    ) -> BoxFuture<'a, EventResult<()>>;
+}
+
+/// Sink sending the domain events out of the service, selected by `EVENT_SINKS`.
+///
+/// An event is sent at least once, it is sent again when the record of the event sent is not committed.
+pub trait EventSink: Send + Sync {
+    /// The name recording the events sent by the sink, unique among the consumers.
+    fn name(&self) -> &'static str;
+
+    fn send<'a>(&'a self, event: &'a PublishedEvent) -> BoxFuture<'a, EventResult<()>>;
+}
+
+/// Dispatches the events of the outbox to the subscribers and the sinks.
+#[derive(Default)]
+pub struct EventDispatcher {
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl EventDispatcher {
+    // The subscribers of the service and the sinks of the configuration.
+    pub fn from_config(config: &Config) -> Self {
+        let mut dispatcher = Self::default()
+            .subscribe(Box::new(WebhookSubscriber))
+            .subscribe(Box::new(RevocationSubscriber));
+        let sinks = config.event_sinks.split(',').map(str::trim);
+        for sink in sinks.filter(|sink| !sink.is_empty()) {
+            if sink.eq_ignore_ascii_case(EVENT_SINK_LOG) {
+                dispatcher = dispatcher.sink(Box::new(LogSink));
+            }
+        }
+        dispatcher
+    }
+
+    #[must_use]
+    pub fn subscribe(mut self, subscriber: Box<dyn EventSubscriber>) -> Self {
+        self.subscribers.push(subscriber);
+        self
+    }
+
+    #[must_use]
+    pub fn sink(mut self, sink: Box<dyn EventSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    // Dispatches the due events, returns the count of the events dispatched or failed. The events
+    // locked by the dispatchers of the other instances are skipped.
+    pub async fn dispatch_pending(&self, state: &SharedState) -> RepositoryResult<usize> {
+        let now = Utc::now().naive_utc();
+        let mut tx = state.db_pool.begin().await?;
+        let events = outbox_repo::lock_pending(now, EVENT_OUTBOX_BATCH_SIZE, &mut tx).await?;
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<Uuid> = events.iter().map(|event| event.id).collect();
+        let handled: HashSet<(Uuid, String)> = outbox_repo::list_consumers(&ids, &mut tx)
+            .await?
+            .into_iter()
+            .collect();
+        for event in &events {
+            match self.dispatch(event, &handled, &mut tx, state).await? {
+                Ok(()) => outbox_repo::mark_dispatched(event.id, &mut tx).await?,
+                Err(error) if event.attempts + 1 >= EVENT_MAX_ATTEMPTS => {
+                    tracing::error!(
+                        "event {} failed after {} attempts: {}",
+                        event.id,
+                        event.attempts + 1,
+                        error
+                    );
+                    outbox_repo::fail(event.id, error, &mut tx).await?;
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        "event {} failed, attempt: {}: {}",
+                        event.id,
+                        event.attempts + 1,
+                        error
+                    );
+                    let next_attempt_at = now + retry_delay(event.attempts + 1);
+                    outbox_repo::retry_at(event.id, next_attempt_at, error, &mut tx).await?;
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(events.len())
+    }
+
+    // Hands the event to the consumers which have not handled it, the errors of the consumers
+    // are joined.
+    async fn dispatch(
+        &self,
+        outbox_event: &OutboxEvent,
+        handled: &HashSet<(Uuid, String)>,
+        conn: &mut DatabaseConnection,
+        state: &SharedState,
+    ) -> RepositoryResult<Result<(), String>> {
+        let event = match DomainEvent::from_parts(outbox_event.event, outbox_event.data.0.clone()) {
+            Ok(event) => PublishedEvent {
+                id: outbox_event.id,
+                created_at: outbox_event.created_at,
+                event,
+            },
+            Err(e) => return Ok(Err(format!("invalid event: {}", e))),
+        };
+        let is_handled = |name: &str| handled.contains(&(event.id, name.to_owned()));
+
+        let mut errors = Vec::new();
+        for subscriber in &self.subscribers {
+            if !subscriber.accepts(event.event.kind()) || is_handled(subscriber.name()) {
+                continue;
+            }
+            // The changes of a failed subscriber are rolled back to the savepoint.
+            let mut savepoint = conn.begin().await?;
+            match subscriber.handle(&event, &mut savepoint, state).await {
+                Ok(()) => {
+                    outbox_repo::add_consumer(event.id, subscriber.name(), &mut savepoint).await?;
+                    savepoint.commit().await?;
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    errors.push(format!("{}: {}", subscriber.name(), e));
+                }
+            }
+        }
+        for sink in &self.sinks {
+            if is_handled(sink.name()) {
+                continue;
+            }
+            match sink.send(&event).await {
+                Ok(()) => outbox_repo::add_consumer(event.id, sink.name(), conn).await?,
+                Err(e) => errors.push(format!("{}: {}", sink.name(), e)),
+            }
+        }
+        Ok(if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        })
+    }
+}
+
+/// Writes the events to the log, as JSON.
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn name(&self) -> &'static str {
+        EVENT_SINK_LOG
+    }
+
+    fn send<'a>(&'a self, event: &'a PublishedEvent) -> BoxFuture<'a, EventResult<()>> {
+        let event = serde_json::to_string(event).unwrap_or_default();
+        tracing::info!(target: "axum_web::events", "{}", event);
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EventError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TokenStore(#[from] TokenStoreError),
+}
+
+// Dispatches the events of the outbox. Every instance runs the dispatcher, an event is
+// dispatched by the instance locking it first.
+pub async fn run_dispatcher(state: SharedState) {
+    let dispatcher = EventDispatcher::from_config(&state.config.get());
+    let poll_interval = Duration::from_millis(EVENT_OUTBOX_POLL_INTERVAL_MILLIS);
+    loop {
+        match dispatcher.dispatch_pending(&state).await {
+            Ok(0) => tokio::time::sleep(poll_interval).await,
+            Ok(dispatched) => tracing::debug!("dispatched events: {}", dispatched),
+            Err(e) => {
+                tracing::error!("could not dispatch the events: {}", e);
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+// Publishes the event of a change made outside the database, e.g. in the token store.
+pub async fn publish(event: DomainEvent, state: &SharedState) -> RepositoryResult<()> {
+    let mut conn = state.db_pool.acquire().await?;
+    outbox_repo::add(&event, &mut conn).await
+}
+
+// The delay before the next dispatch, doubled with every failed attempt.
+fn retry_delay(attempts: i32) -> TimeDelta {
+    retry_service::backoff(
+        attempts,
+        TimeDelta::seconds(EVENT_RETRY_BASE_DELAY_SECONDS),
+        TimeDelta::seconds(EVENT_RETRY_MAX_DELAY_SECONDS),
+    )
+}
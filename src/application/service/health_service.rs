@@ -0,0 +1,123 @@
+use std::{future::Future, time::Duration};
+
+use tokio::time::{Instant, timeout};
+
+use crate::{
+    application::{
+        constants::{HEALTH_CHECK_TIMEOUT_MILLIS, TOKEN_STORE_REDIS},
+        state::SharedState,
+    },
+    domain::models::health::{DependencyHealth, HealthStatus, MigrationHealth, Readiness},
+    infrastructure::database::Database,
+};
+
+// Checks the dependencies concurrently, the service is ready when the required ones are up.
+pub async fn readiness(state: &SharedState) -> Readiness {
+    let (postgres, redis, migrations) = tokio::join!(
+        check_postgres(state),
+        check_redis(state),
+        check_migrations(state)
+    );
+
+    let ready = [&postgres, &redis]
+        .iter()
+        .all(|dependency| !dependency.required || dependency.status == HealthStatus::Up)
+        && migrations.status == HealthStatus::Up;
+
+    Readiness {
+        status: if ready {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        },
+        postgres,
+        redis,
+        migrations,
+    }
+}
+
+async fn check_postgres(state: &SharedState) -> DependencyHealth {
+    timed_ping(true, async {
+        Database::ping(&state.db_pool)
+            .await
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+// Redis is required when it stores the revoked tokens, otherwise it only caches the rendered notes.
+async fn check_redis(state: &SharedState) -> DependencyHealth {
+    let config = state.config.get();
+    let required = config.jwt_enable_revoked_tokens
+        && config.token_store.eq_ignore_ascii_case(TOKEN_STORE_REDIS);
+    timed_ping(required, async {
+        redis::cmd("PING")
+            .query_async::<String>(&mut state.redis.clone())
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+async fn check_migrations(state: &SharedState) -> MigrationHealth {
+    let status = timeout(
+        Duration::from_millis(HEALTH_CHECK_TIMEOUT_MILLIS),
+        Database::migration_status(&state.db_pool),
+    )
+    .await;
+    match status {
+        Ok(Ok(status)) => MigrationHealth {
+            status: if status.pending == 0 && !status.dirty {
+                HealthStatus::Up
+            } else {
+                HealthStatus::Down
+            },
+            applied: status.applied,
+            pending: status.pending,
+            latest_version: status.latest_version,
+            error: status
+                .dirty
+                .then(|| "a migration failed to apply".to_owned()),
+        },
+        Ok(Err(e)) => migrations_down(e.to_string()),
+        Err(_) => migrations_down("timed out".to_owned()),
+    }
+}
+
+fn migrations_down(error: String) -> MigrationHealth {
+    tracing::error!("migration status check failed: {}", error);
+    MigrationHealth {
+        status: HealthStatus::Down,
+        applied: 0,
+        pending: 0,
+        latest_version: None,
+        error: Some(error),
+    }
+}
+
+// Runs the ping within the check timeout and measures its latency.
+async fn timed_ping<F>(required: bool, ping: F) -> DependencyHealth
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let result = timeout(Duration::from_millis(HEALTH_CHECK_TIMEOUT_MILLIS), ping)
+        .await
+        .unwrap_or_else(|_| Err("timed out".to_owned()));
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    if let Err(e) = &result {
+        tracing::error!("health check failed: {}", e);
+    }
+    DependencyHealth {
+        status: if result.is_ok() {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        },
+        required,
+        latency_ms,
+        error: result.err(),
+    }
+}
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, sync::LazyLock, time::Duration};
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use sqlx::{Postgres, Transaction};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Config,
+        constants::JOB_POLL_INTERVAL_SECONDS,
+        repository::{
+            RepositoryResult, job_repo, note_import_repo, outbox_repo, reminder_repo, task_repo,
+            webhook_delivery_repo,
+        },
+        service::{cron_service::CronSchedule, stats_service, token_service},
+        state::SharedState,
+    },
+    domain::models::job::{JobName, JobResponse, JobRun, JobRunStatus, JobTrigger},
+    infrastructure::token_store::TokenStoreError,
+};
+
+/// Transaction holding the advisory lock of a running job, released when it ends.
+pub type JobLock = Transaction<'static, Postgres>;
+
+// The instance recorded in the runs, the host and the process.
+static INSTANCE: LazyLock<String> = LazyLock::new(|| {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
+    format!("{}:{}", host, std::process::id())
+});
+
+#[derive(Debug, Error)]
+enum JobError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    TokenStore(#[from] TokenStoreError),
+}
+
+// Runs the jobs on their schedules. Every instance runs the scheduler, a scheduled occurrence
+// is run by the instance taking the lock of the job first.
+pub async fn run_scheduler(state: SharedState) {
+    let mut next_runs: HashMap<JobName, Option<NaiveDateTime>> = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(JOB_POLL_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        let now = Utc::now().naive_utc();
+        let config = state.config.get();
+        for job in JobName::ALL {
+            let Some(schedule) = schedule(job, &config) else {
+                continue;
+            };
+            let next_run = next_runs
+                .entry(job)
+                .or_insert_with(|| schedule.next_after(now));
+            let Some(scheduled_at) = next_run.filter(|at| *at <= now) else {
+                continue;
+            };
+            *next_run = schedule.next_after(now);
+            let state = SharedState::clone(&state);
+            tokio::spawn(async move {
+                match claim(job, JobTrigger::Scheduled, Some(scheduled_at), &state).await {
+                    Ok(Some((run, lock))) => {
+                        execute(run, lock, &state).await;
+                    }
+                    Ok(None) => tracing::debug!("job {} run by another instance", job.as_str()),
+                    Err(e) => tracing::error!("could not start the job {}: {}", job.as_str(), e),
+                }
+            });
+        }
+    }
+}
+
+// Takes the lock of the job and records the start of the run, none when the job is running
+// on another instance or the scheduled occurrence already ran.
+pub async fn claim(
+    job: JobName,
+    trigger: JobTrigger,
+    scheduled_at: Option<NaiveDateTime>,
+    state: &SharedState,
+) -> RepositoryResult<Option<(JobRun, JobLock)>> {
+    let mut lock = state.db_pool.begin().await?;
+    if !job_repo::try_lock(job, &mut lock).await? {
+        return Ok(None);
+    }
+    let interrupted = job_repo::fail_interrupted(job, state).await?;
+    if interrupted > 0 {
+        tracing::warn!("job {}: {} interrupted runs", job.as_str(), interrupted);
+    }
+
+    let run = JobRun {
+        id: Uuid::new_v4(),
+        job,
+        trigger,
+        status: JobRunStatus::Running,
+        scheduled_at,
+        processed: None,
+        error: None,
+        instance: INSTANCE.clone(),
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+    };
+    Ok(job_repo::start(run, state).await?.map(|run| (run, lock)))
+}
+
+// Runs the claimed job, records its result and releases its lock.
+pub async fn execute(mut run: JobRun, lock: JobLock, state: &SharedState) -> JobRun {
+    tracing::info!("job {} started, run: {}", run.job.as_str(), run.id);
+    let result = perform(run.job, state).await;
+    run.finished_at = Some(Utc::now().naive_utc());
+    match result {
+        Ok(processed) => {
+            tracing::info!(
+                "job {} succeeded, processed: {}",
+                run.job.as_str(),
+                processed
+            );
+            run.status = JobRunStatus::Succeeded;
+            run.processed = Some(i64::try_from(processed).unwrap_or(i64::MAX));
+        }
+        Err(e) => {
+            tracing::error!("job {} failed: {}", run.job.as_str(), e);
+            run.status = JobRunStatus::Failed;
+            run.error = Some(e.to_string());
+        }
+    }
+
+    let run = match job_repo::finish(&run, state).await {
+        Ok(finished) => finished,
+        Err(e) => {
+            tracing::error!("could not record the run {}: {}", run.id, e);
+            run
+        }
+    };
+    // The lock is released with the transaction.
+    if let Err(e) = lock.rollback().await {
+        tracing::error!(
+            "could not release the lock of the job {}: {}",
+            run.job.as_str(),
+            e
+        );
+    }
+    run
+}
+
+// The jobs with their schedules and their last runs.
+pub async fn list(state: &SharedState) -> RepositoryResult<Vec<JobResponse>> {
+    let now = Utc::now().naive_utc();
+    let config = state.config.get();
+    let mut last_runs: HashMap<JobName, JobRun> = job_repo::last_runs(state)
+        .await?
+        .into_iter()
+        .map(|run| (run.job, run))
+        .collect();
+
+    Ok(JobName::ALL
+        .into_iter()
+        .map(|job| JobResponse {
+            name: job,
+            schedule: schedule_expression(job, &config).to_owned(),
+            next_run_at: config
+                .jobs_enabled
+                .then(|| schedule(job, &config))
+                .flatten()
+                .and_then(|schedule| schedule.next_after(now)),
+            last_run: last_runs.remove(&job),
+        })
+        .collect())
+}
+
+fn schedule_expression(job: JobName, config: &Config) -> &str {
+    match job {
+        JobName::TokenCleanup => &config.job_token_cleanup_schedule,
+        JobName::Purge => &config.job_purge_schedule,
+        JobName::StatsRollup => &config.job_stats_rollup_schedule,
+    }
+}
+
+// The schedules are checked when the configuration is loaded.
+fn schedule(job: JobName, config: &Config) -> Option<CronSchedule> {
+    schedule_expression(job, config).parse().ok()
+}
+
+// Runs the job, returning the count of the rows it handled.
+async fn perform(job: JobName, state: &SharedState) -> Result<u64, JobError> {
+    let config = state.config.get();
+    match job {
+        JobName::TokenCleanup => {
+            if !config.jwt_enable_revoked_tokens {
+                return Ok(0);
+            }
+            let deleted = token_service::cleanup_expired(state).await?;
+            Ok(deleted as u64)
+        }
+        JobName::Purge => {
+            let before = Utc::now().naive_utc() - TimeDelta::days(config.job_purge_retention_days);
+            let reminders = reminder_repo::purge_finished(before, state).await?;
+            let imports = note_import_repo::purge_finished(before, state).await?;
+            let runs = job_repo::purge_finished(before, state).await?;
+            let tasks = task_repo::purge_finished(before, state).await?;
+            let events = outbox_repo::purge_dispatched(before, state).await?;
+            let deliveries = webhook_delivery_repo::purge_finished(before, state).await?;
+            tracing::debug!(
+                "purged reminders: {}, note imports: {}, job runs: {}, tasks: {}, events: {}, webhook deliveries: {}",
+                reminders,
+                imports,
+                runs,
+                tasks,
+                events,
+                deliveries
+            );
+            Ok(reminders + imports + runs + tasks + events + deliveries)
+        }
+        JobName::StatsRollup => Ok(stats_service::rebuild(state).await?),
+    }
+}
@@ -0,0 +1,51 @@
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+use uuid::Uuid;
+
+pub const NOTE_LINK_ID_PREFIX: &str = "note:";
+
+/// Wiki-style link between notes: `[[Note Title]]` or `[[note:uuid]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WikiLink {
+    Id(Uuid),
+    Title(String),
+}
+
+impl WikiLink {
+    /// The link target as written in the note text.
+    pub fn target_ref(&self) -> String {
+        match self {
+            Self::Id(id) => format!("{}{}", NOTE_LINK_ID_PREFIX, id),
+            Self::Title(title) => title.clone(),
+        }
+    }
+}
+
+impl From<&str> for WikiLink {
+    fn from(target: &str) -> Self {
+        let target = target.trim();
+        target
+            .strip_prefix(NOTE_LINK_ID_PREFIX)
+            .and_then(|id| id.trim().parse().ok())
+            .map_or_else(|| Self::Title(target.to_owned()), Self::Id)
+    }
+}
+
+// Parses wiki-style links out of the note text, links inside code are ignored.
+pub fn parse_links(text: &str) -> Vec<WikiLink> {
+    let mut links: Vec<WikiLink> = Vec::new();
+    for event in Parser::new_ext(text, Options::ENABLE_WIKILINKS) {
+        if let Event::Start(Tag::Link {
+            link_type: LinkType::WikiLink { .. },
+            dest_url,
+            ..
+        }) = event
+        {
+            let link = WikiLink::from(dest_url.as_ref());
+            if link.target_ref().is_empty() || links.contains(&link) {
+                continue;
+            }
+            links.push(link);
+        }
+    }
+    links
+}
@@ -1 +1,20 @@
+pub mod archive_service;
+pub mod comment_service;
+pub mod config_service;
+pub mod cron_service;
+pub mod encryption_service;
+pub mod envelope_service;
+pub mod event_service;
+pub mod health_service;
+pub mod job_service;
+pub mod link_service;
+pub mod notification_service;
+pub mod recurrence_service;
+pub mod reminder_service;
+pub mod render_service;
+pub mod retry_service;
+pub mod stats_service;
+pub mod task_service;
+pub mod template_service;
 pub mod token_service;
+pub mod webhook_service;
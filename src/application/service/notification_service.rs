@@ -0,0 +1,231 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use futures::future::BoxFuture;
+use reqwest::header::HeaderMap;
+use sqlx::postgres::PgListener;
+use thiserror::Error;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Config,
+        constants::{
+            REMINDER_EVENTS_CHANNEL_CAPACITY, REMINDER_EVENTS_LISTEN_RETRY_SECONDS,
+            REMINDER_EVENTS_NOTIFY_CHANNEL, REMINDER_WEBHOOK_TIMEOUT_SECONDS,
+        },
+        repository::reminder_repo,
+        security::outbound,
+        state::SharedState,
+    },
+    domain::models::{
+        reminder::{Reminder, ReminderChannel, ReminderNotification},
+        user::User,
+    },
+    infrastructure::{
+        database::DatabasePool,
+        mail::{Mail, MailError, Mailer},
+        telemetry,
+    },
+};
+
+pub type NotifyResult<T> = Result<T, NotifyError>;
+
+/// Delivery channel of the reminder notifications.
+pub trait Notifier: Send + Sync {
+    fn notify<'a>(
+        &'a self,
+        reminder: &'a Reminder,
+        notification: &'a ReminderNotification,
+        user: &'a User,
+        config: &'a Config,
+    ) -> BoxFuture<'a, NotifyResult<()>>;
+}
+
+/// The notifiers of the reminder channels.
+pub struct Notifiers {
+    webhook: WebhookNotifier,
+    email: EmailNotifier,
+    sse: SseNotifier,
+}
+
+impl Notifiers {
+    pub fn new(mailer: Box<dyn Mailer>, db_pool: DatabasePool) -> Self {
+        Self {
+            webhook: WebhookNotifier,
+            email: EmailNotifier { mailer },
+            sse: SseNotifier::new(db_pool),
+        }
+    }
+
+    pub fn get(&self, channel: ReminderChannel) -> &dyn Notifier {
+        match channel {
+            ReminderChannel::Webhook => &self.webhook,
+            ReminderChannel::Email => &self.email,
+            ReminderChannel::Sse => &self.sse,
+        }
+    }
+
+    // Subscribes to the notifications of the user pushed over server-sent events.
+    pub fn subscribe(&self, user_id: Uuid) -> broadcast::Receiver<ReminderNotification> {
+        self.sse.subscribe(user_id)
+    }
+}
+
+// Pushes the notifications published by every instance to the event streams connected to this one.
+pub async fn run_listener(state: SharedState) {
+    loop {
+        if let Err(e) = listen(&state).await {
+            tracing::error!("could not listen to the reminder events: {}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(REMINDER_EVENTS_LISTEN_RETRY_SECONDS)).await;
+    }
+}
+
+async fn listen(state: &SharedState) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(&state.db_pool).await?;
+    listener.listen(REMINDER_EVENTS_NOTIFY_CHANNEL).await?;
+    loop {
+        let message = listener.recv().await?;
+        match serde_json::from_str::<ReminderNotification>(message.payload()) {
+            Ok(notification) => state.notifiers.sse.push(&notification),
+            Err(e) => tracing::warn!("invalid reminder event: {}", e),
+        }
+    }
+}
+
+/// Posts the notification as JSON to the URL of the reminder, the internal addresses are
+/// refused unless their host is allowed.
+pub struct WebhookNotifier;
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(
+        &'a self,
+        reminder: &'a Reminder,
+        notification: &'a ReminderNotification,
+        _user: &'a User,
+        config: &'a Config,
+    ) -> BoxFuture<'a, NotifyResult<()>> {
+        Box::pin(async move {
+            let url = reminder
+                .webhook_url
+                .as_deref()
+                .ok_or(NotifyError::MissingWebhookUrl)?;
+            let client = outbound::client(url, &config.webhook_allowed_hosts)
+                .map_err(NotifyError::ForbiddenWebhookUrl)?;
+            let mut headers = HeaderMap::new();
+            telemetry::inject(&tracing::Span::current(), &mut headers);
+            let response = client
+                .post(url)
+                .timeout(Duration::from_secs(REMINDER_WEBHOOK_TIMEOUT_SECONDS))
+                .headers(headers)
+                .json(notification)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(NotifyError::WebhookStatus(response.status().as_u16()));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Mails the notification to the email address of the user.
+pub struct EmailNotifier {
+    mailer: Box<dyn Mailer>,
+}
+
+impl Notifier for EmailNotifier {
+    fn notify<'a>(
+        &'a self,
+        _reminder: &'a Reminder,
+        notification: &'a ReminderNotification,
+        user: &'a User,
+        _config: &'a Config,
+    ) -> BoxFuture<'a, NotifyResult<()>> {
+        Box::pin(async move {
+            let title = notification.title.as_deref().unwrap_or("untitled note");
+            let mail = Mail {
+                to: user.email.clone(),
+                subject: format!("Reminder: {}", title),
+                body: format!(
+                    "Your reminder for the note '{}' ({}) is due at {}.",
+                    title, notification.note_id, notification.due_at
+                ),
+            };
+            Ok(self.mailer.send(&mail).await?)
+        })
+    }
+}
+
+/// Publishes the notification to every instance, each pushes it to the server-sent event
+/// streams of the user connected to it.
+pub struct SseNotifier {
+    senders: Mutex<HashMap<Uuid, broadcast::Sender<ReminderNotification>>>,
+    db_pool: DatabasePool,
+}
+
+impl SseNotifier {
+    fn new(db_pool: DatabasePool) -> Self {
+        Self {
+            senders: Mutex::new(HashMap::new()),
+            db_pool,
+        }
+    }
+
+    fn subscribe(&self, user_id: Uuid) -> broadcast::Receiver<ReminderNotification> {
+        let mut senders = self.senders.lock().unwrap_or_else(|e| e.into_inner());
+        // Forget the users whose streams are all closed.
+        senders.retain(|_, sender| sender.receiver_count() > 0);
+        senders
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(REMINDER_EVENTS_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    fn push(&self, notification: &ReminderNotification) {
+        let mut senders = self.senders.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = senders.get(&notification.user_id) else {
+            return;
+        };
+        if sender.receiver_count() == 0 {
+            senders.remove(&notification.user_id);
+        } else {
+            // The receivers are counted under the lock, the send cannot fail.
+            let _ = sender.send(notification.clone());
+        }
+        drop(senders);
+    }
+}
+
+impl Notifier for SseNotifier {
+    fn notify<'a>(
+        &'a self,
+        _reminder: &'a Reminder,
+        notification: &'a ReminderNotification,
+        _user: &'a User,
+        _config: &'a Config,
+    ) -> BoxFuture<'a, NotifyResult<()>> {
+        // The streams connected when the notification is published receive it, it is not kept.
+        Box::pin(async move {
+            let mut conn = self.db_pool.acquire().await?;
+            Ok(reminder_repo::notify(notification, &mut conn).await?)
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("the reminder has no webhook URL")]
+    MissingWebhookUrl,
+    #[error("the webhook URL is not allowed: {0}")]
+    ForbiddenWebhookUrl(String),
+    #[error("webhook request failed: {0}")]
+    Webhook(#[from] reqwest::Error),
+    #[error("webhook responded with status: {0}")]
+    WebhookStatus(u16),
+    #[error(transparent)]
+    Mail(#[from] MailError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
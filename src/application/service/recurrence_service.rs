@@ -0,0 +1,160 @@
+use std::str::FromStr;
+
+use chrono::{Months, NaiveDate, NaiveDateTime, TimeDelta};
+
+const RRULE_PREFIX: &str = "RRULE:";
+const UNTIL_FORMAT: &str = "%Y%m%dT%H%M%S";
+const UNTIL_DATE_FORMAT: &str = "%Y%m%d";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Recurrence rule, the RFC 5545 RRULE subset with the `FREQ`, `INTERVAL`, `COUNT` and `UNTIL` parts.
+/// Monthly and yearly occurrences falling on a missing day are moved to the last day of the month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix(RRULE_PREFIX).unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid rule part: {}", part))?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => frequency = Some(parse_frequency(value)?),
+                "INTERVAL" => interval = parse_positive(name, value)?,
+                "COUNT" => count = Some(parse_positive(name, value)?),
+                "UNTIL" => until = Some(parse_until(value)?),
+                _ => return Err(format!("unsupported rule part: {}", name)),
+            }
+        }
+
+        if count.is_some() && until.is_some() {
+            return Err("COUNT and UNTIL must not be used together".to_owned());
+        }
+        Ok(Self {
+            frequency: frequency.ok_or_else(|| "FREQ is required".to_owned())?,
+            interval,
+            count,
+            until,
+        })
+    }
+}
+
+impl Recurrence {
+    // Returns the first occurrence strictly after the given time with its index,
+    // the occurrences start at `starts_at`. Missed occurrences are skipped.
+    pub fn next_after(
+        &self,
+        starts_at: NaiveDateTime,
+        after: NaiveDateTime,
+    ) -> Option<(NaiveDateTime, u32)> {
+        let mut index = self.estimate_index(starts_at, after);
+        loop {
+            if self.count.is_some_and(|count| index >= count) {
+                return None;
+            }
+            let occurrence = self.nth(starts_at, index)?;
+            if self.until.is_some_and(|until| occurrence > until) {
+                return None;
+            }
+            if occurrence > after {
+                return Some((occurrence, index));
+            }
+            index = index.checked_add(1)?;
+        }
+    }
+
+    fn nth(&self, starts_at: NaiveDateTime, index: u32) -> Option<NaiveDateTime> {
+        let steps = index.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Monthly => starts_at.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => starts_at.checked_add_months(Months::new(steps.checked_mul(12)?)),
+            _ => {
+                starts_at.checked_add_signed(self.step()?.checked_mul(i32::try_from(steps).ok()?)?)
+            }
+        }
+    }
+
+    // Index of an occurrence at or before the given time, to avoid iterating from the start.
+    fn estimate_index(&self, starts_at: NaiveDateTime, after: NaiveDateTime) -> u32 {
+        if after <= starts_at {
+            return 0;
+        }
+        // Months are between 28 and 31 days, the estimate stays below the occurrence.
+        let elapsed = self.step().map_or_else(
+            || (after - starts_at).num_days() / 31 / self.months_per_step(),
+            |step| (after - starts_at).num_seconds() / step.num_seconds(),
+        );
+        u32::try_from(elapsed / i64::from(self.interval)).unwrap_or(u32::MAX)
+    }
+
+    const fn step(&self) -> Option<TimeDelta> {
+        match self.frequency {
+            Frequency::Minutely => Some(TimeDelta::minutes(1)),
+            Frequency::Hourly => Some(TimeDelta::hours(1)),
+            Frequency::Daily => Some(TimeDelta::days(1)),
+            Frequency::Weekly => Some(TimeDelta::weeks(1)),
+            Frequency::Monthly | Frequency::Yearly => None,
+        }
+    }
+
+    const fn months_per_step(&self) -> i64 {
+        match self.frequency {
+            Frequency::Yearly => 12,
+            _ => 1,
+        }
+    }
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "MINUTELY" => Ok(Frequency::Minutely),
+        "HOURLY" => Ok(Frequency::Hourly),
+        "DAILY" => Ok(Frequency::Daily),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY" => Ok(Frequency::Yearly),
+        _ => Err(format!("unsupported frequency: {}", value)),
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(|| format!("{} must be a positive number: {}", name, value))
+}
+
+// UNTIL is a UTC date-time or a date, which includes the whole day.
+fn parse_until(value: &str) -> Result<NaiveDateTime, String> {
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    NaiveDateTime::parse_from_str(value, UNTIL_FORMAT)
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, UNTIL_DATE_FORMAT)
+                .map(|date| date.and_hms_opt(23, 59, 59).unwrap_or_default())
+        })
+        .map_err(|_| format!("invalid UNTIL: {}", value))
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use futures::{StreamExt, stream};
+
+use crate::{
+    application::{
+        constants::{
+            REMINDER_BATCH_SIZE, REMINDER_DELIVERY_CONCURRENCY, REMINDER_LOCK_SECONDS,
+            REMINDER_MAX_ATTEMPTS, REMINDER_POLL_INTERVAL_SECONDS, REMINDER_RETRY_DELAY_SECONDS,
+        },
+        repository::{RepositoryResult, note_repo, reminder_repo, user_repo},
+        service::{
+            notification_service::{NotifyError, NotifyResult},
+            recurrence_service::Recurrence,
+        },
+        state::SharedState,
+    },
+    domain::models::reminder::{Reminder, ReminderNotification, ReminderStatus},
+};
+
+// Fires the due reminders periodically.
+pub async fn run_scheduler(state: SharedState) {
+    let mut interval = tokio::time::interval(Duration::from_secs(REMINDER_POLL_INTERVAL_SECONDS));
+    loop {
+        interval.tick().await;
+        match fire_due(&state).await {
+            Ok(0) => {}
+            Ok(count) => tracing::debug!("fired {} reminders", count),
+            Err(e) => tracing::error!("could not fire the due reminders: {}", e),
+        }
+    }
+}
+
+// Fires a batch of due reminders. The reminders are claimed in a short transaction, then
+// delivered concurrently without holding it, so several instances can run the scheduler without
+// firing a reminder twice. A failed delivery is recorded on its reminder and retried later.
+pub async fn fire_due(state: &SharedState) -> RepositoryResult<usize> {
+    let now = Utc::now().naive_utc();
+    let locked_until = now + TimeDelta::seconds(REMINDER_LOCK_SECONDS);
+    let reminders = reminder_repo::claim_due(now, locked_until, REMINDER_BATCH_SIZE, state).await?;
+    let count = reminders.len();
+
+    stream::iter(reminders)
+        .for_each_concurrent(REMINDER_DELIVERY_CONCURRENCY, |mut reminder| async move {
+            let result = deliver(&reminder, state).await;
+            schedule_next(&mut reminder, result, now);
+            match reminder_repo::update_schedule(&reminder, state).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!("reminder {} changed while firing", reminder.id),
+                // The reminder is claimed again once its lock expires.
+                Err(e) => tracing::error!("could not record the reminder {}: {}", reminder.id, e),
+            }
+        })
+        .await;
+    Ok(count)
+}
+
+pub fn validate_rrule(rrule: Option<&str>) -> Result<(), String> {
+    rrule.map_or(Ok(()), |rrule| rrule.parse::<Recurrence>().map(|_| ()))
+}
+
+async fn deliver(reminder: &Reminder, state: &SharedState) -> NotifyResult<()> {
+    let title = note_repo::get_title(reminder.note_id, state).await?;
+    let user = user_repo::get_by_id(reminder.user_id, state).await?;
+    let notification = ReminderNotification {
+        reminder_id: reminder.id,
+        note_id: reminder.note_id,
+        user_id: reminder.user_id,
+        title,
+        due_at: reminder.due_at,
+        occurrence: reminder.occurrences + 1,
+    };
+
+    tracing::debug!(
+        "firing reminder: {}, channel: {:?}",
+        reminder.id,
+        reminder.channel
+    );
+    state
+        .notifiers
+        .get(reminder.channel)
+        .notify(reminder, &notification, &user, &state.config.get())
+        .await
+}
+
+// Moves the reminder to its next occurrence, or retries a failed delivery later.
+fn schedule_next(reminder: &mut Reminder, result: Result<(), NotifyError>, now: NaiveDateTime) {
+    match result {
+        Ok(()) => {
+            reminder.occurrences += 1;
+            reminder.attempts = 0;
+            reminder.last_fired_at = Some(now);
+            reminder.last_error = None;
+
+            let next = reminder
+                .rrule
+                .as_deref()
+                .and_then(|rrule| rrule.parse::<Recurrence>().ok())
+                .and_then(|recurrence| {
+                    recurrence.next_after(reminder.starts_at, now.max(reminder.due_at))
+                });
+            match next {
+                Some((due_at, _)) => reminder.due_at = due_at,
+                None => reminder.status = ReminderStatus::Fired,
+            }
+        }
+        Err(e) => {
+            tracing::warn!("could not deliver reminder: {}, error: {}", reminder.id, e);
+            reminder.attempts += 1;
+            reminder.last_error = Some(e.to_string());
+            if reminder.attempts >= REMINDER_MAX_ATTEMPTS {
+                reminder.status = ReminderStatus::Failed;
+            } else {
+                reminder.due_at = now + TimeDelta::seconds(REMINDER_RETRY_DELAY_SECONDS);
+            }
+        }
+    }
+}
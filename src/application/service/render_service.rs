@@ -0,0 +1,144 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use ammonia::Builder;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, html};
+use redis::{AsyncCommands, RedisResult};
+
+use crate::{
+    application::{constants::*, state::SharedState},
+    domain::models::note::{Note, RenderedNote},
+};
+
+// HTML sanitizer based on the default ammonia allowlist,
+// extended with the markup produced by the GFM extensions (task lists, table alignment).
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked", "disabled"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .filter_style_properties(HashSet::from(["text-align"]));
+    builder
+});
+
+pub async fn render_note(note: &Note, state: &SharedState) -> RenderedNote {
+    // The rendered notes are not cached when the notes are encrypted at rest, Redis would
+    // hold their plaintext.
+    if state.note_cipher.is_some() {
+        return render(note);
+    }
+    let key = cache_key(note);
+
+    match get_cached(&key, state).await {
+        Ok(Some(rendered)) => {
+            tracing::trace!("rendered note found in cache: {}", key);
+            return rendered;
+        }
+        Ok(None) => {}
+        Err(e) => tracing::error!("could not read the rendered note from cache: {}", e),
+    }
+
+    let rendered = render(note);
+    if let Err(e) = set_cached(&key, &rendered, state).await {
+        tracing::error!("could not store the rendered note in cache: {}", e);
+    }
+    rendered
+}
+
+// Renders CommonMark with GFM extensions into sanitized HTML and extracts the metadata.
+pub fn render(note: &Note) -> RenderedNote {
+    let parser = Parser::new_ext(&note.text, markdown_options());
+
+    let mut events = Vec::new();
+    let mut plain_text = String::new();
+
+    for event in parser {
+        match &event {
+            Event::Text(text) | Event::Code(text) => plain_text.push_str(text),
+            Event::SoftBreak | Event::HardBreak | Event::Rule => plain_text.push(' '),
+            Event::End(tag) if !is_inline(tag) => plain_text.push(' '),
+            _ => {}
+        }
+        events.push(event);
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+    let html = SANITIZER.clean(&unsafe_html).to_string();
+
+    RenderedNote {
+        id: note.id,
+        version: note.version(),
+        html,
+        title: extract_title(&note.text),
+        word_count: plain_text.split_whitespace().count(),
+    }
+}
+
+// Extracts the text of the first heading.
+pub fn extract_title(text: &str) -> Option<String> {
+    let mut heading: Option<String> = None;
+    for event in Parser::new_ext(text, markdown_options()) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(_)) => {
+                return heading
+                    .map(|h| h.trim().to_owned())
+                    .filter(|h| !h.is_empty());
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = heading.as_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_GFM
+}
+
+const fn is_inline(tag: &TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+fn cache_key(note: &Note) -> String {
+    format!(
+        "{}.{}.{}",
+        NOTE_RENDER_REDIS_KEY_PREFIX,
+        note.id,
+        note.version()
+    )
+}
+
+async fn get_cached(key: &str, state: &SharedState) -> RedisResult<Option<RenderedNote>> {
+    let cached: Option<String> = state.redis.clone().get(key).await?;
+    Ok(cached.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+async fn set_cached(key: &str, rendered: &RenderedNote, state: &SharedState) -> RedisResult<()> {
+    let json = serde_json::to_string(rendered).unwrap_or_default();
+    state
+        .redis
+        .clone()
+        .set_ex(key, json, NOTE_RENDER_CACHE_TTL_SECONDS)
+        .await
+}
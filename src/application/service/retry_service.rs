@@ -0,0 +1,8 @@
+use chrono::TimeDelta;
+
+// The delay before the next attempt, doubled with every failed attempt up to the maximum.
+pub fn backoff(attempts: i32, base: TimeDelta, max: TimeDelta) -> TimeDelta {
+    let exponent = u32::try_from(attempts - 1).unwrap_or_default().min(30);
+    base.checked_mul(1 << exponent)
+        .map_or(max, |delay| delay.min(max))
+}
@@ -0,0 +1,43 @@
+use chrono::Utc;
+use futures::TryStreamExt;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        repository::{RepositoryResult, activity_repo, note_repo, stats_repo},
+        state::SharedState,
+    },
+    domain::models::stats::ActivityKind,
+};
+
+// Recomputes the note stats of every user, measuring first the notes stored without a text length.
+pub async fn rebuild(state: &SharedState) -> RepositoryResult<u64> {
+    let mut notes = note_repo::stream_unmeasured(state);
+    let mut measured = 0;
+    while let Some(note) = notes.try_next().await? {
+        note_repo::update_text_length(&note, state).await?;
+        measured += 1;
+    }
+    tracing::info!("measured the text length of {} notes", measured);
+
+    let users = stats_repo::rebuild(state).await?;
+    tracing::info!("rebuilt the stats of {} users", users);
+    Ok(users)
+}
+
+// Records the login of the user, a failure is logged and does not fail the login.
+pub async fn record_login(user_id: Uuid, state: &SharedState) {
+    let time_now = Utc::now().naive_utc();
+    let result = async {
+        let mut conn = state.db_pool.acquire().await?;
+        activity_repo::record(user_id, ActivityKind::Login, time_now, &mut conn).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::error!(
+            "failed to record the login, user: {}, error: {}",
+            user_id,
+            e
+        );
+    }
+}
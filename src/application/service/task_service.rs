@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use sqlx::types::Json;
+use thiserror::Error;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        constants::{
+            TASK_POLL_INTERVAL_MILLIS, TASK_RETRY_BASE_DELAY_SECONDS, TASK_RETRY_MAX_DELAY_SECONDS,
+        },
+        repository::{RepositoryResult, note_import_repo, task_repo, webhook_delivery_repo},
+        service::{
+            archive_service, retry_service,
+            webhook_service::{self, WebhookError},
+        },
+        state::SharedState,
+    },
+    domain::models::{
+        note_import::NoteImportStatus,
+        task::{Task, TaskPayload, TaskStatus},
+    },
+    infrastructure::database::DatabaseConnection,
+};
+
+#[derive(Debug, Error)]
+enum TaskError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Webhook(#[from] WebhookError),
+}
+
+// Queues the task, run by the first free worker.
+pub async fn enqueue(payload: TaskPayload, state: &SharedState) -> RepositoryResult<Task> {
+    let mut conn = state.db_pool.acquire().await?;
+    enqueue_with(payload, &mut conn, state).await
+}
+
+// Queues the task in the transaction of the caller, it is run once the transaction is committed.
+pub async fn enqueue_with(
+    payload: TaskPayload,
+    conn: &mut DatabaseConnection,
+    state: &SharedState,
+) -> RepositoryResult<Task> {
+    let now = Utc::now().naive_utc();
+    let task = Task {
+        id: Uuid::new_v4(),
+        kind: payload.kind().to_owned(),
+        payload: Json(payload),
+        status: TaskStatus::Queued,
+        attempts: 0,
+        max_attempts: state.config.get().task_max_attempts,
+        run_at: now,
+        locked_until: None,
+        last_error: None,
+        created_at: None,
+        updated_at: None,
+    };
+    task_repo::add(task, conn).await
+}
+
+// Runs the workers, taking the due tasks from the queue concurrently.
+pub async fn run_workers(state: SharedState, workers: usize) {
+    tracing::info!("starting {} task workers", workers);
+    let workers = (0..workers).map(|_| run_worker(SharedState::clone(&state)));
+    futures::future::join_all(workers).await;
+}
+
+async fn run_worker(state: SharedState) {
+    let poll_interval = Duration::from_millis(TASK_POLL_INTERVAL_MILLIS);
+    loop {
+        match work_once(&state).await {
+            Ok(true) => {}
+            Ok(false) => tokio::time::sleep(poll_interval).await,
+            Err(e) => {
+                tracing::error!("could not run the next task: {}", e);
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+// Runs the next due task, false when no task is due.
+pub async fn work_once(state: &SharedState) -> RepositoryResult<bool> {
+    let visibility = TimeDelta::seconds(state.config.get().task_visibility_timeout_seconds);
+    let now = Utc::now().naive_utc();
+    let Some(task) = task_repo::dequeue(now, now + visibility, state).await? else {
+        return Ok(false);
+    };
+
+    // A task taken again after the visibility timeout may have used all its attempts.
+    if task.attempts > task.max_attempts {
+        give_up(&task, "visibility timeout exceeded".to_owned(), state).await?;
+        return Ok(true);
+    }
+
+    tracing::debug!(
+        "running task: {}, kind: {}, attempt: {}",
+        task.id,
+        task.kind,
+        task.attempts
+    );
+    let finished = match run_visible(&task, visibility, state).await {
+        Ok(()) => task_repo::finish(&task, TaskStatus::Succeeded, task.run_at, None, state).await?,
+        Err(e) if task.attempts >= task.max_attempts => {
+            give_up(&task, e.to_string(), state).await?
+        }
+        Err(e) => {
+            tracing::warn!("task {} failed, attempt: {}: {}", task.id, task.attempts, e);
+            let run_at = Utc::now().naive_utc() + retry_delay(task.attempts);
+            task_repo::finish(
+                &task,
+                TaskStatus::Queued,
+                run_at,
+                Some(e.to_string()),
+                state,
+            )
+            .await?
+        }
+    };
+    if !finished {
+        tracing::warn!("task {} was taken by another worker", task.id);
+    }
+    Ok(true)
+}
+
+// The delay before the next attempt, doubled with every failed attempt.
+fn retry_delay(attempts: i32) -> TimeDelta {
+    retry_service::backoff(
+        attempts,
+        TimeDelta::seconds(TASK_RETRY_BASE_DELAY_SECONDS),
+        TimeDelta::seconds(TASK_RETRY_MAX_DELAY_SECONDS),
+    )
+}
+
+// Runs the task, extending its visibility timeout while it runs.
+async fn run_visible(
+    task: &Task,
+    visibility: TimeDelta,
+    state: &SharedState,
+) -> Result<(), TaskError> {
+    let period = (visibility.to_std().unwrap_or_default() / 2).max(Duration::from_secs(1));
+    let mut heartbeat = tokio::time::interval_at(Instant::now() + period, period);
+    let work = perform(&task.payload, state);
+    tokio::pin!(work);
+    loop {
+        tokio::select! {
+            result = &mut work => return result,
+            _ = heartbeat.tick() => {
+                let locked_until = Utc::now().naive_utc() + visibility;
+                match task_repo::extend(task, locked_until, state).await {
+                    Ok(true) => {}
+                    Ok(false) => tracing::warn!("task {} was taken by another worker", task.id),
+                    Err(e) => tracing::error!("could not extend the task {}: {}", task.id, e),
+                }
+            }
+        }
+    }
+}
+
+async fn perform(payload: &TaskPayload, state: &SharedState) -> Result<(), TaskError> {
+    match payload {
+        TaskPayload::ImportNotes { import_id } => {
+            let note_import = note_import_repo::get_by_id(*import_id, state).await?;
+            let Some(notes) = archive_service::open_archive(&note_import, state).await? else {
+                tracing::warn!("the archive of the import {} is gone", import_id);
+                return Ok(());
+            };
+            archive_service::import(note_import, notes, SharedState::clone(state)).await?;
+        }
+        TaskPayload::DeliverWebhook { delivery_id } => {
+            webhook_service::deliver(*delivery_id, state).await?;
+        }
+    }
+    Ok(())
+}
+
+// Moves the task to the dead tasks, the work it was doing is marked failed.
+async fn give_up(task: &Task, error: String, state: &SharedState) -> RepositoryResult<bool> {
+    tracing::error!(
+        "task {} is dead after {} attempts: {}",
+        task.id,
+        task.attempts,
+        error
+    );
+    let result = match &task.payload.0 {
+        TaskPayload::ImportNotes { import_id } => {
+            match note_import_repo::update_status(
+                *import_id,
+                NoteImportStatus::Failed,
+                vec![],
+                state,
+            )
+            .await
+            {
+                Ok(_) => note_import_repo::delete_archive(*import_id, state).await,
+                Err(e) => Err(e),
+            }
+        }
+        TaskPayload::DeliverWebhook { delivery_id } => {
+            webhook_delivery_repo::fail(*delivery_id, state).await
+        }
+    };
+    if let Err(e) = result {
+        tracing::error!(
+            "could not mark the work of the task {} failed: {}",
+            task.id,
+            e
+        );
+    }
+    task_repo::finish(task, TaskStatus::Dead, task.run_at, Some(error), state).await
+}
@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+
+use crate::domain::models::{
+    note_template::{NoteTemplate, TemplatePrompt},
+    user::User,
+};
+
+const PLACEHOLDER_START: &str = "{{";
+const PLACEHOLDER_END: &str = "}}";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Variables available in every template, the dates are in UTC.
+pub const BUILTIN_VARIABLES: [&str; 6] = [
+    "date",
+    "time",
+    "datetime",
+    "user.id",
+    "user.username",
+    "user.email",
+];
+
+// Checks the prompts and that the placeholders of the template are known variables.
+pub fn validate(
+    name: &str,
+    title: Option<&str>,
+    text: &str,
+    prompts: &[TemplatePrompt],
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("the template name must not be empty".to_owned());
+    }
+
+    let mut names = HashSet::new();
+    for prompt in prompts {
+        if !is_variable_name(&prompt.name) || prompt.name.contains('.') {
+            return Err(format!("invalid prompt name: {}", prompt.name));
+        }
+        if BUILTIN_VARIABLES.contains(&prompt.name.as_str()) {
+            return Err(format!("reserved prompt name: {}", prompt.name));
+        }
+        if !names.insert(prompt.name.as_str()) {
+            return Err(format!("duplicate prompt name: {}", prompt.name));
+        }
+    }
+
+    let texts = title.into_iter().chain([text]);
+    for placeholder in texts.flat_map(placeholders) {
+        if !BUILTIN_VARIABLES.contains(&placeholder) && !names.contains(placeholder) {
+            return Err(format!("unknown placeholder: {}", placeholder));
+        }
+    }
+    Ok(())
+}
+
+// Renders the title and the text of the template for the user.
+// Every prompt needs a value or a default.
+pub fn render(
+    template: &NoteTemplate,
+    values: &HashMap<String, String>,
+    user: &User,
+    now: NaiveDateTime,
+) -> Result<(Option<String>, String), String> {
+    if let Some(name) = values
+        .keys()
+        .find(|name| !template.prompts.iter().any(|p| &p.name == *name))
+    {
+        return Err(format!("unknown prompt: {}", name));
+    }
+
+    let mut variables: HashMap<&str, String> = HashMap::from([
+        ("date", now.format(DATE_FORMAT).to_string()),
+        ("time", now.format(TIME_FORMAT).to_string()),
+        ("datetime", now.format(DATETIME_FORMAT).to_string()),
+        ("user.id", user.id.to_string()),
+        ("user.username", user.username.clone()),
+        ("user.email", user.email.clone()),
+    ]);
+    for prompt in template.prompts.iter() {
+        let value = values
+            .get(&prompt.name)
+            .or(prompt.default.as_ref())
+            .ok_or_else(|| format!("missing value for prompt: {}", prompt.name))?;
+        variables.insert(&prompt.name, value.clone());
+    }
+
+    let title = template
+        .title
+        .as_deref()
+        .map(|title| substitute(title, &variables));
+    Ok((title, substitute(&template.text, &variables)))
+}
+
+// Replaces the placeholders, the values are inserted as is and never expanded again.
+fn substitute(text: &str, variables: &HashMap<&str, String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((before, name, after)) = next_placeholder(rest) {
+        rendered.push_str(before);
+        match variables.get(name) {
+            Some(value) => rendered.push_str(value),
+            None => rendered.push_str(&rest[before.len()..rest.len() - after.len()]),
+        }
+        rest = after;
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some((_, name, after)) = next_placeholder(rest) {
+        names.push(name);
+        rest = after;
+    }
+    names
+}
+
+// Finds the next `{{ name }}` placeholder, returns the text before, the name and the text after.
+// Braces around anything else than a variable name are kept as text.
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let mut offset = 0;
+    loop {
+        let start = offset + text[offset..].find(PLACEHOLDER_START)?;
+        let inner_start = start + PLACEHOLDER_START.len();
+        let end = inner_start + text[inner_start..].find(PLACEHOLDER_END)?;
+        let name = text[inner_start..end].trim();
+        if is_variable_name(name) {
+            return Some((&text[..start], name, &text[end + PLACEHOLDER_END.len()..]));
+        }
+        offset = start + 1;
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
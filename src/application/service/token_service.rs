@@ -1,188 +1,161 @@
-use std::collections::HashMap;
+use futures::future::BoxFuture;
+use metrics::counter;
+
+use crate::{
+    application::{
+        constants::*,
+        repository::outbox_repo,
+        security::jwt::{ClaimsMethods, RefreshClaims},
+        service::event_service::{self, EventResult, EventSubscriber},
+        state::SharedState,
+    },
+    domain::models::event::{DomainEvent, EventKind, PublishedEvent},
+    infrastructure::{database::DatabaseConnection, token_store::TokenStoreResult},
+};
 
-use redis::{AsyncCommands, RedisResult, aio::MultiplexedConnection};
-use tokio::sync::MutexGuard;
+/// Subscriber revoking the tokens of the deactivated users.
+pub struct RevocationSubscriber;
 
-use crate::application::{
-    constants::*,
-    security::jwt::{ClaimsMethods, RefreshClaims},
-    state::SharedState,
-};
+impl EventSubscriber for RevocationSubscriber {
+    fn name(&self) -> &'static str {
+        "revocations"
+    }
+
+    fn accepts(&self, kind: EventKind) -> bool {
+        kind == EventKind::UserDeactivated
+    }
+
+    // The revocation is published in the transaction of the subscriber, recorded with the event handled.
+    fn handle<'a>(
+        &'a self,
+        event: &'a PublishedEvent,
+        conn: &'a mut DatabaseConnection,
+        state: &'a SharedState,
+    ) -> BoxFuture<'a, EventResult<()>> {
+        Box::pin(async move {
+            let DomainEvent::UserDeactivated { id } = &event.event else {
+                return Ok(());
+            };
+            if state.config.get().jwt_enable_revoked_tokens {
+                let user_id = id.to_string();
+                store_user_revocation(&user_id, state).await?;
+                let event = DomainEvent::TokensRevoked {
+                    user_id: Some(user_id),
+                };
+                outbox_repo::add(&event, conn).await?;
+            }
+            Ok(())
+        })
+    }
+}
 
-pub async fn revoke_global(state: &SharedState) -> RedisResult<()> {
+pub async fn revoke_global(state: &SharedState) -> TokenStoreResult<()> {
     let timestamp_now = chrono::Utc::now().timestamp() as usize;
     tracing::debug!("setting a timestamp for global revoke: {}", timestamp_now);
-    state
-        .redis
-        .lock()
-        .await
-        .set(JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY, timestamp_now)
-        .await
+    counter!(METRICS_AUTH_TOKENS_REVOKED_TOTAL, "scope" => "all").increment(1);
+    state.token_store.revoke_global(timestamp_now).await?;
+    publish_revoked(None, state).await;
+    Ok(())
+}
+
+pub async fn revoke_user_tokens(user_id: &str, state: &SharedState) -> TokenStoreResult<()> {
+    store_user_revocation(user_id, state).await?;
+    publish_revoked(Some(user_id.to_owned()), state).await;
+    Ok(())
 }
 
-pub async fn revoke_user_tokens(user_id: &str, state: &SharedState) -> RedisResult<()> {
+async fn store_user_revocation(user_id: &str, state: &SharedState) -> TokenStoreResult<()> {
     let timestamp_now = chrono::Utc::now().timestamp() as usize;
     tracing::debug!(
         "adding a timestamp for user revoke, user:{}, timestamp: {}",
         user_id,
         timestamp_now
     );
-    state
-        .redis
-        .lock()
-        .await
-        .hset(JWT_REDIS_REVOKE_USER_BEFORE_KEY, user_id, timestamp_now)
-        .await
+    counter!(METRICS_AUTH_TOKENS_REVOKED_TOTAL, "scope" => "user").increment(1);
+    state.token_store.revoke_user(user_id, timestamp_now).await
 }
 
-async fn is_global_revoked<T: ClaimsMethods + Sync + Send>(
-    claims: &T,
-    redis: &mut MutexGuard<'_, redis::aio::MultiplexedConnection>,
-) -> RedisResult<bool> {
-    // Check in global revoke.
-    let opt_exp: Option<String> = redis.get(JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY).await?;
-    if let Some(exp) = opt_exp {
-        let global_exp = exp.parse::<usize>().unwrap();
-        if global_exp >= claims.get_iat() {
-            return Ok(true);
-        }
+// The revocation is stored out of the database, the event is published once it is stored.
+async fn publish_revoked(user_id: Option<String>, state: &SharedState) {
+    let event = DomainEvent::TokensRevoked { user_id };
+    if let Err(e) = event_service::publish(event, state).await {
+        tracing::error!("could not publish the revocation: {}", e);
     }
-    Ok(false)
 }
 
-async fn is_user_revoked<T: ClaimsMethods + Sync + Send>(
-    claims: &T,
-    redis: &mut MutexGuard<'_, redis::aio::MultiplexedConnection>,
-) -> RedisResult<bool> {
-    // Check in user revoke.
-    let user_id = claims.get_sub();
-    let opt_exp: Option<String> = redis
-        .hget(JWT_REDIS_REVOKE_USER_BEFORE_KEY, user_id)
-        .await?;
-    if let Some(exp) = opt_exp {
-        let global_exp = exp.parse::<usize>().unwrap();
-        if global_exp >= claims.get_iat() {
-            return Ok(true);
-        }
-    }
-
-    Ok(false)
-}
-
-async fn is_token_revoked<T: ClaimsMethods + Sync + Send>(
-    claims: &T,
-    redis: &mut MutexGuard<'_, redis::aio::MultiplexedConnection>,
-) -> RedisResult<bool> {
-    // Check the token in revoked list.
-    redis
-        .hexists(JWT_REDIS_REVOKED_TOKENS_KEY, claims.get_jti())
-        .await
+// Whether the token was issued before the revocation timestamp.
+fn issued_before<T: ClaimsMethods>(claims: &T, revoked_before: Option<usize>) -> bool {
+    revoked_before.is_some_and(|before| before >= claims.get_iat())
 }
 
 pub async fn is_revoked<T: std::fmt::Debug + ClaimsMethods + Send + Sync>(
     claims: &T,
     state: &SharedState,
-) -> RedisResult<bool> {
-    let mut redis = state.redis.lock().await;
+) -> TokenStoreResult<bool> {
+    // Check the global revoke, the user revoke and the revoked list at once.
+    let revocation = state
+        .token_store
+        .revocation(claims.get_sub(), claims.get_jti())
+        .await?;
 
-    let global_revoked = is_global_revoked(claims, &mut redis).await?;
-    if global_revoked {
+    if issued_before(claims, revocation.global_before) {
         tracing::error!("Access denied (globally revoked): {:#?}", claims);
         return Ok(true);
     }
 
-    let user_revoked = is_user_revoked(claims, &mut redis).await?;
-    if user_revoked {
+    if issued_before(claims, revocation.user_before) {
         tracing::error!("Access denied (user revoked): {:#?}", claims);
         return Ok(true);
     }
 
-    let token_revoked = is_token_revoked(claims, &mut redis).await?;
-    if token_revoked {
+    if revocation.token_revoked {
         tracing::error!("Access denied (token revoked): {:#?}", claims);
         return Ok(true);
     }
 
-    drop(redis);
     Ok(false)
 }
 
-pub async fn revoke_refresh_token(claims: &RefreshClaims, state: &SharedState) -> RedisResult<()> {
-    // Adds refersh token and its paired access token into revoked list in Redis.
+pub async fn revoke_refresh_token(
+    claims: &RefreshClaims,
+    state: &SharedState,
+) -> TokenStoreResult<()> {
+    // Adds refersh token and its paired access token into revoked list.
     // Tokens are tracked by JWT ID that handles the cases of reusing lost tokens and multi-device scenarios.
 
-    let list_to_revoke = vec![&claims.jti, &claims.prf];
+    let list_to_revoke = [claims.jti.as_str(), claims.prf.as_str()];
     tracing::debug!("adding jwt tokens into revoked list: {:#?}", list_to_revoke);
 
-    let mut redis = state.redis.lock().await;
-    for claims_jti in list_to_revoke {
-        let _: () = redis
-            .hset(JWT_REDIS_REVOKED_TOKENS_KEY, claims_jti, claims.exp)
-            .await?;
-    }
+    state
+        .token_store
+        .revoke_tokens(&list_to_revoke, claims.exp)
+        .await?;
+
+    counter!(METRICS_AUTH_TOKENS_REVOKED_TOTAL, "scope" => "token").increment(2);
 
     if tracing::enabled!(tracing::Level::TRACE) {
-        log_revoked_tokens_count(&mut redis).await;
+        log_revoked_tokens_count(state).await;
     }
-    drop(redis);
 
     Ok(())
 }
 
-pub async fn cleanup_expired(state: &SharedState) -> RedisResult<usize> {
+pub async fn cleanup_expired(state: &SharedState) -> TokenStoreResult<usize> {
     let timestamp_now = chrono::Utc::now().timestamp() as usize;
 
-    let mut redis = state.redis.lock().await;
-
-    let revoked_tokens: HashMap<String, String> =
-        redis.hgetall(JWT_REDIS_REVOKED_TOKENS_KEY).await?;
-
-    let mut deleted = 0;
-    for (key, exp) in revoked_tokens {
-        match exp.parse::<usize>() {
-            Ok(timestamp_exp) => {
-                if timestamp_now > timestamp_exp {
-                    // Workaround for https://github.com/redis-rs/redis-rs/issues/1322
-                    let _: () = redis.hdel(JWT_REDIS_REVOKED_TOKENS_KEY, key).await?;
-                    deleted += 1;
-                }
-            }
-            Err(e) => {
-                tracing::error!("{}", e);
-            }
-        }
-    }
+    let deleted = state.token_store.cleanup_expired(timestamp_now).await?;
 
     if tracing::enabled!(tracing::Level::TRACE) {
-        log_revoked_tokens_count(&mut redis).await;
+        log_revoked_tokens_count(state).await;
     }
-    drop(redis);
 
     Ok(deleted)
 }
 
-pub async fn log_revoked_tokens_count(redis: &mut MultiplexedConnection) {
-    let redis_result: RedisResult<usize> = redis.hlen(JWT_REDIS_REVOKED_TOKENS_KEY).await;
-    match redis_result {
+pub async fn log_revoked_tokens_count(state: &SharedState) {
+    match state.token_store.revoked_tokens_count().await {
         Ok(revoked_tokens_count) => {
-            tracing::debug!(
-                "REDIS: count of revoked jwt tokens: {}",
-                revoked_tokens_count
-            );
-        }
-        Err(e) => {
-            tracing::error!("{}", e);
-        }
-    }
-}
-
-pub async fn log_revoked_tokens(redis: &mut MultiplexedConnection) {
-    let redis_result: RedisResult<HashMap<String, String>> =
-        redis.hgetall(JWT_REDIS_REVOKED_TOKENS_KEY).await;
-
-    match redis_result {
-        Ok(revoked_tokens) => {
-            tracing::trace!("REDIS: list of revoked jwt tokens: {:#?}", revoked_tokens);
+            tracing::debug!("count of revoked jwt tokens: {}", revoked_tokens_count);
         }
         Err(e) => {
             tracing::error!("{}", e);
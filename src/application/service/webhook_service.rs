@@ -0,0 +1,243 @@
+use std::{fmt::Write, time::Duration};
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+use reqwest::header::{CONTENT_TYPE, HeaderMap};
+use ring::hmac;
+use sqlx::types::Json;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        constants::{
+            WEBHOOK_HEADER_EVENT, WEBHOOK_HEADER_ID, WEBHOOK_HEADER_SIGNATURE,
+            WEBHOOK_HEADER_TIMESTAMP, WEBHOOK_RESPONSE_BODY_MAX_LENGTH, WEBHOOK_TIMEOUT_SECONDS,
+        },
+        repository::{RepositoryResult, webhook_delivery_repo, webhook_repo},
+        security::outbound,
+        service::{
+            event_service::{EventResult, EventSubscriber},
+            task_service,
+        },
+        state::SharedState,
+    },
+    domain::models::{
+        event::{EventKind, PublishedEvent},
+        task::TaskPayload,
+        webhook::{WebhookDelivery, WebhookDeliveryStatus, WebhookPayload},
+    },
+    infrastructure::{database::DatabaseConnection, telemetry},
+};
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("webhook request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("webhook responded with status: {0}")]
+    Status(u16),
+    #[error("webhook URL is forbidden: {0}")]
+    ForbiddenUrl(String),
+}
+
+/// Subscriber queuing a delivery of the events to the webhooks subscribed to them, in the
+/// transaction recording the event handled.
+pub struct WebhookSubscriber;
+
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    fn accepts(&self, _kind: EventKind) -> bool {
+        true
+    }
+
+    fn handle<'a>(
+        &'a self,
+        event: &'a PublishedEvent,
+        conn: &'a mut DatabaseConnection,
+        state: &'a SharedState,
+    ) -> BoxFuture<'a, EventResult<()>> {
+        Box::pin(async move {
+            let kind = event.event.kind();
+            let payload = WebhookPayload {
+                id: event.id,
+                event: kind,
+                created_at: event.created_at,
+                data: event.event.data(),
+            };
+            for webhook in webhook_repo::list_subscribed(kind, conn).await? {
+                queue_delivery(webhook.id, payload.clone(), conn, state).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// Queues a new delivery of the payload of a previous delivery.
+pub async fn redeliver(
+    delivery: WebhookDelivery,
+    state: &SharedState,
+) -> RepositoryResult<WebhookDelivery> {
+    let mut tx = state.db_pool.begin().await?;
+    let delivery = queue_delivery(delivery.webhook_id, delivery.payload.0, &mut tx, state).await?;
+    tx.commit().await?;
+    Ok(delivery)
+}
+
+async fn queue_delivery(
+    webhook_id: Uuid,
+    payload: WebhookPayload,
+    conn: &mut DatabaseConnection,
+    state: &SharedState,
+) -> RepositoryResult<WebhookDelivery> {
+    let delivery = WebhookDelivery {
+        id: Uuid::new_v4(),
+        webhook_id,
+        event_id: payload.id,
+        event: payload.event,
+        payload: Json(payload),
+        status: WebhookDeliveryStatus::Pending,
+        attempts: 0,
+        response_status: None,
+        response_body: None,
+        error: None,
+        created_at: None,
+        updated_at: None,
+        delivered_at: None,
+    };
+    let delivery = webhook_delivery_repo::add(delivery, conn).await?;
+    let task = TaskPayload::DeliverWebhook {
+        delivery_id: delivery.id,
+    };
+    task_service::enqueue_with(task, conn, state).await?;
+    Ok(delivery)
+}
+
+// Posts the payload of the delivery to its webhook and records the attempt. A failed attempt
+// returns an error, retried by the task queue.
+pub async fn deliver(delivery_id: Uuid, state: &SharedState) -> Result<(), WebhookError> {
+    // The deliveries of a deleted webhook are deleted with it.
+    let delivery = match webhook_delivery_repo::get_by_id(delivery_id, state).await {
+        Ok(delivery) => delivery,
+        Err(sqlx::Error::RowNotFound) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if delivery.status != WebhookDeliveryStatus::Pending {
+        return Ok(());
+    }
+    let webhook = webhook_repo::get_by_id(delivery.webhook_id, state).await?;
+    if !webhook.active {
+        let error = Some("the webhook is inactive".to_owned());
+        webhook_delivery_repo::record_attempt(
+            delivery.id,
+            WebhookDeliveryStatus::Failed,
+            None,
+            None,
+            error,
+            state,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // The URL is checked again, the allowed hosts may have changed since the webhook was saved.
+    let client = match outbound::client(&webhook.url, &state.config.get().webhook_allowed_hosts) {
+        Ok(client) => client,
+        Err(reason) => {
+            let error = WebhookError::ForbiddenUrl(reason);
+            webhook_delivery_repo::record_attempt(
+                delivery.id,
+                WebhookDeliveryStatus::Failed,
+                None,
+                None,
+                Some(error.to_string()),
+                state,
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let body = serde_json::to_vec(&delivery.payload.0).unwrap_or_default();
+    let timestamp = Utc::now().timestamp();
+    let mut headers = HeaderMap::new();
+    telemetry::inject(&tracing::Span::current(), &mut headers);
+    let result = client
+        .post(&webhook.url)
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECONDS))
+        .headers(headers)
+        .header(CONTENT_TYPE, "application/json")
+        .header(WEBHOOK_HEADER_ID, delivery.id.to_string())
+        .header(WEBHOOK_HEADER_EVENT, delivery.event.as_str())
+        .header(WEBHOOK_HEADER_TIMESTAMP, timestamp.to_string())
+        .header(
+            WEBHOOK_HEADER_SIGNATURE,
+            sign(&webhook.secret, timestamp, &body),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let error = WebhookError::Request(e);
+            webhook_delivery_repo::record_attempt(
+                delivery.id,
+                WebhookDeliveryStatus::Pending,
+                None,
+                None,
+                Some(error.to_string()),
+                state,
+            )
+            .await?;
+            return Err(error);
+        }
+    };
+    let status = response.status();
+    let response_body = response.text().await.ok().map(|body| {
+        body.chars()
+            .take(WEBHOOK_RESPONSE_BODY_MAX_LENGTH)
+            .collect()
+    });
+    let (delivery_status, error) = if status.is_success() {
+        (WebhookDeliveryStatus::Succeeded, None)
+    } else {
+        (
+            WebhookDeliveryStatus::Pending,
+            Some(WebhookError::Status(status.as_u16())),
+        )
+    };
+    webhook_delivery_repo::record_attempt(
+        delivery.id,
+        delivery_status,
+        Some(i32::from(status.as_u16())),
+        response_body,
+        error.as_ref().map(ToString::to_string),
+        state,
+    )
+    .await?;
+    error.map_or(Ok(()), Err)
+}
+
+// Signs the timestamp and the body with the secret of the webhook, as `sha256=<hex digest>`.
+// The receivers compute the HMAC-SHA256 of `<timestamp>.<body>` to verify the payload.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut context = hmac::Context::with_key(&key);
+    context.update(timestamp.to_string().as_bytes());
+    context.update(b".");
+    context.update(body);
+    let digest = context.sign();
+    digest
+        .as_ref()
+        .iter()
+        .fold(String::from("sha256="), |mut signature, byte| {
+            let _ = write!(signature, "{:02x}", byte);
+            signature
+        })
+}
@@ -1,13 +1,20 @@
 use std::sync::Arc;
 
-use tokio::sync::Mutex;
-
-use crate::{application::config::Config, infrastructure::database::DatabasePool};
+use crate::{
+    application::{
+        config::ConfigHandle, security::encryption::NoteCipher,
+        service::notification_service::Notifiers,
+    },
+    infrastructure::{database::DatabasePool, redis::RedisConnection, token_store::TokenStore},
+};
 
 pub type SharedState = Arc<AppState>;
 
 pub struct AppState {
-    pub config: Config,
+    pub config: ConfigHandle,
     pub db_pool: DatabasePool,
-    pub redis: Mutex<redis::aio::MultiplexedConnection>,
+    pub redis: RedisConnection,
+    pub token_store: Box<dyn TokenStore>,
+    pub note_cipher: Option<NoteCipher>,
+    pub notifiers: Notifiers,
 }
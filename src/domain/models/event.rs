@@ -0,0 +1,176 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    FromRow,
+    types::{Json, Uuid},
+};
+
+use crate::domain::models::{note::Note, user::User};
+
+/// The kinds of the domain events, the names of the events in the outbox and the webhooks.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum EventKind {
+    #[serde(rename = "note.created")]
+    #[sqlx(rename = "note.created")]
+    NoteCreated,
+    #[serde(rename = "note.updated")]
+    #[sqlx(rename = "note.updated")]
+    NoteUpdated,
+    #[serde(rename = "note.deleted")]
+    #[sqlx(rename = "note.deleted")]
+    NoteDeleted,
+    #[serde(rename = "user.created")]
+    #[sqlx(rename = "user.created")]
+    UserCreated,
+    #[serde(rename = "user.updated")]
+    #[sqlx(rename = "user.updated")]
+    UserUpdated,
+    #[serde(rename = "user.deactivated")]
+    #[sqlx(rename = "user.deactivated")]
+    UserDeactivated,
+    #[serde(rename = "user.deleted")]
+    #[sqlx(rename = "user.deleted")]
+    UserDeleted,
+    #[serde(rename = "tokens.revoked")]
+    #[sqlx(rename = "tokens.revoked")]
+    TokensRevoked,
+}
+
+impl EventKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::NoteCreated => "note.created",
+            Self::NoteUpdated => "note.updated",
+            Self::NoteDeleted => "note.deleted",
+            Self::UserCreated => "user.created",
+            Self::UserUpdated => "user.updated",
+            Self::UserDeactivated => "user.deactivated",
+            Self::UserDeleted => "user.deleted",
+            Self::TokensRevoked => "tokens.revoked",
+        }
+    }
+}
+
+/// Change of the domain, written to the outbox in the transaction of the change.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "event", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "note.created")]
+    NoteCreated(NoteEvent),
+    #[serde(rename = "note.updated")]
+    NoteUpdated(NoteEvent),
+    #[serde(rename = "note.deleted")]
+    NoteDeleted { id: Uuid, user_id: Uuid },
+    #[serde(rename = "user.created")]
+    UserCreated(UserEvent),
+    #[serde(rename = "user.updated")]
+    UserUpdated(UserEvent),
+    /// An active user was deactivated, sent after the `user.updated` event.
+    #[serde(rename = "user.deactivated")]
+    UserDeactivated { id: Uuid },
+    #[serde(rename = "user.deleted")]
+    UserDeleted { id: Uuid },
+    /// The tokens of the user were revoked, of every user without a user.
+    #[serde(rename = "tokens.revoked")]
+    TokensRevoked { user_id: Option<String> },
+}
+
+impl DomainEvent {
+    pub const fn kind(&self) -> EventKind {
+        match self {
+            Self::NoteCreated(_) => EventKind::NoteCreated,
+            Self::NoteUpdated(_) => EventKind::NoteUpdated,
+            Self::NoteDeleted { .. } => EventKind::NoteDeleted,
+            Self::UserCreated(_) => EventKind::UserCreated,
+            Self::UserUpdated(_) => EventKind::UserUpdated,
+            Self::UserDeactivated { .. } => EventKind::UserDeactivated,
+            Self::UserDeleted { .. } => EventKind::UserDeleted,
+            Self::TokensRevoked { .. } => EventKind::TokensRevoked,
+        }
+    }
+
+    // The data of the event, stored next to its kind in the outbox.
+    pub fn data(&self) -> serde_json::Value {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(mut event)) => event.remove("data").unwrap_or_default(),
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    pub fn from_parts(kind: EventKind, data: serde_json::Value) -> serde_json::Result<Self> {
+        serde_json::from_value(serde_json::json!({"event": kind, "data": data}))
+    }
+}
+
+/// The note in the events, without its text which may be encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoteEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl From<&Note> for NoteEvent {
+    fn from(note: &Note) -> Self {
+        Self {
+            id: note.id,
+            user_id: note.user_id,
+            title: note.title.clone(),
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+        }
+    }
+}
+
+/// The user in the events, without its credentials.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserEvent {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub active: bool,
+    pub roles: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl From<&User> for UserEvent {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username.clone(),
+            email: user.email.clone(),
+            active: user.active,
+            roles: user.roles.clone(),
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+/// Event of the outbox, dispatched once handled by every subscriber and sink.
+/// A failed dispatch is retried at `next_attempt_at`.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    pub event: EventKind,
+    pub data: Json<serde_json::Value>,
+    pub attempts: i32,
+    pub next_attempt_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub dispatched_at: Option<NaiveDateTime>,
+    pub failed_at: Option<NaiveDateTime>,
+}
+
+/// Domain event read from the outbox, given to the subscribers and sinks.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PublishedEvent {
+    pub id: Uuid,
+    pub created_at: NaiveDateTime,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Status of the service or of one of its dependencies.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Up,
+    Down,
+}
+
+/// Result of the timed ping of a dependency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DependencyHealth {
+    pub status: HealthStatus,
+    /// A failed required dependency makes the service not ready.
+    pub required: bool,
+    pub latency_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Database migrations applied against the migrations of the release.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MigrationHealth {
+    pub status: HealthStatus,
+    pub applied: usize,
+    pub pending: usize,
+    pub latest_version: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Readiness of the service to serve requests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Readiness {
+    pub status: HealthStatus,
+    pub postgres: DependencyHealth,
+    pub redis: DependencyHealth,
+    pub migrations: MigrationHealth,
+}
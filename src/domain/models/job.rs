@@ -0,0 +1,75 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+/// Periodic maintenance job run by the job scheduler.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobName {
+    TokenCleanup,
+    Purge,
+    StatsRollup,
+}
+
+impl JobName {
+    pub const ALL: [Self; 3] = [Self::TokenCleanup, Self::Purge, Self::StatsRollup];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::TokenCleanup => "token_cleanup",
+            Self::Purge => "purge",
+            Self::StatsRollup => "stats_rollup",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|job| job.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// A run of a job, `processed` counts the rows handled by a succeeded run.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job: JobName,
+    pub trigger: JobTrigger,
+    pub status: JobRunStatus,
+    pub scheduled_at: Option<NaiveDateTime>,
+    pub processed: Option<i64>,
+    pub error: Option<String>,
+    pub instance: String,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// A job with its schedule and its last run.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct JobResponse {
+    pub name: JobName,
+    pub schedule: String,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct JobRunsQuery {
+    pub limit: Option<i64>,
+}
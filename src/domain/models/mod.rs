@@ -1,3 +1,16 @@
+pub mod event;
+pub mod health;
+pub mod job;
 pub mod note;
+pub mod note_comment;
+pub mod note_flags;
+pub mod note_import;
+pub mod note_key;
+pub mod note_link;
+pub mod note_template;
+pub mod reminder;
 pub mod stats;
+pub mod task;
+pub mod tenant_key;
 pub mod user;
+pub mod webhook;
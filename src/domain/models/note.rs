@@ -1,12 +1,56 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, types::Uuid};
+use sqlx::{
+    FromRow,
+    types::{Json, Uuid},
+};
 
 #[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct Note {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub title: Option<String>,
     pub text: String,
+    pub encryption: Option<Json<NoteEncryption>>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
 }
+
+/// A note as read from the database, its text is ciphertext when it is encrypted at rest,
+/// and so is its title when it has a digest.
+#[derive(Debug, FromRow)]
+pub struct StoredNote {
+    #[sqlx(flatten)]
+    pub note: Note,
+    pub encrypted_at_rest: bool,
+    pub title_digest: Option<String>,
+}
+
+/// Client-side encryption metadata, the text of an encrypted note holds the base64 ciphertext.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteEncryption {
+    pub algorithm: String,
+    pub nonce: String,
+}
+
+impl Note {
+    pub const fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Version of the note content, derived from the last update time.
+    pub fn version(&self) -> i64 {
+        self.updated_at
+            .map(|t| t.and_utc().timestamp_micros())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct RenderedNote {
+    pub id: Uuid,
+    pub version: i64,
+    pub html: String,
+    pub title: Option<String>,
+    pub word_count: usize,
+}
@@ -0,0 +1,48 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+/// Comment on a note. Root comments start a thread, replies keep the thread of their parent.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteComment {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub thread_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub text: String,
+    pub mentions: Vec<Uuid>,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub resolved_by: Option<Uuid>,
+    pub edited_at: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl NoteComment {
+    pub const fn is_root(&self) -> bool {
+        self.parent_id.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteCommentRequest {
+    pub text: String,
+    pub parent_id: Option<Uuid>,
+}
+
+/// Root comment with all the replies of its thread, oldest first.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteCommentThread {
+    #[serde(flatten)]
+    pub comment: NoteComment,
+    pub replies: Vec<NoteComment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteCommentPage {
+    pub threads: Vec<NoteCommentThread>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+use crate::domain::models::note::Note;
+
+/// Pinned, archived and favorite flags of a note, set by each viewer of the note.
+#[derive(Debug, Default, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteFlags {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub pinned: bool,
+    pub archived: bool,
+    pub favorite: bool,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// Changes the given flags, the others are kept.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteFlagsRequest {
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+    pub favorite: Option<bool>,
+}
+
+/// Filters the listed notes by the flags of the viewer, archived notes are hidden by default.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteFilter {
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+    pub favorite: Option<bool>,
+}
+
+/// Note with the flags of the viewer.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ViewedNote {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub note: Note,
+    pub pinned: bool,
+    pub archived: bool,
+    pub favorite: bool,
+}
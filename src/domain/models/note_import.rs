@@ -0,0 +1,91 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    FromRow,
+    types::{Json, Uuid},
+};
+
+use crate::domain::models::note::NoteEncryption;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteArchiveFormat {
+    #[default]
+    Ndjson,
+    Tar,
+}
+
+impl NoteArchiveFormat {
+    pub const fn content_type(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Tar => "application/x-tar",
+        }
+    }
+
+    pub const fn file_name(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "notes.ndjson",
+            Self::Tar => "notes.tar",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NoteImportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteImportItemStatus {
+    Created,
+    Duplicate,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteImportItem {
+    pub index: usize,
+    pub id: Option<Uuid>,
+    pub status: NoteImportItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteImport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: NoteImportStatus,
+    pub total: i32,
+    pub results: Json<Vec<NoteImportItem>>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// The notes of an import run by the task workers, until the import finished.
+/// The notes are serialized as JSON, encrypted with the data key of the user when encrypted at rest.
+#[derive(Debug, FromRow, PartialEq, Eq, Clone)]
+pub struct NoteImportArchive {
+    pub import_id: Uuid,
+    pub notes: String,
+    pub encrypted_at_rest: bool,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// A note read from an import archive, missing fields are filled in on import.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ArchivedNote {
+    pub id: Option<Uuid>,
+    pub title: Option<String>,
+    pub text: String,
+    pub encryption: Option<Json<NoteEncryption>>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+/// Public key of a user, used by clients to wrap note data keys for the user.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct UserKey {
+    pub user_id: Uuid,
+    pub public_key: String,
+    pub algorithm: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// Data key of an encrypted note, wrapped with the public key of the user.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteKey {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub wrapped_key: String,
+    pub algorithm: String,
+    pub wrapped_by: Uuid,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct UserKeyRequest {
+    pub public_key: String,
+    pub algorithm: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteKeyRequest {
+    pub wrapped_key: String,
+    pub algorithm: String,
+}
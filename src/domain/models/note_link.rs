@@ -0,0 +1,30 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteLink {
+    pub id: Uuid,
+    pub source_id: Uuid,
+    pub target_id: Option<Uuid>,
+    pub target_ref: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteGraphNode {
+    pub id: Uuid,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteGraphEdge {
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteGraph {
+    pub nodes: Vec<NoteGraphNode>,
+    pub edges: Vec<NoteGraphEdge>,
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Json, types::Uuid};
+
+/// Skeleton of a note with `{{placeholder}}` variables.
+/// Global templates have no owner and are managed by admins.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteTemplate {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub name: String,
+    pub title: Option<String>,
+    pub text: String,
+    pub prompts: Json<Vec<TemplatePrompt>>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl NoteTemplate {
+    pub const fn is_global(&self) -> bool {
+        self.user_id.is_none()
+    }
+}
+
+/// Custom variable of a template, its value is given when a note is created from the template.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TemplatePrompt {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NoteTemplateRequest {
+    pub name: String,
+    pub title: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub prompts: Vec<TemplatePrompt>,
+    #[serde(default)]
+    pub global: bool,
+}
+
+/// Values of the template prompts, by prompt name.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct FromTemplateRequest {
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
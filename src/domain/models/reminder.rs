@@ -0,0 +1,69 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderStatus {
+    Pending,
+    Fired,
+    Dismissed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderChannel {
+    Webhook,
+    Email,
+    Sse,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Reminder {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub starts_at: NaiveDateTime,
+    pub due_at: NaiveDateTime,
+    pub rrule: Option<String>,
+    pub channel: ReminderChannel,
+    pub webhook_url: Option<String>,
+    pub status: ReminderStatus,
+    pub occurrences: i32,
+    pub attempts: i32,
+    pub last_fired_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    /// The reminder is being delivered by an instance until the time.
+    pub locked_until: Option<NaiveDateTime>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ReminderRequest {
+    pub due_at: NaiveDateTime,
+    pub rrule: Option<String>,
+    pub channel: ReminderChannel,
+    pub webhook_url: Option<String>,
+}
+
+/// Snoozes the reminder for the given minutes, or until the given time.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct SnoozeRequest {
+    pub minutes: Option<i64>,
+    pub until: Option<NaiveDateTime>,
+}
+
+/// Payload delivered to the reminder channels, the note text is never included.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ReminderNotification {
+    pub reminder_id: Uuid,
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub title: Option<String>,
+    pub due_at: NaiveDateTime,
+    pub occurrence: i32,
+}
@@ -1,18 +1,117 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, types::Uuid};
 
+/// Note statistics of a user, maintained with the note changes.
 #[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct StatResponse {
     pub id: Uuid,
     pub user_id: Uuid,
     pub notes: i32,
+    pub notes_created: i32,
+    pub notes_deleted: i32,
+    pub notes_edited: i32,
+    pub total_chars: i64,
+    pub last_activity_at: Option<NaiveDateTime>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
 }
 
+/// Change of the note statistics of a user.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct StatsDelta {
+    pub notes: i32,
+    pub notes_created: i32,
+    pub notes_deleted: i32,
+    pub notes_edited: i32,
+    pub total_chars: i64,
+}
+
+/// Kind of a recorded user activity.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    NoteCreated,
+    NoteEdited,
+    NoteDeleted,
+    Login,
+}
+
+/// Metric of the activity time series.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityMetric {
+    NotesCreated,
+    NotesEdited,
+    Logins,
+    ActiveUsers,
+}
+
+impl ActivityMetric {
+    /// Kinds of the activity events counted by the metric.
+    pub const fn kinds(&self) -> &'static [&'static str] {
+        match self {
+            Self::NotesCreated => &["note_created"],
+            Self::NotesEdited => &["note_edited"],
+            Self::Logins => &["login"],
+            Self::ActiveUsers => &["note_created", "note_edited", "note_deleted", "login"],
+        }
+    }
+
+    /// Whether the metric counts the distinct users instead of the events.
+    pub const fn is_distinct_users(&self) -> bool {
+        matches!(self, Self::ActiveUsers)
+    }
+}
+
+/// Width of the time series buckets, weeks start on Monday.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBucket {
+    #[default]
+    Day,
+    Week,
+}
+
+impl TimeBucket {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    pub const fn days(&self) -> i64 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+        }
+    }
+}
+
+/// Time series query, the dates are inclusive and in UTC.
+/// Without a user the series is system-wide.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TimeseriesQuery {
+    pub metric: ActivityMetric,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub bucket: TimeBucket,
+    pub user_id: Option<Uuid>,
+}
+
 #[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
-pub struct StatRequest {
-    pub user_id: Uuid,
-    pub value: i32,
+pub struct TimeseriesPoint {
+    pub bucket: NaiveDate,
+    pub value: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Timeseries {
+    pub metric: ActivityMetric,
+    pub bucket: TimeBucket,
+    pub user_id: Option<Uuid>,
+    pub points: Vec<TimeseriesPoint>,
 }
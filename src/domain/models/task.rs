@@ -0,0 +1,73 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    FromRow,
+    types::{Json, Uuid},
+};
+
+/// Work run by the task workers outside of the requests.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskPayload {
+    /// Imports the notes of the archive of the import record.
+    ImportNotes { import_id: Uuid },
+    /// Posts an event to a webhook, one attempt of the delivery.
+    DeliverWebhook { delivery_id: Uuid },
+}
+
+impl TaskPayload {
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::ImportNotes { .. } => "import_notes",
+            Self::DeliverWebhook { .. } => "deliver_webhook",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Dead,
+}
+
+/// A queued task. A running task is invisible to the other workers until `locked_until`,
+/// after which it is run again.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Task {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: Json<TaskPayload>,
+    pub status: TaskStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// A task without its payload, listed by the admins.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TaskSummary {
+    pub id: Uuid,
+    pub kind: String,
+    pub status: TaskStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TaskQuery {
+    pub status: Option<TaskStatus>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,13 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Uuid};
+
+/// Data key of a tenant, wrapped by a master key and base64 encoded.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TenantKey {
+    pub user_id: Uuid,
+    pub wrapped_key: String,
+    pub master_key_id: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
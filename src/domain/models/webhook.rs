@@ -0,0 +1,75 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    FromRow,
+    types::{Json, Uuid},
+};
+
+use crate::domain::models::event::EventKind;
+
+/// Subscription of an URL to events. The secret signs the payloads and is never returned.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    pub events: Vec<EventKind>,
+    pub active: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<EventKind>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+const fn default_active() -> bool {
+    true
+}
+
+/// The body posted to the webhook.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct WebhookPayload {
+    pub id: Uuid,
+    pub event: EventKind,
+    pub created_at: NaiveDateTime,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// Delivery of an event to a webhook, with the result of its last attempt.
+#[derive(Debug, FromRow, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event: EventKind,
+    pub payload: Json<WebhookPayload>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct WebhookDeliveriesQuery {
+    pub limit: Option<i64>,
+}
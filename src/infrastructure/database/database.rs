@@ -1,4 +1,4 @@
-use sqlx::{PgConnection, PgPool};
+use sqlx::{PgConnection, PgPool, migrate::Migrator};
 use thiserror::Error;
 
 use crate::infrastructure::database::postgres::{PostgresDatabase, PostgresOptions};
@@ -7,6 +7,8 @@ pub type DatabasePool = PgPool;
 pub type DatabaseConnection = PgConnection;
 pub type TestDatabase = PostgresDatabase;
 
+static MIGRATOR: Migrator = sqlx::migrate!("src/infrastructure/database/postgres/migrations");
+
 #[derive(Clone, Debug)]
 pub struct DatabaseOptions {
     pub postgres: PostgresOptions,
@@ -33,12 +35,50 @@ impl Database {
     }
 
     pub async fn migrate(pool: &DatabasePool) -> Result<(), DatabaseError> {
-        sqlx::migrate!("src/infrastructure/database/postgres/migrations")
-            .run(pool)
-            .await?;
+        MIGRATOR.run(pool).await?;
+
+        Ok(())
+    }
+
+    pub async fn ping(pool: &DatabasePool) -> Result<(), DatabaseError> {
+        sqlx::query("SELECT 1").execute(pool).await?;
 
         Ok(())
     }
+
+    // Compares the applied migrations with the migrations of the release.
+    pub async fn migration_status(pool: &DatabasePool) -> Result<MigrationStatus, DatabaseError> {
+        let applied: Vec<(i64, bool)> =
+            sqlx::query_as("SELECT version, success FROM _sqlx_migrations")
+                .fetch_all(pool)
+                .await?;
+
+        let pending = MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| {
+                !applied
+                    .iter()
+                    .any(|(version, success)| *version == migration.version && *success)
+            })
+            .count();
+
+        Ok(MigrationStatus {
+            applied: applied.iter().filter(|(_, success)| *success).count(),
+            pending,
+            dirty: applied.iter().any(|(_, success)| !success),
+            latest_version: applied.iter().map(|(version, _)| *version).max(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub applied: usize,
+    pub pending: usize,
+    // A migration failed and was left partially applied.
+    pub dirty: bool,
+    pub latest_version: Option<i64>,
 }
 
 #[derive(Error, Debug)]
@@ -2,6 +2,7 @@ mod database;
 mod postgres;
 
 pub use database::{
-    Database, DatabaseConnection, DatabaseError, DatabaseOptions, DatabasePool, TestDatabase,
+    Database, DatabaseConnection, DatabaseError, DatabaseOptions, DatabasePool, MigrationStatus,
+    TestDatabase,
 };
 pub use postgres::PostgresOptions;
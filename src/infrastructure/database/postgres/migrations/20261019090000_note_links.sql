@@ -0,0 +1,13 @@
+-- add note titles
+ALTER TABLE notes ADD COLUMN title TEXT;
+CREATE INDEX notes_user_id_title_idx ON notes (user_id, lower(title));
+-- create note links table
+CREATE TABLE note_links (
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    source_id UUID NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+    target_id UUID REFERENCES notes (id) ON DELETE SET NULL,
+    target_ref TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE (source_id, target_ref)
+);
+CREATE INDEX note_links_target_id_idx ON note_links (target_id);
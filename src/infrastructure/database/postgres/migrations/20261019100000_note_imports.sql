@@ -0,0 +1,12 @@
+-- index notes by content hash for import deduplication
+CREATE INDEX notes_user_id_text_hash_idx ON notes (user_id, md5(text));
+-- create note imports table
+CREATE TABLE note_imports (
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    status TEXT NOT NULL,
+    total INTEGER NOT NULL,
+    results JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
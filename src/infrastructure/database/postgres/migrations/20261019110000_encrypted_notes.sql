@@ -0,0 +1,22 @@
+-- add client-side encryption metadata to notes
+ALTER TABLE notes ADD COLUMN encryption JSONB;
+-- create user public keys table
+CREATE TABLE user_keys (
+    user_id UUID PRIMARY KEY NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    public_key TEXT NOT NULL,
+    algorithm TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+-- create wrapped note data keys table
+CREATE TABLE note_keys (
+    note_id UUID NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    wrapped_key TEXT NOT NULL,
+    algorithm TEXT NOT NULL,
+    wrapped_by UUID NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (note_id, user_id)
+);
+CREATE INDEX note_keys_user_id_idx ON note_keys (user_id);
@@ -0,0 +1,12 @@
+-- add the keyed digest of the note text, used to find duplicates of encrypted notes
+ALTER TABLE notes ADD COLUMN text_digest TEXT;
+CREATE INDEX notes_user_id_text_digest_idx ON notes (user_id, text_digest);
+-- create per-tenant data keys table, wrapped by the master key
+CREATE TABLE tenant_keys (
+    user_id UUID PRIMARY KEY NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    wrapped_key TEXT NOT NULL,
+    master_key_id TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+CREATE INDEX tenant_keys_master_key_id_idx ON tenant_keys (master_key_id);
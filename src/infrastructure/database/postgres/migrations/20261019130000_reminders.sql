@@ -0,0 +1,21 @@
+-- create reminders table
+CREATE TABLE reminders (
+    id UUID PRIMARY KEY NOT NULL,
+    note_id UUID NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    starts_at TIMESTAMP NOT NULL,
+    due_at TIMESTAMP NOT NULL,
+    rrule TEXT,
+    channel TEXT NOT NULL,
+    webhook_url TEXT,
+    status TEXT NOT NULL,
+    occurrences INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_fired_at TIMESTAMP,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+CREATE INDEX reminders_due_at_idx ON reminders (due_at) WHERE status = 'pending';
+CREATE INDEX reminders_note_id_idx ON reminders (note_id);
+CREATE INDEX reminders_user_id_idx ON reminders (user_id);
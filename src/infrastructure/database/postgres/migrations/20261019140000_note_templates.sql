@@ -0,0 +1,12 @@
+-- create note templates table, templates without an owner are global
+CREATE TABLE note_templates (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    title TEXT,
+    text TEXT NOT NULL,
+    prompts JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+CREATE INDEX note_templates_user_id_idx ON note_templates (user_id);
@@ -0,0 +1,18 @@
+-- create note comments table, replies share the thread of their root comment
+CREATE TABLE note_comments (
+    id UUID PRIMARY KEY NOT NULL,
+    note_id UUID NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+    thread_id UUID NOT NULL,
+    parent_id UUID REFERENCES note_comments (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    text TEXT NOT NULL,
+    mentions UUID[] NOT NULL DEFAULT '{}',
+    resolved_at TIMESTAMP,
+    resolved_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    edited_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+CREATE INDEX note_comments_note_id_idx ON note_comments (note_id, created_at) WHERE parent_id IS NULL;
+CREATE INDEX note_comments_thread_id_idx ON note_comments (thread_id);
+CREATE INDEX note_comments_mentions_idx ON note_comments USING GIN (mentions);
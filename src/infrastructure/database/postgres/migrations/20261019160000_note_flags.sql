@@ -0,0 +1,11 @@
+-- create note flags table, the flags of a note are per viewer
+CREATE TABLE note_flags (
+    note_id UUID NOT NULL REFERENCES notes (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    favorite BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (note_id, user_id)
+);
+CREATE INDEX note_flags_user_id_idx ON note_flags (user_id);
@@ -0,0 +1,33 @@
+-- keep a single stats row per user, the most recently updated one
+DELETE FROM stats s
+USING stats newer
+WHERE s.user_id = newer.user_id
+AND (s.updated_at, s.id) < (newer.updated_at, newer.id);
+DELETE FROM stats WHERE user_id NOT IN (SELECT id FROM users);
+
+ALTER TABLE stats
+    ADD COLUMN notes_created INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN notes_deleted INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN notes_edited INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN total_chars BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN last_activity_at TIMESTAMP,
+    ADD CONSTRAINT stats_user_id_key UNIQUE (user_id),
+    ADD CONSTRAINT stats_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
+
+-- length in characters of the plaintext note text, the stored text may be encrypted at rest
+ALTER TABLE notes ADD COLUMN text_length INTEGER;
+UPDATE notes SET text_length = 0 WHERE encryption IS NOT NULL;
+UPDATE notes SET text_length = char_length(text)
+WHERE encryption IS NULL AND NOT starts_with(text, 'enc:v1:');
+
+-- replace the manually maintained counters with the counts of the existing notes
+INSERT INTO stats (user_id, notes, notes_created, total_chars, last_activity_at, created_at, updated_at)
+SELECT user_id, COUNT(*), COUNT(*), COALESCE(SUM(text_length), 0), MAX(updated_at), now(), now()
+FROM notes
+GROUP BY user_id
+ON CONFLICT (user_id) DO UPDATE
+SET notes = EXCLUDED.notes,
+notes_created = EXCLUDED.notes_created,
+total_chars = EXCLUDED.total_chars,
+last_activity_at = EXCLUDED.last_activity_at,
+updated_at = EXCLUDED.updated_at;
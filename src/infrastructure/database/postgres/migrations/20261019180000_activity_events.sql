@@ -0,0 +1,14 @@
+-- create activity events table, the source of the activity time series
+-- the events of a deleted user are kept for the system-wide series
+CREATE TABLE activity_events (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID REFERENCES users (id) ON DELETE SET NULL,
+    kind TEXT NOT NULL,
+    occurred_at TIMESTAMP NOT NULL
+);
+CREATE INDEX activity_events_kind_occurred_at_idx ON activity_events (kind, occurred_at);
+CREATE INDEX activity_events_user_id_occurred_at_idx ON activity_events (user_id, occurred_at);
+
+-- backfill the creation of the existing notes, the past edits and logins are not known
+INSERT INTO activity_events (id, user_id, kind, occurred_at)
+SELECT gen_random_uuid(), user_id, 'note_created', created_at FROM notes;
@@ -0,0 +1,13 @@
+-- create revocation tables of the postgres token store
+-- the tokens issued before `revoked_before` are revoked, globally with the `*` subject or per user
+CREATE TABLE jwt_revocations (
+    subject TEXT PRIMARY KEY NOT NULL,
+    revoked_before BIGINT NOT NULL
+);
+
+-- the tokens revoked by JWT ID, deleted by the cleanup once expired
+CREATE TABLE jwt_revoked_tokens (
+    jti TEXT PRIMARY KEY NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+CREATE INDEX jwt_revoked_tokens_expires_at_idx ON jwt_revoked_tokens (expires_at);
@@ -0,0 +1,16 @@
+-- create job runs table, the history of the maintenance jobs
+-- a scheduled occurrence of a job runs once across the instances, the manual runs have no occurrence
+CREATE TABLE job_runs (
+    id UUID PRIMARY KEY NOT NULL,
+    job TEXT NOT NULL,
+    trigger TEXT NOT NULL,
+    status TEXT NOT NULL,
+    scheduled_at TIMESTAMP,
+    processed BIGINT,
+    error TEXT,
+    instance TEXT NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    finished_at TIMESTAMP
+);
+CREATE UNIQUE INDEX job_runs_job_scheduled_at_idx ON job_runs (job, scheduled_at);
+CREATE INDEX job_runs_job_started_at_idx ON job_runs (job, started_at);
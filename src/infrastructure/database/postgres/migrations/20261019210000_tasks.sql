@@ -0,0 +1,16 @@
+-- create tasks table, the queue of the task workers
+-- a running task is reclaimed by another worker once `locked_until` has passed
+CREATE TABLE tasks (
+    id UUID PRIMARY KEY NOT NULL,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at TIMESTAMP NOT NULL,
+    locked_until TIMESTAMP,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+CREATE INDEX tasks_status_run_at_idx ON tasks (status, run_at);
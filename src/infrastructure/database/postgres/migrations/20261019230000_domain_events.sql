@@ -0,0 +1,14 @@
+-- the outbox carries the domain events, each event is handled by every subscriber and sink
+-- a failed dispatch is retried at `next_attempt_at`, the handled consumers are not called again
+ALTER TABLE outbox_events
+    ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN next_attempt_at TIMESTAMP,
+    ADD COLUMN last_error TEXT;
+
+-- create outbox consumers table, the subscribers and sinks which handled an event
+CREATE TABLE outbox_consumers (
+    event_id UUID NOT NULL REFERENCES outbox_events (id) ON DELETE CASCADE,
+    consumer TEXT NOT NULL,
+    handled_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (event_id, consumer)
+);
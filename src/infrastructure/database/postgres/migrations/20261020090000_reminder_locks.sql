@@ -0,0 +1,2 @@
+-- the reminders are claimed until the time, then delivered outside the claiming transaction
+ALTER TABLE reminders ADD COLUMN locked_until TIMESTAMP;
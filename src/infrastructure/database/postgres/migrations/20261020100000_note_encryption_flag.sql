@@ -0,0 +1,5 @@
+-- record whether the note is encrypted at rest, rather than telling it from the prefix of its text
+ALTER TABLE notes ADD COLUMN encrypted_at_rest BOOLEAN NOT NULL DEFAULT FALSE;
+-- only the notes encrypted at rest have a keyed digest of their text
+UPDATE notes SET encrypted_at_rest = TRUE WHERE text_digest IS NOT NULL;
+CREATE INDEX notes_plaintext_idx ON notes (id) WHERE NOT encrypted_at_rest;
@@ -0,0 +1,8 @@
+-- the titles of the notes encrypted at rest are encrypted too, and looked up by their keyed digest
+ALTER TABLE notes ADD COLUMN title_digest TEXT;
+CREATE INDEX notes_user_id_title_digest_idx ON notes (user_id, title_digest);
+-- so are the targets of their links
+ALTER TABLE note_links ADD COLUMN target_digest TEXT;
+CREATE INDEX note_links_target_digest_idx ON note_links (target_digest);
+-- duplicates are found by the keyed digest of the text, the plaintext is not hashed
+DROP INDEX notes_user_id_text_hash_idx;
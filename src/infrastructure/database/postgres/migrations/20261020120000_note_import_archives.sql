@@ -0,0 +1,14 @@
+-- create note import archives table, the notes of the imports run by the task workers are kept
+-- out of the task payloads and deleted once the import finished
+CREATE TABLE note_import_archives (
+    import_id UUID PRIMARY KEY NOT NULL REFERENCES note_imports (id) ON DELETE CASCADE,
+    notes TEXT NOT NULL,
+    encrypted_at_rest BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL
+);
+-- move the notes of the queued imports out of their tasks, and drop them from the finished tasks
+INSERT INTO note_import_archives (import_id, notes, created_at)
+SELECT (payload->>'import_id')::UUID, (payload->'notes')::TEXT, now() FROM tasks
+WHERE kind = 'import_notes' AND status IN ('queued', 'running') AND payload ? 'notes'
+AND EXISTS (SELECT 1 FROM note_imports WHERE id = (payload->>'import_id')::UUID);
+UPDATE tasks SET payload = payload - 'notes' WHERE kind = 'import_notes';
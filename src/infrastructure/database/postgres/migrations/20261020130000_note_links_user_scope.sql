@@ -0,0 +1,5 @@
+-- unresolve the links to the notes of other users, links are resolved within the notes of the same user
+UPDATE note_links SET target_id = NULL
+FROM notes source, notes target
+WHERE note_links.source_id = source.id AND note_links.target_id = target.id
+AND source.user_id <> target.user_id;
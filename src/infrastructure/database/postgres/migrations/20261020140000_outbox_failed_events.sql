@@ -0,0 +1,5 @@
+-- an event failing its last attempt is failed and no longer dispatched
+ALTER TABLE outbox_events ADD COLUMN failed_at TIMESTAMP;
+DROP INDEX outbox_events_pending_idx;
+CREATE INDEX outbox_events_pending_idx ON outbox_events (created_at)
+WHERE dispatched_at IS NULL AND failed_at IS NULL;
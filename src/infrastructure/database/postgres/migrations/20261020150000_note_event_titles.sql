@@ -0,0 +1,7 @@
+-- the title of a note encrypted at rest is left out of its events and webhook deliveries
+UPDATE outbox_events SET data = jsonb_set(data, '{title}', 'null')
+WHERE event IN ('note.created', 'note.updated')
+AND (data->>'id')::UUID IN (SELECT id FROM notes WHERE encrypted_at_rest);
+UPDATE webhook_deliveries SET payload = jsonb_set(payload, '{data,title}', 'null')
+WHERE event IN ('note.created', 'note.updated')
+AND (payload->'data'->>'id')::UUID IN (SELECT id FROM notes WHERE encrypted_at_rest);
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fs};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use futures::future::{self, BoxFuture};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::infrastructure::kms::{KeyManagementService, KmsError, KmsResult, WrappedKey};
+
+const KEY_FILE_SEPARATOR: char = ':';
+const KEY_FILE_COMMENT: char = '#';
+
+/// Key management service with the master keys held in process memory, loaded from a local key file.
+pub struct LocalKms {
+    current_key_id: String,
+    keys: HashMap<String, LessSafeKey>,
+    random: SystemRandom,
+}
+
+impl LocalKms {
+    pub fn new(keys: HashMap<String, Vec<u8>>, current_key_id: &str) -> KmsResult<Self> {
+        if !keys.contains_key(current_key_id) {
+            return Err(KmsError::UnknownKey(current_key_id.to_owned()));
+        }
+        let keys = keys
+            .into_iter()
+            .map(|(key_id, key)| {
+                UnboundKey::new(&AES_256_GCM, &key)
+                    .map(|key| (key_id.clone(), LessSafeKey::new(key)))
+                    .map_err(|_| {
+                        KmsError::InvalidKeyFile(format!("master key must be 32 bytes: {}", key_id))
+                    })
+            })
+            .collect::<KmsResult<_>>()?;
+
+        Ok(Self {
+            current_key_id: current_key_id.to_owned(),
+            keys,
+            random: SystemRandom::new(),
+        })
+    }
+
+    // Loads the master keys from a file with one `key_id:base64_key` line per key.
+    // Older keys are kept in the file after a rotation to unwrap the data keys not yet re-wrapped.
+    // The last key of the file is the current key, unless the current key is given.
+    pub fn from_file(path: &str, current_key_id: Option<&str>) -> KmsResult<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| KmsError::InvalidKeyFile(format!("{}: {}", path, e)))?;
+
+        let mut keys = HashMap::new();
+        let mut last_key_id = None;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(KEY_FILE_COMMENT) {
+                continue;
+            }
+            let (key_id, key) = line
+                .split_once(KEY_FILE_SEPARATOR)
+                .ok_or_else(|| KmsError::InvalidKeyFile(format!("invalid line in {}", path)))?;
+            let key = STANDARD
+                .decode(key.trim())
+                .map_err(|e| KmsError::InvalidKeyFile(format!("{}: {}", key_id, e)))?;
+            keys.insert(key_id.trim().to_owned(), key);
+            last_key_id = Some(key_id.trim().to_owned());
+        }
+
+        let current_key_id = current_key_id
+            .map(str::to_owned)
+            .or(last_key_id)
+            .ok_or_else(|| KmsError::InvalidKeyFile(format!("no master key in {}", path)))?;
+        Self::new(keys, &current_key_id)
+    }
+
+    fn key(&self, key_id: &str) -> KmsResult<&LessSafeKey> {
+        self.keys
+            .get(key_id)
+            .ok_or_else(|| KmsError::UnknownKey(key_id.to_owned()))
+    }
+}
+
+impl KeyManagementService for LocalKms {
+    fn current_key_id(&self) -> &str {
+        &self.current_key_id
+    }
+
+    // The wrapped key is the nonce followed by the sealed data key,
+    // the master key ID is authenticated as associated data.
+    fn wrap<'a>(&'a self, data_key: &'a [u8]) -> BoxFuture<'a, KmsResult<WrappedKey>> {
+        let result = self.key(&self.current_key_id).and_then(|key| {
+            let mut nonce = [0u8; NONCE_LEN];
+            self.random
+                .fill(&mut nonce)
+                .map_err(|_| KmsError::WrapFailed)?;
+            let mut ciphertext = data_key.to_vec();
+            key.seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(self.current_key_id.as_bytes()),
+                &mut ciphertext,
+            )
+            .map_err(|_| KmsError::WrapFailed)?;
+
+            Ok(WrappedKey {
+                master_key_id: self.current_key_id.clone(),
+                ciphertext: [nonce.as_slice(), &ciphertext].concat(),
+            })
+        });
+        Box::pin(future::ready(result))
+    }
+
+    fn unwrap<'a>(&'a self, wrapped_key: &'a WrappedKey) -> BoxFuture<'a, KmsResult<Vec<u8>>> {
+        let result = self.key(&wrapped_key.master_key_id).and_then(|key| {
+            if wrapped_key.ciphertext.len() < NONCE_LEN {
+                return Err(KmsError::UnwrapFailed);
+            }
+            let (nonce, ciphertext) = wrapped_key.ciphertext.split_at(NONCE_LEN);
+            let nonce =
+                Nonce::try_assume_unique_for_key(nonce).map_err(|_| KmsError::UnwrapFailed)?;
+            let mut ciphertext = ciphertext.to_vec();
+            let data_key = key
+                .open_in_place(
+                    nonce,
+                    Aad::from(wrapped_key.master_key_id.as_bytes()),
+                    &mut ciphertext,
+                )
+                .map_err(|_| KmsError::UnwrapFailed)?;
+            Ok(data_key.to_vec())
+        });
+        Box::pin(future::ready(result))
+    }
+}
@@ -0,0 +1,39 @@
+use futures::future::BoxFuture;
+use thiserror::Error;
+
+mod local;
+pub use local::LocalKms;
+
+pub type KmsResult<T> = Result<T, KmsError>;
+
+/// Data key wrapped by a master key of the key management service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    pub master_key_id: String,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Key management service holding the master keys, the master keys never leave the service.
+/// Remote services are called over the network, hence the asynchronous interface.
+pub trait KeyManagementService: Send + Sync {
+    /// The master key used to wrap new data keys.
+    fn current_key_id(&self) -> &str;
+
+    /// Wraps the data key with the current master key.
+    fn wrap<'a>(&'a self, data_key: &'a [u8]) -> BoxFuture<'a, KmsResult<WrappedKey>>;
+
+    /// Unwraps the data key with the master key it was wrapped with.
+    fn unwrap<'a>(&'a self, wrapped_key: &'a WrappedKey) -> BoxFuture<'a, KmsResult<Vec<u8>>>;
+}
+
+#[derive(Debug, Error)]
+pub enum KmsError {
+    #[error("invalid master key file: {0}")]
+    InvalidKeyFile(String),
+    #[error("unknown master key: {0}")]
+    UnknownKey(String),
+    #[error("could not wrap the data key")]
+    WrapFailed,
+    #[error("could not unwrap the data key")]
+    UnwrapFailed,
+}
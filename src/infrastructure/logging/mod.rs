@@ -0,0 +1,59 @@
+mod redact;
+pub use redact::{RedactingMakeWriter, RedactingWriter, redact};
+
+use std::sync::OnceLock;
+
+use tracing::Subscriber;
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, fmt::MakeWriter, registry::LookupSpan, reload,
+};
+
+use crate::application::config::Config;
+
+const LOG_FORMAT_JSON: &str = "json";
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+// Filter of the log levels, replaced by `set_level` on the configuration reloads.
+pub fn filter(config: &Config) -> reload::Layer<EnvFilter, Registry> {
+    let filter = EnvFilter::try_new(&config.log_level).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (layer, handle) = reload::Layer::new(filter);
+    let _ = FILTER.set(handle);
+    layer
+}
+
+// Replaces the log filter, without effect when the filter was not installed.
+pub fn set_level(level: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+    FILTER.get().map_or(Ok(()), |handle| {
+        handle.reload(filter).map_err(|e| e.to_string())
+    })
+}
+
+// Layer formatting the log lines as text, or as JSON objects when configured.
+// The lines carry the fields of the request span and are redacted before being written.
+pub fn layer<S, W>(config: &Config, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let writer = RedactingMakeWriter::new(writer);
+    if config.log_format.eq_ignore_ascii_case(LOG_FORMAT_JSON) {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(false)
+            .with_span_list(true)
+            .with_file(true)
+            .with_line_number(true)
+            .with_writer(writer)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer()
+            .compact()
+            .with_target(false)
+            .with_file(true)
+            .with_line_number(true)
+            .with_writer(writer)
+            .boxed()
+    }
+}
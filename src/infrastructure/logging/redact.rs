@@ -0,0 +1,89 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+    sync::LazyLock,
+};
+
+use regex::{Captures, Regex};
+use tracing_subscriber::fmt::MakeWriter;
+
+const REDACTED: &str = "[REDACTED]";
+
+// Credential fields, in the Debug (`key: "value"`), key=value and JSON (`"key":"value"`) forms.
+// The key ends with the credential word or goes on after a separator (`password_hash`), so the
+// counts like `tokens: 5` are kept. The value is either quoted, quoted inside a JSON string, or a
+// bare word.
+static CREDENTIAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)([\w.-]*(?:password|passwd|secret|token|api_?key|authorization)(?:[_.-][\w.-]*)?)(\\?"?\s*[:=]\s*)(\\"(?:[^"\\]|\\[^"])*\\"|"(?:[^"\\]|\\.)*"|[^\s,;})\]"\\]+)"#,
+    )
+    .unwrap()
+});
+
+// Credentials of the `Authorization` header schemes.
+static SCHEME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(bearer|basic)\s+[\w.~+/=-]+").unwrap());
+
+// JSON Web Tokens, wherever they appear.
+static JWT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"eyJ[\w-]*\.[\w-]*\.[\w-]*").unwrap());
+
+// Masks the tokens, the authorization headers and the credential fields of a log line.
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let mut line = Cow::Borrowed(line);
+    if CREDENTIAL.is_match(&line) {
+        let redacted = CREDENTIAL.replace_all(&line, |caps: &Captures| {
+            let value = &caps[3];
+            // Keep the value a string, the JSON lines stay valid.
+            let masked = if value.starts_with("\\\"") {
+                format!("\\\"{}\\\"", REDACTED)
+            } else if value.starts_with('"') || caps[2].contains('"') {
+                format!("\"{}\"", REDACTED)
+            } else {
+                REDACTED.to_owned()
+            };
+            format!("{}{}{}", &caps[1], &caps[2], masked)
+        });
+        line = Cow::Owned(redacted.into_owned());
+    }
+    if SCHEME.is_match(&line) {
+        line = Cow::Owned(SCHEME.replace_all(&line, "$1 [REDACTED]").into_owned());
+    }
+    if JWT.is_match(&line) {
+        line = Cow::Owned(JWT.replace_all(&line, REDACTED).into_owned());
+    }
+    line
+}
+
+// Writer factory redacting the formatted log lines before writing them.
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub const fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.inner.make_writer())
+    }
+}
+
+// The formatter writes a whole log line at once, so each write is redacted as a line.
+pub struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&line).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
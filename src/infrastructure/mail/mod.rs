@@ -0,0 +1,35 @@
+use futures::future::{self, BoxFuture};
+use thiserror::Error;
+
+pub type MailResult<T> = Result<T, MailError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sends emails, implemented over the mail service of the deployment.
+pub trait Mailer: Send + Sync {
+    fn send<'a>(&'a self, mail: &'a Mail) -> BoxFuture<'a, MailResult<()>>;
+}
+
+/// Mailer writing the emails to the log, used when no mail service is configured.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send<'a>(&'a self, mail: &'a Mail) -> BoxFuture<'a, MailResult<()>> {
+        tracing::info!("mail to: {}, subject: {}", mail.to, mail.subject);
+        tracing::debug!("mail body: {}", mail.body);
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("invalid recipient: {0}")]
+    InvalidRecipient(String),
+    #[error("could not send the mail: {0}")]
+    SendFailed(String),
+}
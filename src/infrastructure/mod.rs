@@ -1,2 +1,8 @@
 pub mod database;
+pub mod kms;
+pub mod logging;
+pub mod mail;
+pub mod prometheus;
 pub mod redis;
+pub mod telemetry;
+pub mod token_store;
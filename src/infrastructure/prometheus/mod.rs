@@ -0,0 +1,2 @@
+mod recorder;
+pub use recorder::{install, render};
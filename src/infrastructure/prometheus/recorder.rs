@@ -0,0 +1,68 @@
+use std::sync::OnceLock;
+
+use metrics::{Unit, describe_counter, describe_gauge, describe_histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use crate::application::constants::*;
+
+// Buckets of the latency histograms, in seconds.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+// Installs the Prometheus recorder as the global metrics recorder, once per process.
+pub fn install() -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        let handle = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Suffix("duration_seconds".to_owned()),
+                &LATENCY_BUCKETS,
+            )
+            .expect("Failed to set the metrics buckets.")
+            .install_recorder()
+            .expect("Failed to install the metrics recorder.");
+        describe();
+        handle
+    })
+}
+
+// Renders the metrics in the Prometheus text format.
+pub fn render() -> String {
+    install().render()
+}
+
+fn describe() {
+    describe_counter!(
+        METRICS_HTTP_REQUESTS_TOTAL,
+        "HTTP requests per route and status."
+    );
+    describe_histogram!(
+        METRICS_HTTP_REQUEST_DURATION,
+        Unit::Seconds,
+        "HTTP request latency per route and status."
+    );
+    describe_counter!(METRICS_AUTH_LOGINS_TOTAL, "Logins per result.");
+    describe_counter!(
+        METRICS_AUTH_TOKENS_ISSUED_TOTAL,
+        "JWT tokens issued per type."
+    );
+    describe_counter!(
+        METRICS_AUTH_TOKENS_REVOKED_TOTAL,
+        "JWT token revocations per scope."
+    );
+    describe_gauge!(
+        METRICS_DB_POOL_CONNECTIONS,
+        "Database pool connections per state."
+    );
+    describe_gauge!(
+        METRICS_DB_POOL_MAX_CONNECTIONS,
+        "Maximum connections of the database pool."
+    );
+    describe_histogram!(
+        METRICS_REDIS_COMMAND_DURATION,
+        Unit::Seconds,
+        "Redis command latency per command."
+    );
+}
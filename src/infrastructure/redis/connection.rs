@@ -1,22 +1,361 @@
-use redis::aio::MultiplexedConnection;
+use std::{
+    io,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use crate::application::config::Config;
+use metrics::histogram;
+use redis::{
+    Arg, Client, Cmd, ConnectionAddr, ConnectionInfo, ErrorKind, Pipeline, RedisConnectionInfo,
+    RedisError, RedisFuture, RedisResult, TlsMode, Value,
+    aio::{ConnectionLike, ConnectionManager, ConnectionManagerConfig},
+    cluster::{ClusterClient, ClusterClientBuilder},
+    cluster_async::ClusterConnection,
+    sentinel::{Sentinel, SentinelNodeConnectionInfo},
+};
+use tracing::Instrument;
 
-pub async fn open(config: &Config) -> MultiplexedConnection {
-    match redis::Client::open(config.redis_url()) {
-        Ok(redis) => match redis.get_multiplexed_async_connection().await {
-            Ok(connection) => {
-                tracing::info!("Connected to redis");
-                connection
+use crate::application::{config::Config, constants::*};
+
+// How the connections are made, depending on the Redis deployment.
+enum Topology {
+    Standalone(Client),
+    // The master is discovered through the sentinels.
+    Sentinel {
+        sentinels: Vec<ConnectionInfo>,
+        master: String,
+        node: SentinelNodeConnectionInfo,
+    },
+    Cluster(ClusterClient),
+}
+
+// The connection of the deployment, reconnecting on its own.
+#[derive(Clone)]
+enum Manager {
+    Single(ConnectionManager),
+    Cluster(ClusterConnection),
+}
+
+/// Redis connection shared without holding a lock across the commands, reconnected in the background.
+///
+/// The commands fail fast while there is no connection, Redis being down does not hold up the requests.
+/// Records the latency of the commands.
+#[derive(Clone)]
+pub struct RedisConnection {
+    topology: Arc<Topology>,
+    // Dropped after a failover in sentinel mode, for the reconnection to discover the new master.
+    manager: Arc<RwLock<Option<Arc<Manager>>>>,
+    connecting: Arc<AtomicBool>,
+    db: i64,
+}
+
+impl RedisConnection {
+    fn new(topology: Topology, db: i64) -> Self {
+        Self {
+            topology: Arc::new(topology),
+            manager: Arc::new(RwLock::new(None)),
+            connecting: Arc::new(AtomicBool::new(false)),
+            db,
+        }
+    }
+
+    // Without a connection the command fails at once, the connection is made by a background task.
+    fn manager(&self) -> RedisResult<Arc<Manager>> {
+        let manager = self
+            .manager
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        manager.ok_or_else(|| {
+            self.reconnect();
+            RedisError::from(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "redis is not connected",
+            ))
+        })
+    }
+
+    // Connects in a background task retried with backoff, until connected or the connection dropped.
+    fn reconnect(&self) {
+        if self.connecting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let topology = Arc::clone(&self.topology);
+        let slot = Arc::downgrade(&self.manager);
+        let connecting = Arc::clone(&self.connecting);
+        tokio::spawn(async move {
+            let mut delay = Duration::from_millis(REDIS_RECONNECT_INITIAL_DELAY_MILLIS);
+            loop {
+                let result = topology.connect().await;
+                let Some(slot) = slot.upgrade() else {
+                    break;
+                };
+                match result {
+                    Ok(manager) => {
+                        tracing::info!("Connected to redis.");
+                        *slot.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(manager));
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Could not connect to redis, retrying in {:?}: {}", delay, e)
+                    }
+                }
+                drop(slot);
+                tokio::time::sleep(delay).await;
+                delay = (delay * REDIS_RECONNECT_FACTOR as u32)
+                    .min(Duration::from_millis(REDIS_RECONNECT_INTERVAL_MAX_MILLIS));
+            }
+            connecting.store(false, Ordering::Release);
+        });
+    }
+
+    // After a failover the former master is unreachable or read-only, the master is discovered again.
+    fn on_error(&self, manager: &Arc<Manager>, error: &RedisError) {
+        let failover = error.kind() == ErrorKind::ReadOnly
+            || error.is_io_error()
+            || error.is_connection_refusal()
+            || error.is_connection_dropped();
+        if !failover || !matches!(self.topology.as_ref(), Topology::Sentinel { .. }) {
+            return;
+        }
+        let mut current = self.manager.write().unwrap_or_else(|e| e.into_inner());
+        if current
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, manager))
+        {
+            tracing::warn!("redis master failed, rediscovering it: {}", error);
+            *current = None;
+            drop(current);
+            self.reconnect();
+        }
+    }
+}
+
+impl Topology {
+    async fn connect(&self) -> RedisResult<Manager> {
+        match self {
+            Self::Standalone(client) => {
+                ConnectionManager::new_with_config(client.clone(), manager_config())
+                    .await
+                    .map(Manager::Single)
+            }
+            Self::Sentinel {
+                sentinels,
+                master,
+                node,
+            } => {
+                let mut sentinel = Sentinel::build(sentinels.clone())?;
+                let client = tokio::time::timeout(
+                    Duration::from_millis(REDIS_CONNECTION_TIMEOUT_MILLIS),
+                    sentinel.async_master_for(master, Some(node)),
+                )
+                .await
+                .map_err(|_| {
+                    RedisError::from(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "sentinel master discovery timed out",
+                    ))
+                })??;
+                ConnectionManager::new_with_config(client, manager_config())
+                    .await
+                    .map(Manager::Single)
+            }
+            Self::Cluster(client) => client.get_async_connection().await.map(Manager::Cluster),
+        }
+    }
+}
+
+fn manager_config() -> ConnectionManagerConfig {
+    ConnectionManagerConfig::new()
+        .set_connection_timeout(Duration::from_millis(REDIS_CONNECTION_TIMEOUT_MILLIS))
+        .set_response_timeout(Duration::from_millis(REDIS_RESPONSE_TIMEOUT_MILLIS))
+        .set_number_of_retries(REDIS_RECONNECT_RETRIES)
+        .set_factor(REDIS_RECONNECT_FACTOR)
+        .set_max_delay(REDIS_RECONNECT_MAX_DELAY_MILLIS)
+}
+
+impl ConnectionLike for Manager {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Single(manager) => manager.req_packed_command(cmd),
+            Self::Cluster(connection) => connection.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Single(manager) => manager.req_packed_commands(cmd, offset, count),
+            Self::Cluster(connection) => connection.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Single(manager) => manager.get_db(),
+            Self::Cluster(connection) => connection.get_db(),
+        }
+    }
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let command = command_name(cmd);
+        let span = tracing::info_span!("redis", db.system = "redis", db.operation = %command);
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let result = match self.manager() {
+                    Ok(manager) => {
+                        let result = Manager::clone(&manager).req_packed_command(cmd).await;
+                        if let Err(e) = &result {
+                            self.on_error(&manager, e);
+                        }
+                        result
+                    }
+                    Err(e) => Err(e),
+                };
+                histogram!(METRICS_REDIS_COMMAND_DURATION, "command" => command)
+                    .record(started.elapsed());
+                result
             }
-            Err(e) => {
-                tracing::error!("Could not connect to redis: {}", e);
-                std::process::exit(1);
+            .instrument(span),
+        )
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        let span = tracing::info_span!("redis", db.system = "redis", db.operation = "pipeline");
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let result = match self.manager() {
+                    Ok(manager) => {
+                        let result = Manager::clone(&manager)
+                            .req_packed_commands(cmd, offset, count)
+                            .await;
+                        if let Err(e) = &result {
+                            self.on_error(&manager, e);
+                        }
+                        result
+                    }
+                    Err(e) => Err(e),
+                };
+                histogram!(METRICS_REDIS_COMMAND_DURATION, "command" => "pipeline")
+                    .record(started.elapsed());
+                result
             }
-        },
+            .instrument(span),
+        )
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+}
+
+// The command name is the first argument, e.g. `hget`.
+fn command_name(cmd: &Cmd) -> String {
+    match cmd.args_iter().next() {
+        Some(Arg::Simple(name)) => String::from_utf8_lossy(name).to_ascii_lowercase(),
+        _ => "unknown".to_owned(),
+    }
+}
+
+fn address(host: &str, port: u16, tls: bool) -> ConnectionAddr {
+    if tls {
+        ConnectionAddr::TcpTls {
+            host: host.to_owned(),
+            port,
+            insecure: false,
+            tls_params: None,
+        }
+    } else {
+        ConnectionAddr::Tcp(host.to_owned(), port)
+    }
+}
+
+// The database and the credentials of the Redis servers.
+fn redis_info(config: &Config) -> RedisConnectionInfo {
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_owned());
+    RedisConnectionInfo {
+        db: config.redis_db,
+        username: non_empty(&config.redis_username),
+        password: non_empty(&config.redis_password),
+        ..Default::default()
+    }
+}
+
+fn topology(config: &Config) -> RedisResult<Topology> {
+    let nodes = config.redis_node_addrs();
+    if config.redis_mode.eq_ignore_ascii_case(REDIS_MODE_SENTINEL) {
+        // The sentinels use the TLS mode of the servers, without credentials.
+        let sentinels = nodes
+            .iter()
+            .map(|(host, port)| ConnectionInfo {
+                addr: address(host, *port, config.redis_tls),
+                redis: RedisConnectionInfo::default(),
+            })
+            .collect();
+        Ok(Topology::Sentinel {
+            sentinels,
+            master: config.redis_sentinel_master.clone(),
+            node: SentinelNodeConnectionInfo {
+                tls_mode: config.redis_tls.then_some(TlsMode::Secure),
+                redis_connection_info: Some(redis_info(config)),
+            },
+        })
+    } else if config.redis_mode.eq_ignore_ascii_case(REDIS_MODE_CLUSTER) {
+        let seeds = nodes.iter().map(|(host, port)| ConnectionInfo {
+            addr: address(host, *port, config.redis_tls),
+            redis: redis_info(config),
+        });
+        ClusterClientBuilder::new(seeds)
+            .connection_timeout(Duration::from_millis(REDIS_CONNECTION_TIMEOUT_MILLIS))
+            .response_timeout(Duration::from_millis(REDIS_RESPONSE_TIMEOUT_MILLIS))
+            .retries(REDIS_RECONNECT_RETRIES as u32)
+            .max_retry_wait(REDIS_RECONNECT_MAX_DELAY_MILLIS)
+            .build()
+            .map(Topology::Cluster)
+    } else {
+        Client::open(ConnectionInfo {
+            addr: address(&config.redis_host, config.redis_port, config.redis_tls),
+            redis: redis_info(config),
+        })
+        .map(Topology::Standalone)
+    }
+}
+
+// Opens the connection, the service starts even when Redis is not reachable yet.
+pub async fn open(config: &Config) -> RedisConnection {
+    let topology = topology(config).expect("Invalid redis configuration.");
+    let connection = RedisConnection::new(topology, config.redis_db);
+    match connection.topology.connect().await {
+        Ok(manager) => {
+            tracing::info!("Connected to redis, mode: {}", config.redis_mode);
+            *connection
+                .manager
+                .write()
+                .unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(manager));
+        }
         Err(e) => {
-            tracing::error!("Could not open redis: {}", e);
-            std::process::exit(1);
+            tracing::warn!(
+                "Could not connect to redis, retrying in the background: {}",
+                e
+            );
+            connection.reconnect();
         }
     }
+    connection
 }
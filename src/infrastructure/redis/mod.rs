@@ -1,2 +1,2 @@
 mod connection;
-pub use connection::open;
+pub use connection::{RedisConnection, open};
@@ -0,0 +1,80 @@
+use opentelemetry::{
+    Context, global,
+    trace::{TraceContextExt, TraceId, TracerProvider as _},
+};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use reqwest::header::HeaderMap;
+use tracing::Subscriber;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::application::config::Config;
+
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+// Builds the tracer provider, the spans are exported over OTLP/HTTP when an endpoint is configured.
+// Without an endpoint the spans are not exported, but the requests still get a trace context.
+pub fn tracer_provider(config: &Config) -> SdkTracerProvider {
+    // The W3C `traceparent` and `tracestate` headers.
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let resource = Resource::builder()
+        .with_service_name(config.otel_service_name.clone())
+        .build();
+    let mut builder = SdkTracerProvider::builder().with_resource(resource);
+    if !config.otel_exporter_otlp_endpoint.is_empty() {
+        let endpoint = format!(
+            "{}{}",
+            config.otel_exporter_otlp_endpoint.trim_end_matches('/'),
+            OTLP_TRACES_PATH
+        );
+        match SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+        {
+            Ok(exporter) => builder = builder.with_batch_exporter(exporter),
+            Err(e) => eprintln!("Could not build the OTLP span exporter: {}", e),
+        }
+    }
+
+    let provider = builder.build();
+    global::set_tracer_provider(provider.clone());
+    provider
+}
+
+// Layer exporting the tracing spans to OpenTelemetry.
+pub fn layer<S>(
+    provider: &SdkTracerProvider,
+) -> OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+}
+
+// Sets the parent of the span from the trace context of the incoming headers.
+pub fn extract_parent(span: &tracing::Span, headers: &HeaderMap) {
+    let parent =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    if parent.span().span_context().is_valid() {
+        let _ = span.set_parent(parent);
+    }
+}
+
+// Writes the trace context of the span into the headers.
+pub fn inject(span: &tracing::Span, headers: &mut HeaderMap) {
+    let context = span.context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers));
+    });
+}
+
+// Trace id of the current span, none outside of a traced request.
+pub fn current_trace_id() -> Option<String> {
+    let context: Context = tracing::Span::current().context();
+    let trace_id = context.span().span_context().trace_id();
+    (trace_id != TraceId::INVALID).then(|| trace_id.to_string())
+}
@@ -0,0 +1,87 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use futures::future::{self, BoxFuture};
+
+use super::{Revocation, TokenStore, TokenStoreResult};
+
+#[derive(Default)]
+struct Revocations {
+    global_before: Option<usize>,
+    users_before: HashMap<String, usize>,
+    tokens: HashMap<String, usize>,
+}
+
+/// Stores the revocations in the memory of the process, for the tests and the single-node deployments.
+/// The revocations are lost on restart.
+#[derive(Default)]
+pub struct MemoryTokenStore {
+    revocations: Mutex<Revocations>,
+}
+
+impl MemoryTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Revocations) -> T) -> BoxFuture<'_, TokenStoreResult<T>>
+    where
+        T: Send + 'static,
+    {
+        let mut revocations = self.revocations.lock().unwrap_or_else(|e| e.into_inner());
+        Box::pin(future::ready(Ok(f(&mut revocations))))
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn revoke_global(&self, before: usize) -> BoxFuture<'_, TokenStoreResult<()>> {
+        self.with(|revocations| revocations.global_before = Some(before))
+    }
+
+    fn revoke_user<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        self.with(|revocations| {
+            revocations.users_before.insert(user_id.to_owned(), before);
+        })
+    }
+
+    fn revoke_tokens<'a>(
+        &'a self,
+        jtis: &'a [&'a str],
+        expires_at: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        self.with(|revocations| {
+            for jti in jtis {
+                revocations.tokens.insert((*jti).to_owned(), expires_at);
+            }
+        })
+    }
+
+    fn revocation<'a>(
+        &'a self,
+        user_id: &'a str,
+        jti: &'a str,
+    ) -> BoxFuture<'a, TokenStoreResult<Revocation>> {
+        self.with(|revocations| Revocation {
+            global_before: revocations.global_before,
+            user_before: revocations.users_before.get(user_id).copied(),
+            token_revoked: revocations.tokens.contains_key(jti),
+        })
+    }
+
+    fn cleanup_expired(&self, now: usize) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        self.with(|revocations| {
+            let count = revocations.tokens.len();
+            revocations
+                .tokens
+                .retain(|_, expires_at| *expires_at >= now);
+            count - revocations.tokens.len()
+        })
+    }
+
+    fn revoked_tokens_count(&self) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        self.with(|revocations| revocations.tokens.len())
+    }
+}
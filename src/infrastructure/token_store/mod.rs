@@ -0,0 +1,94 @@
+use futures::future::{self, BoxFuture};
+use thiserror::Error;
+
+mod memory_store;
+mod postgres_store;
+mod redis_store;
+pub use memory_store::MemoryTokenStore;
+pub use postgres_store::PostgresTokenStore;
+pub use redis_store::RedisTokenStore;
+
+use crate::{
+    application::{
+        config::Config,
+        constants::{TOKEN_STORE_MEMORY, TOKEN_STORE_POSTGRES},
+    },
+    infrastructure::{database::DatabasePool, redis::RedisConnection},
+};
+
+pub type TokenStoreResult<T> = Result<T, TokenStoreError>;
+
+/// The revocations applying to a token, the timestamps are in seconds since the epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Revocation {
+    /// The tokens issued until this time are revoked.
+    pub global_before: Option<usize>,
+    /// The tokens of the user issued until this time are revoked.
+    pub user_before: Option<usize>,
+    /// The token is revoked by its JWT ID.
+    pub token_revoked: bool,
+}
+
+/// Stores the revoked tokens, implemented over Redis, PostgreSQL or the memory of the process.
+pub trait TokenStore: Send + Sync {
+    /// Revokes the tokens issued until the timestamp.
+    fn revoke_global(&self, before: usize) -> BoxFuture<'_, TokenStoreResult<()>>;
+
+    /// Revokes the tokens of the user issued until the timestamp.
+    fn revoke_user<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>>;
+
+    /// Revokes the tokens by JWT ID, until their expiration time.
+    fn revoke_tokens<'a>(
+        &'a self,
+        jtis: &'a [&'a str],
+        expires_at: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>>;
+
+    /// The revocations of the tokens of the user and of the token, read at once.
+    fn revocation<'a>(
+        &'a self,
+        user_id: &'a str,
+        jti: &'a str,
+    ) -> BoxFuture<'a, TokenStoreResult<Revocation>>;
+
+    /// Deletes the revoked tokens expired at the timestamp, returns their count.
+    fn cleanup_expired(&self, now: usize) -> BoxFuture<'_, TokenStoreResult<usize>>;
+
+    /// The count of the revoked tokens.
+    fn revoked_tokens_count(&self) -> BoxFuture<'_, TokenStoreResult<usize>>;
+
+    /// Migrates the revocations stored by the previous versions, returns the count of migrated entries.
+    fn migrate(&self) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(future::ready(Ok(0)))
+    }
+}
+
+// The token store of the configuration, Redis by default.
+pub fn open(
+    config: &Config,
+    redis: &RedisConnection,
+    db_pool: &DatabasePool,
+) -> Box<dyn TokenStore> {
+    if config
+        .token_store
+        .eq_ignore_ascii_case(TOKEN_STORE_POSTGRES)
+    {
+        Box::new(PostgresTokenStore::new(db_pool.clone()))
+    } else if config.token_store.eq_ignore_ascii_case(TOKEN_STORE_MEMORY) {
+        Box::new(MemoryTokenStore::new())
+    } else {
+        Box::new(RedisTokenStore::new(redis.clone()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TokenStoreError {
+    #[error(transparent)]
+    Redis(#[from] redis::RedisError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
@@ -0,0 +1,125 @@
+use futures::future::BoxFuture;
+
+use super::{Revocation, TokenStore, TokenStoreResult};
+use crate::infrastructure::database::DatabasePool;
+
+// The subject of the global revocation, the user revocations use the user id.
+const GLOBAL_SUBJECT: &str = "*";
+
+/// Stores the revocations in the `jwt_revocations` and `jwt_revoked_tokens` tables, for the deployments
+/// without Redis.
+pub struct PostgresTokenStore {
+    db_pool: DatabasePool,
+}
+
+impl PostgresTokenStore {
+    pub const fn new(db_pool: DatabasePool) -> Self {
+        Self { db_pool }
+    }
+
+    #[tracing::instrument(name = "token_store.revoke_before", skip_all, fields(db.system = "postgresql"))]
+    async fn revoke_before(&self, subject: &str, before: usize) -> TokenStoreResult<()> {
+        sqlx::query(
+            r#"INSERT INTO jwt_revocations (subject, revoked_before)
+             VALUES ($1,$2)
+             ON CONFLICT (subject) DO UPDATE
+             SET revoked_before = EXCLUDED.revoked_before"#,
+        )
+        .bind(subject)
+        .bind(before as i64)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "token_store.revoke_tokens", skip_all, fields(db.system = "postgresql"))]
+    async fn insert_tokens(&self, jtis: &[&str], expires_at: usize) -> TokenStoreResult<()> {
+        sqlx::query(
+            r#"INSERT INTO jwt_revoked_tokens (jti, expires_at)
+             SELECT jti, $2 FROM UNNEST($1::TEXT[]) AS jti
+             ON CONFLICT (jti) DO UPDATE
+             SET expires_at = EXCLUDED.expires_at"#,
+        )
+        .bind(jtis)
+        .bind(expires_at as i64)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "token_store.revocation", skip_all, fields(db.system = "postgresql"))]
+    async fn select_revocation(&self, user_id: &str, jti: &str) -> TokenStoreResult<Revocation> {
+        let (global_before, user_before, token_revoked): (Option<i64>, Option<i64>, bool) =
+            sqlx::query_as(
+                r#"SELECT
+                 (SELECT revoked_before FROM jwt_revocations WHERE subject = $1),
+                 (SELECT revoked_before FROM jwt_revocations WHERE subject = $2),
+                 EXISTS (SELECT 1 FROM jwt_revoked_tokens WHERE jti = $3)"#,
+            )
+            .bind(GLOBAL_SUBJECT)
+            .bind(user_id)
+            .bind(jti)
+            .fetch_one(&self.db_pool)
+            .await?;
+        Ok(Revocation {
+            global_before: global_before.map(|before| before as usize),
+            user_before: user_before.map(|before| before as usize),
+            token_revoked,
+        })
+    }
+
+    #[tracing::instrument(name = "token_store.cleanup_expired", skip_all, fields(db.system = "postgresql"))]
+    async fn delete_expired(&self, now: usize) -> TokenStoreResult<usize> {
+        let result = sqlx::query("DELETE FROM jwt_revoked_tokens WHERE expires_at < $1")
+            .bind(now as i64)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    #[tracing::instrument(name = "token_store.revoked_tokens_count", skip_all, fields(db.system = "postgresql"))]
+    async fn count_tokens(&self) -> TokenStoreResult<usize> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jwt_revoked_tokens")
+            .fetch_one(&self.db_pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+impl TokenStore for PostgresTokenStore {
+    fn revoke_global(&self, before: usize) -> BoxFuture<'_, TokenStoreResult<()>> {
+        Box::pin(self.revoke_before(GLOBAL_SUBJECT, before))
+    }
+
+    fn revoke_user<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        Box::pin(self.revoke_before(user_id, before))
+    }
+
+    fn revoke_tokens<'a>(
+        &'a self,
+        jtis: &'a [&'a str],
+        expires_at: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        Box::pin(self.insert_tokens(jtis, expires_at))
+    }
+
+    fn revocation<'a>(
+        &'a self,
+        user_id: &'a str,
+        jti: &'a str,
+    ) -> BoxFuture<'a, TokenStoreResult<Revocation>> {
+        Box::pin(self.select_revocation(user_id, jti))
+    }
+
+    fn cleanup_expired(&self, now: usize) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(self.delete_expired(now))
+    }
+
+    fn revoked_tokens_count(&self) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(self.count_tokens())
+    }
+}
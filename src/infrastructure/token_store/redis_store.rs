@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use futures::{
+    StreamExt,
+    future::{self, BoxFuture},
+};
+use redis::{AsyncCommands, Pipeline, RedisResult, SetExpiry, SetOptions};
+
+use super::{Revocation, TokenStore, TokenStoreResult};
+use crate::{application::constants::*, infrastructure::redis::RedisConnection};
+
+/// Stores the revocations in the `{jwt.revoke}` keys, the timestamps of the global and user revocations
+/// and a key per revoked JWT ID, expired by Redis at the expiration time of the token.
+pub struct RedisTokenStore {
+    redis: RedisConnection,
+}
+
+impl RedisTokenStore {
+    pub const fn new(redis: RedisConnection) -> Self {
+        Self { redis }
+    }
+}
+
+fn token_key(jti: &str) -> String {
+    format!("{}.{}", JWT_REDIS_REVOKED_TOKEN_KEY_PREFIX, jti)
+}
+
+// Adds the revoked token key to the pipeline, expiring with the token.
+fn revoke_token(pipe: &mut Pipeline, jti: &str, expires_at: usize) {
+    let options = SetOptions::default().with_expiration(SetExpiry::EXAT(expires_at as u64));
+    pipe.set_options(token_key(jti), expires_at, options)
+        .ignore();
+}
+
+impl TokenStore for RedisTokenStore {
+    fn revoke_global(&self, before: usize) -> BoxFuture<'_, TokenStoreResult<()>> {
+        Box::pin(async move {
+            let _: () = self
+                .redis
+                .clone()
+                .set(JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY, before)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn revoke_user<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        Box::pin(async move {
+            let _: () = self
+                .redis
+                .clone()
+                .hset(JWT_REDIS_REVOKE_USER_BEFORE_KEY, user_id, before)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn revoke_tokens<'a>(
+        &'a self,
+        jtis: &'a [&'a str],
+        expires_at: usize,
+    ) -> BoxFuture<'a, TokenStoreResult<()>> {
+        Box::pin(async move {
+            let mut pipe = redis::pipe();
+            for jti in jtis {
+                revoke_token(&mut pipe, jti, expires_at);
+            }
+            let _: () = pipe.query_async(&mut self.redis.clone()).await?;
+            Ok(())
+        })
+    }
+
+    fn revocation<'a>(
+        &'a self,
+        user_id: &'a str,
+        jti: &'a str,
+    ) -> BoxFuture<'a, TokenStoreResult<Revocation>> {
+        Box::pin(async move {
+            // The keys are in the same slot, a single round trip in cluster mode too.
+            let (global_before, user_before, token_revoked) = redis::pipe()
+                .get(JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY)
+                .hget(JWT_REDIS_REVOKE_USER_BEFORE_KEY, user_id)
+                .exists(token_key(jti))
+                .query_async(&mut self.redis.clone())
+                .await?;
+            Ok(Revocation {
+                global_before,
+                user_before,
+                token_revoked,
+            })
+        })
+    }
+
+    // The revoked tokens expire on their own.
+    fn cleanup_expired(&self, _now: usize) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(future::ready(Ok(0)))
+    }
+
+    // Counted with SCAN, the revoked tokens are not indexed.
+    fn revoked_tokens_count(&self) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(async move {
+            let mut redis = self.redis.clone();
+            let pattern = format!("{}.*", JWT_REDIS_REVOKED_TOKEN_KEY_PREFIX);
+            let keys = redis.scan_match::<_, String>(pattern).await?;
+            Ok(keys.count().await)
+        })
+    }
+
+    // Renames the revocation keys written before the hash tags, then moves the revoked tokens of the hash
+    // to their keys, for the revoked tokens to stay revoked.
+    fn migrate(&self) -> BoxFuture<'_, TokenStoreResult<usize>> {
+        Box::pin(async move {
+            let mut redis = self.redis.clone();
+            let mut migrated = 0;
+            // The keys are in other slots than the keys with the hash tag, which cluster mode does not
+            // rename to. A key is copied with DUMP and RESTORE, then deleted.
+            for (legacy_key, key) in JWT_REDIS_LEGACY_KEYS {
+                let dump: Option<Vec<u8>> = redis::cmd("DUMP")
+                    .arg(legacy_key)
+                    .query_async(&mut redis)
+                    .await?;
+                let Some(dump) = dump else {
+                    continue;
+                };
+                let ttl: i64 = redis.pttl(legacy_key).await?;
+                let restored: RedisResult<()> = redis::cmd("RESTORE")
+                    .arg(key)
+                    .arg(ttl.max(0))
+                    .arg(dump)
+                    .query_async(&mut redis)
+                    .await;
+                match restored {
+                    Ok(()) => {
+                        let _: () = redis.del(legacy_key).await?;
+                        tracing::info!("renamed the revocation key {} to {}", legacy_key, key);
+                        migrated += 1;
+                    }
+                    Err(e) if e.code() == Some("BUSYKEY") => tracing::warn!(
+                        "revocation key {} not renamed, {} already exists",
+                        legacy_key,
+                        key
+                    ),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            // The values are read as strings, a token with an invalid expiration is skipped.
+            let revoked_tokens: HashMap<String, String> =
+                redis.hgetall(JWT_REDIS_REVOKED_TOKENS_KEY).await?;
+            if revoked_tokens.is_empty() {
+                return Ok(migrated);
+            }
+            let now = chrono::Utc::now().timestamp() as usize;
+            let mut pipe = redis::pipe();
+            let mut moved = 0;
+            for (jti, expires_at) in &revoked_tokens {
+                match expires_at.parse::<usize>() {
+                    Ok(expires_at) => {
+                        if expires_at >= now {
+                            revoke_token(&mut pipe, jti, expires_at);
+                        }
+                        moved += 1;
+                    }
+                    Err(_) => tracing::warn!(
+                        "revoked token {} skipped, invalid expiration: {}",
+                        jti,
+                        expires_at
+                    ),
+                }
+            }
+            // The hash is deleted once the tokens are moved, an interrupted migration is run again.
+            pipe.del(JWT_REDIS_REVOKED_TOKENS_KEY).ignore();
+            let _: () = pipe.query_async(&mut redis).await?;
+            tracing::info!("moved the revoked tokens to their keys, tokens: {}", moved);
+            Ok(migrated + moved)
+        })
+    }
+}
@@ -1,23 +1,56 @@
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use axum_web::application::app;
+use axum_web::{
+    application::{app, config, security::encryption},
+    infrastructure::{logging, telemetry},
+};
 
 #[tokio::main]
 async fn main() {
+    // Load configuration.
+    let config = match config::try_load() {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("Invalid configuration:\n{}", errors);
+            std::process::exit(1);
+        }
+    };
+    if std::env::args().any(|arg| arg == "--print-config") {
+        print!("{}", config.to_redacted_toml());
+        return;
+    }
+
     // Tracing configuration.
-    let filter_layer = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "axum_web=trace".into());
-    let fmt_layer = tracing_subscriber::fmt::layer()
-        .compact()
-        .with_target(false)
-        .with_file(true)
-        .with_line_number(true);
+    let tracer_provider = telemetry::tracer_provider(&config);
+    let filter_layer = logging::filter(&config);
+    let fmt_layer = logging::layer(&config, std::io::stdout);
     tracing_subscriber::registry()
         .with(filter_layer)
         .with(fmt_layer)
+        .with(telemetry::layer(&tracer_provider))
         .init();
 
     tracing::info!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 
-    app::run().await;
+    // Load the master key of the note encryption at rest.
+    let note_cipher = match encryption::open(&config) {
+        Ok(note_cipher) => note_cipher,
+        Err(e) => {
+            tracing::error!("Could not load the master key: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Maintenance commands and the worker mode.
+    match std::env::args().nth(1).as_deref() {
+        Some("rebuild-stats") => app::rebuild_stats(config, note_cipher).await,
+        Some("reencrypt-notes") => app::reencrypt_notes(config, note_cipher).await,
+        Some("worker") => app::run_worker(config, note_cipher).await,
+        _ => app::run(config, note_cipher).await,
+    }
+
+    // Export the remaining spans.
+    if let Err(e) = tracer_provider.shutdown() {
+        tracing::error!("Could not shut down the tracer provider: {}", e);
+    }
 }
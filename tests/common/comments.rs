@@ -0,0 +1,137 @@
+use axum_web::domain::models::note_comment::{NoteComment, NoteCommentPage, NoteCommentRequest};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_NOTES, API_V1},
+    helpers,
+};
+
+pub async fn add(
+    note_id: Uuid,
+    request: &NoteCommentRequest,
+    access_token: &str,
+) -> TestResult<NoteComment> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/comments", note_id));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteComment>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list(note_id: Uuid, query: &str, access_token: &str) -> TestResult<NoteCommentPage> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/comments{}", note_id, query),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteCommentPage>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn update(
+    note_id: Uuid,
+    comment_id: Uuid,
+    request: &NoteCommentRequest,
+    access_token: &str,
+) -> TestResult<NoteComment> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/comments/{}", note_id, comment_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteComment>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get(note_id: Uuid, comment_id: Uuid, access_token: &str) -> TestResult<NoteComment> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/comments/{}", note_id, comment_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteComment>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete(note_id: Uuid, comment_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/comments/{}", note_id, comment_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<()>(response, StatusCode::OK)
+        .await
+        .map(|_| ())
+}
+
+// Resolves the thread, or reopens it.
+pub async fn resolve(
+    note_id: Uuid,
+    comment_id: Uuid,
+    resolved: bool,
+    access_token: &str,
+) -> TestResult<NoteComment> {
+    let action = if resolved { "resolve" } else { "unresolve" };
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/comments/{}/{}", note_id, comment_id, action),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteComment>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
@@ -3,6 +3,13 @@ pub const API_PATH_HEALTH: &str = "health";
 pub const API_PATH_VERSION: &str = "version";
 pub const API_PATH_AUTH: &str = "auth";
 pub const API_PATH_USERS: &str = "users";
+pub const API_PATH_NOTES: &str = "notes";
+pub const API_PATH_REMINDERS: &str = "reminders";
+pub const API_PATH_TEMPLATES: &str = "templates";
+pub const API_PATH_STATS: &str = "stats";
+pub const API_PATH_JOBS: &str = "jobs";
+pub const API_PATH_TASKS: &str = "tasks";
+pub const API_PATH_WEBHOOKS: &str = "webhooks";
 pub const API_PATH_ACCOUNTS: &str = "accounts";
 pub const API_PATH_TRANSACTIONS: &str = "transactions";
 
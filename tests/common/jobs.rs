@@ -0,0 +1,53 @@
+use axum_web::domain::models::job::{JobResponse, JobRun};
+use reqwest::StatusCode;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_JOBS, API_V1},
+    helpers,
+};
+
+pub async fn list(access_token: &str) -> TestResult<Vec<JobResponse>> {
+    let url = helpers::build_path(API_V1, API_PATH_JOBS);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<JobResponse>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn runs(name: &str, access_token: &str) -> TestResult<Vec<JobRun>> {
+    let url = helpers::build_url(API_V1, API_PATH_JOBS, &format!("{}/runs", name));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<JobRun>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn run(name: &str, access_token: &str) -> TestResult<JobRun> {
+    let url = helpers::build_url(API_V1, API_PATH_JOBS, &format!("{}/run", name));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<JobRun>(response, StatusCode::ACCEPTED)
+        .await
+        .map(|v| v.unwrap())
+}
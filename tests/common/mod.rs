@@ -1,11 +1,19 @@
 pub mod auth;
+pub mod comments;
 pub mod constants;
 pub mod error;
 pub mod helpers;
 pub mod hyper_fetch;
+pub mod jobs;
+pub mod notes;
+pub mod reminders;
 pub mod root;
+pub mod stats;
+pub mod tasks;
+pub mod templates;
 pub mod test_app;
 pub mod users;
+pub mod webhooks;
 
 pub use error::TestError;
 pub type TestResult<T> = Result<T, TestError>;
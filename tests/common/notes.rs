@@ -0,0 +1,375 @@
+use axum_web::domain::models::{
+    note::{Note, RenderedNote},
+    note_flags::{NoteFlags, NoteFlagsRequest, ViewedNote},
+    note_import::{NoteArchiveFormat, NoteImport},
+    note_key::{NoteKey, NoteKeyRequest},
+    note_link::{NoteGraph, NoteLink},
+};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_NOTES, API_V1},
+    helpers,
+};
+
+pub fn test_note(user_id: Uuid, text: &str) -> Note {
+    Note {
+        id: Uuid::new_v4(),
+        user_id,
+        title: None,
+        text: text.to_string(),
+        encryption: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+pub async fn add(note: Note, access_token: &str) -> TestResult<Note> {
+    let url = helpers::build_path(API_V1, API_PATH_NOTES);
+    let json_param = serde_json::json!(note);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(&json_param)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Note>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn update(note: Note, access_token: &str) -> TestResult<Note> {
+    update_at(note.id, note, access_token).await
+}
+
+// Updates the note at the path of the given ID, the ID of the body can differ.
+pub async fn update_at(note_id: Uuid, note: Note, access_token: &str) -> TestResult<Note> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &note_id.to_string());
+    let json_param = serde_json::json!(note);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(&json_param)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Note>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete(note_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &note_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<String>(response, StatusCode::OK).await?;
+    Ok(())
+}
+
+pub async fn get(note_id: Uuid, access_token: &str) -> TestResult<Note> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &note_id.to_string());
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Note>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get_rendered(note_id: Uuid, access_token: &str) -> TestResult<RenderedNote> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}?format=html", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<RenderedNote>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get_html(note_id: Uuid, access_token: &str) -> TestResult<String> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &note_id.to_string());
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "text/html")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let content_type = response.headers()["content-type"].to_str().unwrap();
+    assert!(content_type.starts_with("text/html"));
+
+    Ok(response.text().await?)
+}
+
+pub async fn links(note_id: Uuid, access_token: &str) -> TestResult<Vec<NoteLink>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/links", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<NoteLink>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn backlinks(note_id: Uuid, access_token: &str) -> TestResult<Vec<Note>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/backlinks", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<Note>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn graph(user_id: Uuid, access_token: &str) -> TestResult<NoteGraph> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("graph/{}", user_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteGraph>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn export(format: &str, access_token: &str) -> TestResult<Vec<u8>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("export?format={}", format));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(response.bytes().await?.to_vec())
+}
+
+pub async fn import(
+    format: NoteArchiveFormat,
+    body: Vec<u8>,
+    access_token: &str,
+) -> TestResult<(StatusCode, NoteImport)> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, "import");
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Content-Type", format.content_type())
+        .header("Authorization", authorization)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let expected_status = if status == StatusCode::ACCEPTED {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::OK
+    };
+    helpers::dispatch_reqwest_response::<NoteImport>(response, expected_status)
+        .await
+        .map(|v| (status, v.unwrap()))
+}
+
+pub async fn get_import(import_id: Uuid, access_token: &str) -> TestResult<NoteImport> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("import/{}", import_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteImport>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list_keys(note_id: Uuid, access_token: &str) -> TestResult<Vec<NoteKey>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/keys", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<NoteKey>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get_key(note_id: Uuid, user_id: Uuid, access_token: &str) -> TestResult<NoteKey> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/keys/{}", note_id, user_id),
+    );
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteKey>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn put_key(
+    note_id: Uuid,
+    user_id: Uuid,
+    note_key: NoteKeyRequest,
+    access_token: &str,
+) -> TestResult<NoteKey> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/keys/{}", note_id, user_id),
+    );
+    let json_param = serde_json::json!(note_key);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(&json_param)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteKey>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete_key(note_id: Uuid, user_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("{}/keys/{}", note_id, user_id),
+    );
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<String>(response, StatusCode::OK).await?;
+    Ok(())
+}
+
+pub async fn list_by_user(
+    user_id: Uuid,
+    query: &str,
+    access_token: &str,
+) -> TestResult<Vec<ViewedNote>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("user{}", query));
+    let json_param = serde_json::json!({ "id": user_id });
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(&json_param)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<ViewedNote>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get_flags(note_id: Uuid, access_token: &str) -> TestResult<NoteFlags> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/flags", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteFlags>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn put_flags(
+    note_id: Uuid,
+    request: &NoteFlagsRequest,
+    access_token: &str,
+) -> TestResult<NoteFlags> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/flags", note_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteFlags>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
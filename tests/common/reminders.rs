@@ -0,0 +1,147 @@
+use axum_web::domain::models::reminder::{Reminder, ReminderRequest, SnoozeRequest};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_NOTES, API_PATH_REMINDERS, API_V1},
+    helpers,
+};
+
+pub async fn add(
+    note_id: Uuid,
+    request: &ReminderRequest,
+    access_token: &str,
+) -> TestResult<Reminder> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/reminders", note_id));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Reminder>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list_by_note(note_id: Uuid, access_token: &str) -> TestResult<Vec<Reminder>> {
+    let url = helpers::build_url(API_V1, API_PATH_NOTES, &format!("{}/reminders", note_id));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<Reminder>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list(access_token: &str) -> TestResult<Vec<Reminder>> {
+    let url = helpers::build_path(API_V1, API_PATH_REMINDERS);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<Reminder>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get(reminder_id: Uuid, access_token: &str) -> TestResult<Reminder> {
+    let url = helpers::build_url(API_V1, API_PATH_REMINDERS, &reminder_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Reminder>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn snooze(
+    reminder_id: Uuid,
+    request: &SnoozeRequest,
+    access_token: &str,
+) -> TestResult<Reminder> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_REMINDERS,
+        &format!("{}/snooze", reminder_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Reminder>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn dismiss(reminder_id: Uuid, access_token: &str) -> TestResult<Reminder> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_REMINDERS,
+        &format!("{}/dismiss", reminder_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Reminder>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete(reminder_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(API_V1, API_PATH_REMINDERS, &reminder_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<()>(response, StatusCode::OK)
+        .await
+        .map(|_| ())
+}
+
+// Opens the server-sent event stream of the reminders.
+pub async fn events(access_token: &str) -> TestResult<reqwest::Response> {
+    let url = helpers::build_url(API_V1, API_PATH_REMINDERS, "events");
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "text/event-stream")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(response)
+}
@@ -0,0 +1,41 @@
+use axum_web::domain::models::stats::{StatResponse, Timeseries};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_STATS, API_V1},
+    helpers,
+};
+
+pub async fn get(user_id: Uuid, access_token: &str) -> TestResult<StatResponse> {
+    let url = helpers::build_url(API_V1, API_PATH_STATS, &user_id.to_string());
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<StatResponse>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn timeseries(query: &str, access_token: &str) -> TestResult<Timeseries> {
+    let url = helpers::build_url(API_V1, API_PATH_STATS, &format!("timeseries?{}", query));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Timeseries>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
@@ -0,0 +1,40 @@
+use axum_web::domain::models::task::TaskSummary;
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_TASKS, API_V1},
+    helpers,
+};
+
+pub async fn list(query: &str, access_token: &str) -> TestResult<Vec<TaskSummary>> {
+    let mut url = helpers::build_path(API_V1, API_PATH_TASKS);
+    url.set_query(Some(query));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<TaskSummary>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn retry(task_id: Uuid, access_token: &str) -> TestResult<TaskSummary> {
+    let url = helpers::build_url(API_V1, API_PATH_TASKS, &format!("{}/retry", task_id));
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<TaskSummary>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
@@ -0,0 +1,117 @@
+use axum_web::domain::models::{
+    note::Note,
+    note_template::{FromTemplateRequest, NoteTemplate, NoteTemplateRequest},
+};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_NOTES, API_PATH_TEMPLATES, API_V1},
+    helpers,
+};
+
+pub async fn add(request: &NoteTemplateRequest, access_token: &str) -> TestResult<NoteTemplate> {
+    let url = helpers::build_path(API_V1, API_PATH_TEMPLATES);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteTemplate>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn update(
+    template_id: Uuid,
+    request: &NoteTemplateRequest,
+    access_token: &str,
+) -> TestResult<NoteTemplate> {
+    let url = helpers::build_url(API_V1, API_PATH_TEMPLATES, &template_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteTemplate>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list(access_token: &str) -> TestResult<Vec<NoteTemplate>> {
+    let url = helpers::build_path(API_V1, API_PATH_TEMPLATES);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<NoteTemplate>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get(template_id: Uuid, access_token: &str) -> TestResult<NoteTemplate> {
+    let url = helpers::build_url(API_V1, API_PATH_TEMPLATES, &template_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<NoteTemplate>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete(template_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(API_V1, API_PATH_TEMPLATES, &template_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<()>(response, StatusCode::OK)
+        .await
+        .map(|_| ())
+}
+
+pub async fn add_note(
+    template_id: Uuid,
+    request: &FromTemplateRequest,
+    access_token: &str,
+) -> TestResult<Note> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_NOTES,
+        &format!("from-template/{}", template_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Note>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
@@ -1,14 +1,25 @@
 use std::{sync::Arc, time::Duration};
 
 use reqwest::StatusCode;
-use tokio::{sync::Mutex, time::Instant};
+use tokio::time::Instant;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use axum_web::{
     api,
-    application::{config, state::AppState},
+    application::{
+        config::{self, ConfigHandle},
+        security::encryption,
+        service::{
+            event_service,
+            notification_service::{self, Notifiers},
+            reminder_service, task_service,
+        },
+        state::AppState,
+    },
     infrastructure::{
         database::{Database, TestDatabase},
-        redis,
+        mail::LogMailer,
+        redis, telemetry, token_store,
     },
 };
 
@@ -26,6 +37,12 @@ pub async fn run() -> TestDatabase {
     let config = config::load();
     helpers::CONFIG.get_or_init(|| config.clone());
 
+    // Trace the requests, the spans are not exported.
+    let tracer_provider = telemetry::tracer_provider(&config);
+    let _ = tracing_subscriber::registry()
+        .with(telemetry::layer(&tracer_provider))
+        .try_init();
+
     // Connect to Redis.
     let redis = redis::open(&config).await;
 
@@ -34,13 +51,40 @@ pub async fn run() -> TestDatabase {
         .await
         .expect("Failed to connect to the test database.");
 
+    // Load the master key of the note encryption at rest.
+    let note_cipher = encryption::open(&config).expect("Failed to load the master key.");
+
+    // Select the store of the revoked tokens.
+    let token_store = token_store::open(&config, &redis, test_database.pool());
+
     // Build the application state.
     let shared_state = Arc::new(AppState {
-        config,
+        config: ConfigHandle::new(config),
         db_pool: test_database.pool().clone(),
-        redis: Mutex::new(redis),
+        redis,
+        token_store,
+        note_cipher,
+        notifiers: Notifiers::new(Box::new(LogMailer), test_database.pool().clone()),
     });
 
+    // Run the reminder scheduler.
+    tokio::spawn(reminder_service::run_scheduler(Arc::clone(&shared_state)));
+
+    // Push the fired reminders to the event streams.
+    tokio::spawn(notification_service::run_listener(Arc::clone(
+        &shared_state,
+    )));
+
+    // Dispatch the events to the webhooks.
+    tokio::spawn(event_service::run_dispatcher(Arc::clone(&shared_state)));
+
+    // Run the task workers.
+    let workers = shared_state.config.get().task_workers;
+    tokio::spawn(task_service::run_workers(
+        Arc::clone(&shared_state),
+        workers,
+    ));
+
     // Run the api server.
     tokio::spawn(async move {
         api::server::start(shared_state).await;
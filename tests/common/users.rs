@@ -1,4 +1,7 @@
-use axum_web::domain::models::user::User;
+use axum_web::domain::models::{
+    note_key::{UserKey, UserKeyRequest},
+    user::User,
+};
 use reqwest::StatusCode;
 use uuid::Uuid;
 
@@ -8,6 +11,21 @@ use crate::common::{
     helpers,
 };
 
+pub fn test_user() -> User {
+    let username = format!("test-{}", Uuid::new_v4());
+    User {
+        id: Uuid::new_v4(),
+        username: username.clone(),
+        email: format!("{}@email.com", username),
+        password_hash: "xyz123".to_string(),
+        password_salt: "xyz123".to_string(),
+        active: true,
+        roles: "guest".to_string(),
+        created_at: None,
+        updated_at: None,
+    }
+}
+
 pub async fn list(access_token: &str) -> TestResult<Vec<User>> {
     let url = helpers::build_path(API_V1, API_PATH_USERS);
 
@@ -87,3 +105,40 @@ pub async fn delete(user_id: Uuid, access_token: &str) -> TestResult<()> {
     helpers::dispatch_reqwest_response::<String>(response, StatusCode::OK).await?;
     Ok(())
 }
+
+pub async fn get_key(user_id: Uuid, access_token: &str) -> TestResult<UserKey> {
+    let url = helpers::build_url(API_V1, API_PATH_USERS, &format!("{}/key", user_id));
+
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<UserKey>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn put_key(
+    user_id: Uuid,
+    user_key: UserKeyRequest,
+    access_token: &str,
+) -> TestResult<UserKey> {
+    let url = helpers::build_url(API_V1, API_PATH_USERS, &format!("{}/key", user_id));
+    let json_param = serde_json::json!(user_key);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(&json_param)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<UserKey>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
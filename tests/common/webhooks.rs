@@ -0,0 +1,132 @@
+use axum_web::domain::models::webhook::{Webhook, WebhookDelivery, WebhookRequest};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+use crate::common::{
+    TestResult,
+    constants::{API_PATH_WEBHOOKS, API_V1},
+    helpers,
+};
+
+pub async fn add(request: &WebhookRequest, access_token: &str) -> TestResult<Webhook> {
+    let url = helpers::build_path(API_V1, API_PATH_WEBHOOKS);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Webhook>(response, StatusCode::CREATED)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn update(
+    webhook_id: Uuid,
+    request: &WebhookRequest,
+    access_token: &str,
+) -> TestResult<Webhook> {
+    let url = helpers::build_url(API_V1, API_PATH_WEBHOOKS, &webhook_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .put(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .json(request)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Webhook>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn list(access_token: &str) -> TestResult<Vec<Webhook>> {
+    let url = helpers::build_path(API_V1, API_PATH_WEBHOOKS);
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<Webhook>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn get(webhook_id: Uuid, access_token: &str) -> TestResult<Webhook> {
+    let url = helpers::build_url(API_V1, API_PATH_WEBHOOKS, &webhook_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Webhook>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn delete(webhook_id: Uuid, access_token: &str) -> TestResult<()> {
+    let url = helpers::build_url(API_V1, API_PATH_WEBHOOKS, &webhook_id.to_string());
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .delete(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<()>(response, StatusCode::OK)
+        .await
+        .map(|_| ())
+}
+
+pub async fn deliveries(webhook_id: Uuid, access_token: &str) -> TestResult<Vec<WebhookDelivery>> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_WEBHOOKS,
+        &format!("{}/deliveries", webhook_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<Vec<WebhookDelivery>>(response, StatusCode::OK)
+        .await
+        .map(|v| v.unwrap())
+}
+
+pub async fn redeliver(
+    webhook_id: Uuid,
+    delivery_id: Uuid,
+    access_token: &str,
+) -> TestResult<WebhookDelivery> {
+    let url = helpers::build_url(
+        API_V1,
+        API_PATH_WEBHOOKS,
+        &format!("{}/deliveries/{}/redeliver", webhook_id, delivery_id),
+    );
+    let authorization = format!("Bearer {}", access_token);
+    let response = reqwest::Client::new()
+        .post(url.as_str())
+        .header("Accept", "application/json")
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+
+    helpers::dispatch_reqwest_response::<WebhookDelivery>(response, StatusCode::ACCEPTED)
+        .await
+        .map(|v| v.unwrap())
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        config::{ConfigHandle, ConfigSources},
+        security::encryption,
+        service::{config_service, notification_service::Notifiers},
+        state::AppState,
+    },
+    infrastructure::{mail::LogMailer, redis, token_store::MemoryTokenStore},
+};
+
+pub mod common;
+use common::{helpers, test_app};
+
+#[tokio::test]
+#[serial]
+async fn config_reload_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+    let state = Arc::new(AppState {
+        config: ConfigHandle::new(config.clone()),
+        db_pool: test_db.pool().clone(),
+        redis: redis::open(config).await,
+        token_store: Box::new(MemoryTokenStore::new()),
+        note_cipher: encryption::open(config).expect("Failed to load the master key."),
+        notifiers: Notifiers::new(Box::new(LogMailer), test_db.pool().clone()),
+    });
+    assert!(state.config.get().cors_allows("https://any.example.com"));
+
+    // The reloadable settings are applied, the others are reported as requiring a restart.
+    let path = std::env::temp_dir().join(format!("config-{}.toml", Uuid::new_v4()));
+    std::fs::write(
+        &path,
+        r#"
+cors_allowed_origins = "https://app.example.com, https://admin.example.com"
+log_level = "axum_web=info"
+"#,
+    )
+    .unwrap();
+    let mut sources = ConfigSources::read();
+    sources
+        .env
+        .insert("CONFIG_FILE".to_owned(), path.display().to_string());
+    sources.env.insert(
+        "OTEL_SERVICE_NAME".to_owned(),
+        "reloaded-service".to_owned(),
+    );
+    let changes = config_service::reload_from(sources.clone(), &state).unwrap();
+    let mut changed: Vec<_> = changes
+        .iter()
+        .map(|change| (change.key.as_str(), change.reloadable))
+        .collect();
+    changed.sort_unstable();
+    assert_eq!(
+        changed,
+        vec![
+            ("cors_allowed_origins", true),
+            ("log_level", true),
+            ("otel_service_name", false),
+        ]
+    );
+    let reloaded = state.config.get();
+    assert!(reloaded.cors_allows("https://admin.example.com"));
+    assert!(!reloaded.cors_allows("https://any.example.com"));
+    assert_eq!(reloaded.log_level, "axum_web=info");
+    assert_eq!(reloaded.otel_service_name, config.otel_service_name);
+
+    // An invalid configuration is rejected and the current one is kept.
+    std::fs::write(
+        &path,
+        "cors_allowed_origins = \"ftp://files.example.com\"\n",
+    )
+    .unwrap();
+    let errors = config_service::reload_from(sources, &state).unwrap_err();
+    assert_eq!(errors.0.len(), 1);
+    assert!(!state.config.get().cors_allows("https://any.example.com"));
+
+    std::fs::remove_file(path).unwrap();
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn config_reload_env_file_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // The shipped `.env` file sets the token lifetimes and the config file is empty.
+    let path = std::env::temp_dir().join(format!("config-{}.toml", Uuid::new_v4()));
+    std::fs::write(&path, "").unwrap();
+    let mut sources = ConfigSources::with_env_file(".env");
+    sources
+        .env
+        .insert("CONFIG_FILE".to_owned(), path.display().to_string());
+    let config = sources.clone().load().unwrap();
+    assert_eq!(config.jwt_expire_access_token_seconds, 3600);
+    assert_eq!(config.jwt_expire_refresh_token_seconds, 7776000);
+
+    let state = Arc::new(AppState {
+        config: ConfigHandle::new(config.clone()),
+        db_pool: test_db.pool().clone(),
+        redis: redis::open(helpers::config()).await,
+        token_store: Box::new(MemoryTokenStore::new()),
+        note_cipher: None,
+        notifiers: Notifiers::new(Box::new(LogMailer), test_db.pool().clone()),
+    });
+
+    // The token lifetimes of the edited config file override the `.env` file.
+    std::fs::write(
+        &path,
+        r#"
+[jwt]
+expire_access_token_seconds = 600
+expire_refresh_token_seconds = 86400
+"#,
+    )
+    .unwrap();
+    let changes = config_service::reload_from(sources, &state).unwrap();
+    let mut changed: Vec<_> = changes
+        .iter()
+        .map(|change| (change.key.as_str(), change.reloadable))
+        .collect();
+    changed.sort_unstable();
+    assert_eq!(
+        changed,
+        vec![
+            ("jwt_expire_access_token_seconds", true),
+            ("jwt_expire_refresh_token_seconds", true),
+        ]
+    );
+    let reloaded = state.config.get();
+    assert_eq!(reloaded.jwt_expire_access_token_seconds, 600);
+    assert_eq!(reloaded.jwt_expire_refresh_token_seconds, 86400);
+
+    std::fs::remove_file(path).unwrap();
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
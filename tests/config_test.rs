@@ -0,0 +1,252 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use uuid::Uuid;
+
+use axum_web::application::config::{self, ConfigError};
+
+// Writes the content into a new temporary file.
+fn temp_file(extension: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("config-{}.{}", Uuid::new_v4(), extension));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn config_layers_test() {
+    let secret = temp_file("txt", "file-secret-0123456789\n");
+    let file = temp_file(
+        "toml",
+        &format!(
+            r#"
+service_port = 9090
+jwt_secret_file = "{}"
+
+[postgres]
+user = "file-user"
+password = "file-password"
+db = "file-db"
+connection_pool = 8
+"#,
+            secret.display()
+        ),
+    );
+
+    // The config file overrides the defaults, the environment overrides the config file.
+    let config = config::from_env(env(&[
+        ("CONFIG_FILE", file.to_str().unwrap()),
+        ("POSTGRES_DB", "env-db"),
+    ]))
+    .unwrap();
+    assert_eq!(config.service_host, "127.0.0.1");
+    assert_eq!(config.service_port, 9090);
+    assert_eq!(config.postgres_user, "file-user");
+    assert_eq!(config.postgres_db, "env-db");
+    assert_eq!(config.postgres_connection_pool, 8);
+    assert_eq!(config.jwt_secret, "file-secret-0123456789");
+    assert_eq!(config.jwt_expire_access_token_seconds, 3600);
+
+    // The `_FILE` variable of the environment overrides the value of the config file.
+    let env_secret = temp_file("txt", "env-secret");
+    let config = config::from_env(env(&[
+        ("CONFIG_FILE", file.to_str().unwrap()),
+        ("POSTGRES_PASSWORD_FILE", env_secret.to_str().unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(config.postgres_password, "env-secret");
+
+    // The secrets are redacted from the printed configuration, which is a valid config file.
+    let printed = config.to_redacted_toml();
+    assert!(!printed.contains("env-secret"));
+    assert!(!printed.contains("file-secret"));
+    assert!(printed.contains(r#"postgres_password = "[REDACTED]""#));
+    let printed_file = temp_file("toml", &printed);
+    let reloaded =
+        config::from_env(env(&[("CONFIG_FILE", printed_file.to_str().unwrap())])).unwrap();
+    assert_eq!(reloaded.postgres_db, config.postgres_db);
+    assert_eq!(reloaded.service_port, config.service_port);
+
+    for path in [secret, file, env_secret, printed_file] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn config_yaml_test() {
+    let file = temp_file(
+        "yaml",
+        r#"
+postgres:
+  user: yaml-user
+  password: yaml-password
+  db: yaml-db
+jwt:
+  secret: yaml-secret-0123456789
+  enable_revoked_tokens: false
+log_format: json
+"#,
+    );
+    let config = config::from_env(env(&[("CONFIG_FILE", file.to_str().unwrap())])).unwrap();
+    assert_eq!(config.postgres_user, "yaml-user");
+    assert!(!config.jwt_enable_revoked_tokens);
+    assert_eq!(config.log_format, "json");
+    std::fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn config_errors_test() {
+    let file = temp_file(
+        "toml",
+        r#"
+service_port = "not-a-port"
+postgres_connection_pool = 0
+unknown_setting = 1
+"#,
+    );
+
+    // All the errors are reported at once.
+    let errors = config::from_env(env(&[
+        ("CONFIG_FILE", file.to_str().unwrap()),
+        ("POSTGRES_USER", "user"),
+        ("POSTGRES_DB", "db"),
+        ("POSTGRES_PASSWORD_FILE", "/nonexistent/secret"),
+        ("LOG_FORMAT", "xml"),
+        ("TOKEN_STORE", "file"),
+        ("NOTE_ENCRYPTION_ENABLED", "true"),
+    ]))
+    .unwrap_err();
+    let keys: Vec<String> = errors
+        .0
+        .iter()
+        .map(|e| e.to_string().split(':').next().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "JWT_SECRET",
+            "SERVICE_PORT",
+            "POSTGRES_PASSWORD_FILE",
+            "POSTGRES_PASSWORD",
+            "UNKNOWN_SETTING",
+            "POSTGRES_CONNECTION_POOL",
+            "TOKEN_STORE",
+            "MASTER_KEY_FILE",
+            "LOG_FORMAT",
+        ]
+    );
+    assert!(
+        errors
+            .0
+            .contains(&ConfigError::Missing("JWT_SECRET".to_owned()))
+    );
+
+    // Unsupported config files are reported.
+    let unsupported = temp_file("ini", "service_port = 1");
+    let errors =
+        config::from_env(env(&[("CONFIG_FILE", unsupported.to_str().unwrap())])).unwrap_err();
+    assert!(matches!(errors.0[0], ConfigError::File { .. }));
+
+    for path in [file, unsupported] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn config_diff_test() {
+    let vars = [
+        ("POSTGRES_USER", "user"),
+        ("POSTGRES_PASSWORD", "password"),
+        ("POSTGRES_DB", "db"),
+        ("JWT_SECRET", "first-secret"),
+    ];
+    let config = config::from_env(env(&vars)).unwrap();
+    let mut other = config.clone();
+    other.jwt_secret = "second-secret".to_string();
+    other.jwt_expire_access_token_seconds = 60;
+
+    // The secrets changes are reported redacted.
+    let changes = config.diff(&other);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        changes[0].to_string(),
+        "jwt_expire_access_token_seconds: 3600 -> 60"
+    );
+    assert!(changes[0].reloadable);
+    assert_eq!(
+        changes[1].to_string(),
+        r#"jwt_secret: "[REDACTED]" -> "[REDACTED]""#
+    );
+    assert!(!changes[1].reloadable);
+
+    // Only the reloadable settings are applied.
+    let reloaded = config.with_reloaded(&other);
+    assert_eq!(reloaded.jwt_expire_access_token_seconds, 60);
+    assert_eq!(reloaded.jwt_secret, "first-secret");
+}
+
+#[test]
+fn config_redis_test() {
+    let vars = [
+        ("POSTGRES_USER", "user"),
+        ("POSTGRES_PASSWORD", "password"),
+        ("POSTGRES_DB", "db"),
+        ("JWT_SECRET", "secret"),
+    ];
+
+    // The nodes default to the Redis host.
+    let config = config::from_env(env(&vars)).unwrap();
+    assert_eq!(config.redis_mode, "standalone");
+    assert_eq!(
+        config.redis_node_addrs(),
+        vec![("127.0.0.1".to_owned(), 6379)]
+    );
+
+    // The sentinels are the configured nodes, the password is redacted.
+    let mut sentinel_vars = vars.to_vec();
+    sentinel_vars.extend([
+        ("REDIS_MODE", "sentinel"),
+        ("REDIS_NODES", "sentinel-1:26379, sentinel-2:26379"),
+        ("REDIS_PASSWORD", "redis-secret"),
+        ("REDIS_DB", "2"),
+        ("REDIS_TLS", "true"),
+    ]);
+    let config = config::from_env(env(&sentinel_vars)).unwrap();
+    assert_eq!(
+        config.redis_node_addrs(),
+        vec![
+            ("sentinel-1".to_owned(), 26379),
+            ("sentinel-2".to_owned(), 26379)
+        ]
+    );
+    assert_eq!(config.redis_sentinel_master, "mymaster");
+    assert!(config.redis_tls);
+    assert!(!config.to_redacted_toml().contains("redis-secret"));
+
+    // Cluster mode only has the database 0.
+    let mut invalid_vars = vars.to_vec();
+    invalid_vars.extend([
+        ("REDIS_MODE", "cluster"),
+        ("REDIS_NODES", "node-1:6379,node-2"),
+        ("REDIS_DB", "1"),
+    ]);
+    let errors = config::from_env(env(&invalid_vars)).unwrap_err();
+    let keys: Vec<&str> = errors
+        .0
+        .iter()
+        .map(|e| match e {
+            ConfigError::Invalid { key, .. } => key.as_str(),
+            _ => "",
+        })
+        .collect();
+    assert_eq!(keys, vec!["REDIS_NODES", "REDIS_DB"]);
+
+    let mut invalid_vars = vars.to_vec();
+    invalid_vars.push(("REDIS_MODE", "replicated"));
+    let errors = config::from_env(env(&invalid_vars)).unwrap_err();
+    assert_eq!(errors.0.len(), 1);
+}
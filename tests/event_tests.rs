@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::constants::EVENT_MAX_ATTEMPTS,
+    domain::models::{event::EventKind, user::User, webhook::WebhookRequest},
+    infrastructure::database::DatabasePool,
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    notes, test_app, users, webhooks,
+};
+
+const NOTE_TEXT: &str = "# Domain events\n\nWritten to the outbox.";
+
+const DISPATCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn admin_token() -> String {
+    auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.")
+        .access_token
+}
+
+// The outbox events of the kind about the entity, the key of its ID in the data.
+async fn event_ids(pool: &DatabasePool, kind: EventKind, key: &str, id: &str) -> Vec<Uuid> {
+    sqlx::query_scalar(
+        "SELECT id FROM outbox_events WHERE event = $1 AND data->>$2 = $3 ORDER BY created_at",
+    )
+    .bind(kind)
+    .bind(key)
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+async fn consumers(pool: &DatabasePool, event_id: Uuid) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT consumer FROM outbox_consumers WHERE event_id = $1 ORDER BY consumer",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+// Waits for the dispatch of the event, returns its attempts.
+async fn wait_dispatched(pool: &DatabasePool, event_id: Uuid) -> i32 {
+    tokio::time::timeout(DISPATCH_TIMEOUT, async {
+        loop {
+            let (attempts, dispatched): (i32, bool) = sqlx::query_as(
+                "SELECT attempts, dispatched_at IS NOT NULL FROM outbox_events WHERE id = $1",
+            )
+            .bind(event_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            if dispatched {
+                return attempts;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Event dispatch timeout.")
+}
+
+// Waits for a failed dispatch of the event, returns its error.
+async fn wait_failed_attempt(pool: &DatabasePool, event_id: Uuid) -> String {
+    tokio::time::timeout(DISPATCH_TIMEOUT, async {
+        loop {
+            let last_error: Option<String> =
+                sqlx::query_scalar("SELECT last_error FROM outbox_events WHERE id = $1")
+                    .bind(event_id)
+                    .fetch_one(pool)
+                    .await
+                    .unwrap();
+            if let Some(last_error) = last_error {
+                return last_error;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Event failure timeout.")
+}
+
+#[tokio::test]
+#[serial]
+async fn deactivated_user_event_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let pool = test_db.pool();
+    let access_token = admin_token().await;
+
+    let user = users::add(users::test_user(), &access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    // The deactivation is an event of its own, next to the update.
+    let user_id = user.id.to_string();
+    let user = users::update(
+        User {
+            active: false,
+            ..user
+        },
+        &access_token,
+    )
+    .await
+    .expect("User update error.");
+    assert!(!user.active);
+    let updated = event_ids(pool, EventKind::UserUpdated, "id", &user_id).await;
+    assert_eq!(updated.len(), 1);
+    let deactivated = event_ids(pool, EventKind::UserDeactivated, "id", &user_id).await;
+    assert_eq!(deactivated.len(), 1);
+
+    // The revocation subscriber revokes the tokens of the user, and publishes the revocation.
+    wait_dispatched(pool, deactivated[0]).await;
+    assert_eq!(
+        consumers(pool, deactivated[0]).await,
+        ["log", "revocations", "webhooks"]
+    );
+    let revoked = event_ids(pool, EventKind::TokensRevoked, "user_id", &user_id).await;
+    assert_eq!(revoked.len(), 1);
+    wait_dispatched(pool, revoked[0]).await;
+    let result = auth::refresh(&user_tokens.refresh_token).await;
+    assert_api_error_status!(result, StatusCode::UNAUTHORIZED);
+
+    // An inactive user is not deactivated again.
+    let access_token = admin_token().await;
+    users::update(user, &access_token)
+        .await
+        .expect("User update error.");
+    let updated = event_ids(pool, EventKind::UserUpdated, "id", &user_id).await;
+    assert_eq!(updated.len(), 2);
+    let deactivated = event_ids(pool, EventKind::UserDeactivated, "id", &user_id).await;
+    assert_eq!(deactivated.len(), 1);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_change_event_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let pool = test_db.pool();
+    let access_token = admin_token().await;
+
+    let user = users::add(users::test_user(), &access_token)
+        .await
+        .expect("User creation error.");
+    let created = event_ids(pool, EventKind::UserCreated, "email", &user.email).await;
+    assert_eq!(created.len(), 1);
+
+    // The event is rolled back with the change.
+    let duplicate = User {
+        id: Uuid::new_v4(),
+        username: format!("test-{}", Uuid::new_v4()),
+        ..user.clone()
+    };
+    let result = users::add(duplicate, &access_token).await;
+    assert!(result.is_err());
+    let created = event_ids(pool, EventKind::UserCreated, "email", &user.email).await;
+    assert_eq!(created.len(), 1);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_subscriber_event_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let pool = test_db.pool();
+    let access_token = admin_token().await;
+
+    // The webhook subscriber fails while the deliveries can not be written.
+    let request = WebhookRequest {
+        url: "http://127.0.0.1:9/hook".to_string(),
+        secret: "a-secret-of-the-receiver".to_string(),
+        events: vec![EventKind::NoteCreated],
+        active: true,
+    };
+    webhooks::add(&request, &access_token).await.unwrap();
+    sqlx::query(
+        "ALTER TABLE webhook_deliveries ADD CONSTRAINT no_deliveries CHECK (false) NOT VALID",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let user = users::add(users::test_user(), &access_token)
+        .await
+        .expect("User creation error.");
+    let note = notes::add(notes::test_note(user.id, NOTE_TEXT), &access_token)
+        .await
+        .expect("Note creation error.");
+    let created = event_ids(pool, EventKind::NoteCreated, "id", &note.id.to_string()).await;
+    assert_eq!(created.len(), 1);
+    let event_id = created[0];
+
+    // The failed event is retried, the sink which handled it is not sent the event again.
+    let last_error = wait_failed_attempt(pool, event_id).await;
+    assert!(last_error.starts_with("webhooks: "));
+    assert_eq!(consumers(pool, event_id).await, ["log"]);
+
+    // The event failing its last attempt is failed, it is not dispatched again.
+    sqlx::query("UPDATE outbox_events SET attempts = $1, next_attempt_at = NULL WHERE id = $2")
+        .bind(EVENT_MAX_ATTEMPTS - 1)
+        .bind(event_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let attempts: i32 = tokio::time::timeout(DISPATCH_TIMEOUT, async {
+        loop {
+            let failed: Option<i32> = sqlx::query_scalar(
+                "SELECT attempts FROM outbox_events WHERE id = $1 AND failed_at IS NOT NULL",
+            )
+            .bind(event_id)
+            .fetch_optional(pool)
+            .await
+            .unwrap();
+            if let Some(attempts) = failed {
+                return attempts;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Event failure timeout.");
+    assert_eq!(attempts, EVENT_MAX_ATTEMPTS);
+
+    // A later event is dispatched once the subscriber recovers, the failed event stays failed.
+    let failed_id = event_id;
+    let note = notes::add(notes::test_note(user.id, NOTE_TEXT), &access_token)
+        .await
+        .expect("Note creation error.");
+    let created = event_ids(pool, EventKind::NoteCreated, "id", &note.id.to_string()).await;
+    let event_id = created[0];
+    wait_failed_attempt(pool, event_id).await;
+
+    sqlx::query("ALTER TABLE webhook_deliveries DROP CONSTRAINT no_deliveries")
+        .execute(pool)
+        .await
+        .unwrap();
+    let attempts = wait_dispatched(pool, event_id).await;
+    assert!(attempts >= 1);
+    assert_eq!(consumers(pool, event_id).await, ["log", "webhooks"]);
+    let deliveries: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM webhook_deliveries WHERE event_id = $1")
+            .bind(event_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(deliveries, 1);
+    let failed: (bool, bool) = sqlx::query_as(
+        "SELECT dispatched_at IS NULL, failed_at IS NOT NULL FROM outbox_events WHERE id = $1",
+    )
+    .bind(failed_id)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(failed, (true, true));
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
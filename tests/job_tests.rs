@@ -0,0 +1,259 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        config::ConfigHandle,
+        security::encryption,
+        service::{cron_service::CronSchedule, job_service, notification_service::Notifiers},
+        state::{AppState, SharedState},
+    },
+    domain::models::{
+        job::{JobName, JobRun, JobRunStatus, JobTrigger},
+        note::Note,
+        reminder::{ReminderChannel, ReminderRequest},
+    },
+    infrastructure::{database::DatabasePool, mail::LogMailer, redis, token_store},
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, jobs, notes, reminders, test_app, users,
+};
+
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn build_state(db_pool: &DatabasePool) -> SharedState {
+    let config = helpers::config().clone();
+    let redis = redis::open(&config).await;
+    Arc::new(AppState {
+        token_store: token_store::open(&config, &redis, db_pool),
+        redis,
+        note_cipher: encryption::open(&config).expect("Failed to load the master key."),
+        config: ConfigHandle::new(config),
+        db_pool: db_pool.clone(),
+        notifiers: Notifiers::new(Box::new(LogMailer), db_pool.clone()),
+    })
+}
+
+fn at(date: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+}
+
+fn next_after(schedule: &str, after: &str) -> Option<NaiveDateTime> {
+    schedule
+        .parse::<CronSchedule>()
+        .unwrap()
+        .next_after(at(after))
+}
+
+// Waits for the run to finish.
+async fn finished(run: &JobRun, access_token: &str) -> JobRun {
+    tokio::time::timeout(RUN_TIMEOUT, async {
+        loop {
+            let runs = jobs::runs(run.job.as_str(), access_token).await.unwrap();
+            let current = runs.into_iter().find(|r| r.id == run.id).unwrap();
+            if current.status != JobRunStatus::Running {
+                return current;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Job run timeout.")
+}
+
+#[test]
+fn cron_schedule_test() {
+    assert_eq!(
+        next_after("*/15 * * * *", "2026-10-19 10:07"),
+        Some(at("2026-10-19 10:15"))
+    );
+    // The occurrences are strictly after the time.
+    assert_eq!(
+        next_after("30 3 * * *", "2026-10-19 03:30"),
+        Some(at("2026-10-20 03:30"))
+    );
+    // Ranges, lists and steps, on the last day of the year.
+    assert_eq!(
+        next_after("0 9-17/4 31 12 *", "2026-10-19 00:00"),
+        Some(at("2026-12-31 09:00"))
+    );
+    assert_eq!(
+        next_after("5,35 * * * *", "2026-10-19 10:35"),
+        Some(at("2026-10-19 11:05"))
+    );
+    // Sunday is 0 or 7, 2026-10-25 is a Sunday.
+    assert_eq!(
+        next_after("0 0 * * 7", "2026-10-19 10:00"),
+        Some(at("2026-10-25 00:00"))
+    );
+    // With both days restricted, either day matches.
+    assert_eq!(
+        next_after("0 0 1 * 0", "2026-10-19 10:00"),
+        Some(at("2026-10-25 00:00"))
+    );
+    // February 29 is in a leap year, February 30 never is.
+    assert_eq!(
+        next_after("0 0 29 2 *", "2026-10-19 10:00"),
+        NaiveDate::from_ymd_opt(2028, 2, 29).and_then(|d| d.and_hms_opt(0, 0, 0))
+    );
+    assert_eq!(next_after("0 0 30 2 *", "2026-10-19 10:00"), None);
+
+    for invalid in [
+        "* * * *",
+        "60 * * * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+    ] {
+        assert!(invalid.parse::<CronSchedule>().is_err(), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn job_runs_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    // The jobs are listed with their schedules, the scheduler is disabled by the tests.
+    let list = jobs::list(&tokens.access_token).await.unwrap();
+    assert_eq!(
+        list.iter().map(|job| job.name).collect::<Vec<_>>(),
+        JobName::ALL
+    );
+    assert_eq!(list[0].schedule, config.job_token_cleanup_schedule);
+    assert!(list.iter().all(|job| job.next_run_at.is_none()));
+
+    // A dismissed reminder older than the retention is purged.
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+    let note = Note {
+        id: Uuid::new_v4(),
+        user_id: user.id,
+        title: None,
+        text: "purged reminder".to_string(),
+        encryption: None,
+        created_at: None,
+        updated_at: None,
+    };
+    let note = notes::add(note, &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let request = ReminderRequest {
+        due_at: Utc::now().naive_utc() + TimeDelta::days(1),
+        rrule: None,
+        channel: ReminderChannel::Sse,
+        webhook_url: None,
+    };
+    let old = reminders::add(note.id, &request, &user_tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+    let recent = reminders::add(note.id, &request, &user_tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+    for reminder in [&old, &recent] {
+        reminders::dismiss(reminder.id, &user_tokens.access_token)
+            .await
+            .expect("Reminder dismiss error.");
+    }
+    sqlx::query("UPDATE reminders SET updated_at = $1 WHERE id = $2")
+        .bind(Utc::now().naive_utc() - TimeDelta::days(config.job_purge_retention_days + 1))
+        .bind(old.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+
+    // The admins run a job manually.
+    let run = jobs::run("purge", &tokens.access_token).await.unwrap();
+    assert_eq!(run.job, JobName::Purge);
+    assert_eq!(run.trigger, JobTrigger::Manual);
+    let run = finished(&run, &tokens.access_token).await;
+    assert_eq!(run.status, JobRunStatus::Succeeded);
+    assert!(run.processed.unwrap() >= 1);
+    assert!(run.finished_at.is_some());
+    let result = reminders::get(old.id, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+    reminders::get(recent.id, &user_tokens.access_token)
+        .await
+        .expect("Reminder purged before the retention.");
+
+    // The last run is listed with the job.
+    let list = jobs::list(&tokens.access_token).await.unwrap();
+    let purge = list.iter().find(|job| job.name == JobName::Purge).unwrap();
+    assert_eq!(purge.last_run.as_ref(), Some(&run));
+
+    // A running job is not run again, on this or another instance.
+    let state = build_state(test_db.pool()).await;
+    let (running, lock) =
+        job_service::claim(JobName::StatsRollup, JobTrigger::Manual, None, &state)
+            .await
+            .unwrap()
+            .unwrap();
+    let result = jobs::run("stats_rollup", &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::CONFLICT);
+    let run = job_service::execute(running, lock, &state).await;
+    assert_eq!(run.status, JobRunStatus::Succeeded);
+    let run = jobs::run("stats_rollup", &tokens.access_token)
+        .await
+        .unwrap();
+    finished(&run, &tokens.access_token).await;
+
+    // A scheduled occurrence runs once across the instances.
+    let scheduled_at = Some(Utc::now().naive_utc());
+    let (running, lock) = job_service::claim(
+        JobName::TokenCleanup,
+        JobTrigger::Scheduled,
+        scheduled_at,
+        &state,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let run = job_service::execute(running, lock, &state).await;
+    assert_eq!(run.status, JobRunStatus::Succeeded);
+    let claimed = job_service::claim(
+        JobName::TokenCleanup,
+        JobTrigger::Scheduled,
+        scheduled_at,
+        &state,
+    )
+    .await
+    .unwrap();
+    assert!(claimed.is_none());
+
+    // The runs are listed, the most recent first.
+    let runs = jobs::runs("stats_rollup", &tokens.access_token)
+        .await
+        .unwrap();
+    assert!(runs.len() >= 2);
+    assert!(runs[0].started_at >= runs[1].started_at);
+
+    // Unknown jobs are not found, the jobs are for the admins only.
+    let result = jobs::run("unknown", &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+    let result = jobs::list(&user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = jobs::run("purge", &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
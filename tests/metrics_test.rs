@@ -0,0 +1,68 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+
+pub mod common;
+use common::{
+    auth,
+    constants::{API_PATH_HEALTH, API_V1, TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, root, test_app,
+};
+
+#[tokio::test]
+#[serial]
+async fn metrics_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+    let url = format!("{}/metrics", config.service_http_addr());
+
+    // The metrics require the token.
+    let response = reqwest::get(&url).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("Authorization", "Bearer wrong-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Generate some traffic.
+    let health_url = helpers::build_path(API_V1, API_PATH_HEALTH);
+    reqwest::get(health_url.as_str()).await.unwrap();
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+    // The access token is checked against the revoked tokens in Redis.
+    let status = root::fetch_root(&tokens.access_token).await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    let result = auth::login(TEST_ADMIN_USERNAME, "wrong-password").await;
+    assert!(result.is_err());
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", config.metrics_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.text().await.unwrap();
+
+    // Requests are labeled with the matched route, not the raw path.
+    assert!(
+        body.contains(
+            r#"http_requests_total{method="GET",route="/{version}/health",status="200"}"#
+        )
+    );
+    assert!(body.contains("# TYPE http_request_duration_seconds histogram"));
+    assert!(body.contains(r#"auth_logins_total{result="success"}"#));
+    assert!(body.contains(r#"auth_logins_total{result="failure"}"#));
+    assert!(body.contains(r#"auth_tokens_issued_total{type="access"}"#));
+    assert!(body.contains(r#"db_pool_connections{state="idle"}"#));
+    assert!(body.contains("db_pool_max_connections"));
+    assert!(body.contains(r#"redis_command_duration_seconds_bucket{command="#));
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
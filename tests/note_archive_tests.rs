@@ -0,0 +1,244 @@
+use std::{io::Read, time::Duration};
+
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        constants::NOTE_IMPORT_BACKGROUND_THRESHOLD,
+        security::jwt::{self, AccessClaims},
+    },
+    domain::models::{
+        note::Note,
+        note_import::{NoteArchiveFormat, NoteImportItemStatus, NoteImportStatus},
+    },
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app, users,
+};
+
+#[tokio::test]
+#[serial]
+async fn export_import_notes_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let first = notes::add(
+        notes::test_note(user_id, "# First\n\nthe first note"),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+    let second = notes::add(
+        notes::test_note(user_id, "the second note\n"),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+
+    // Export as NDJSON.
+    let ndjson = notes::export("ndjson", &tokens.access_token)
+        .await
+        .expect("Notes export error.");
+    let exported: Vec<Note> = String::from_utf8(ndjson.clone())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(exported, vec![first.clone(), second.clone()]);
+
+    // Export as a tar archive of Markdown files with front-matter.
+    let tar = notes::export("tar", &tokens.access_token)
+        .await
+        .expect("Notes export error.");
+    let mut archive = tar::Archive::new(&tar[..]);
+    let mut files = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().to_string();
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        files.push((path, content));
+    }
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].0, format!("{}.md", first.id));
+    assert!(
+        files[0]
+            .1
+            .starts_with(&format!("---\nid: \"{}\"\ntitle: \"First\"\n", first.id))
+    );
+    assert!(files[1].1.ends_with("---\n\nthe second note\n"));
+
+    // Importing the export back finds duplicates by ID.
+    let (status, note_import) = notes::import(
+        NoteArchiveFormat::Ndjson,
+        ndjson.clone(),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Notes import error.");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(note_import.status, NoteImportStatus::Completed);
+    assert_eq!(note_import.total, 2);
+    assert!(
+        note_import
+            .results
+            .iter()
+            .all(|r| r.status == NoteImportItemStatus::Duplicate)
+    );
+
+    // The notes of another user are not duplicates, they are imported with new IDs.
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+    let (_, note_import) =
+        notes::import(NoteArchiveFormat::Ndjson, ndjson, &user_tokens.access_token)
+            .await
+            .expect("Notes import error.");
+    for (item, note) in note_import.results.iter().zip(&exported) {
+        assert_eq!(item.status, NoteImportItemStatus::Created);
+        assert_ne!(item.id, Some(note.id));
+    }
+    let original = notes::get(first.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(original, first);
+
+    // Duplicates by content, new notes and invalid items are reported per item.
+    let ndjson = format!(
+        "{}\n{}\n{}\n",
+        serde_json::json!({"text": "the second note\n"}),
+        serde_json::json!({"text": "# Third\n\nthe third note"}),
+        "{not json}",
+    );
+    let (_, note_import) = notes::import(
+        NoteArchiveFormat::Ndjson,
+        ndjson.into_bytes(),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Notes import error.");
+    let statuses: Vec<NoteImportItemStatus> =
+        note_import.results.iter().map(|r| r.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            NoteImportItemStatus::Duplicate,
+            NoteImportItemStatus::Created,
+            NoteImportItemStatus::Failed,
+        ]
+    );
+    assert_eq!(note_import.results[0].id, Some(second.id));
+    assert!(note_import.results[2].reason.is_some());
+
+    // Invalid end-to-end encrypted notes are not imported.
+    let encryption =
+        serde_json::json!({"algorithm": "XChaCha20-Poly1305", "nonce": "AAECAwQFBgcICQoL"});
+    let ndjson = format!(
+        "{}\n{}\n",
+        serde_json::json!({"title": "Plaintext", "text": "c2VjcmV0", "encryption": encryption}),
+        serde_json::json!({"text": "not base64!", "encryption": encryption}),
+    );
+    let (_, note_import) = notes::import(
+        NoteArchiveFormat::Ndjson,
+        ndjson.into_bytes(),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Notes import error.");
+    for item in note_import.results.iter() {
+        assert_eq!(item.status, NoteImportItemStatus::Failed);
+        assert!(item.reason.is_some());
+    }
+    assert_eq!(note_import.results.len(), 2);
+
+    // Import a Markdown file with front-matter from a tar archive.
+    let imported_id = Uuid::new_v4();
+    let markdown = format!(
+        "---\nid: {}\ntitle: \"Imported\"\ncreated_at: \"2025-01-02T03:04:05\"\n---\n\nimported text\n",
+        imported_id
+    );
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(markdown.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "imported.md", markdown.as_bytes())
+        .unwrap();
+    let (_, note_import) = notes::import(
+        NoteArchiveFormat::Tar,
+        builder.into_inner().unwrap(),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Notes import error.");
+    assert_eq!(note_import.results[0].status, NoteImportItemStatus::Created);
+
+    let imported = notes::get(imported_id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(imported.title.as_deref(), Some("Imported"));
+    assert_eq!(imported.text, "imported text\n");
+    assert_eq!(
+        imported.created_at.unwrap().to_string(),
+        "2025-01-02 03:04:05"
+    );
+
+    // Large imports run in the background.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+    let ndjson: String = (0..=NOTE_IMPORT_BACKGROUND_THRESHOLD)
+        .map(|i| {
+            format!(
+                "{}\n",
+                serde_json::json!({"text": format!("bulk note {}", i)})
+            )
+        })
+        .collect();
+    let (status, note_import) = notes::import(
+        NoteArchiveFormat::Ndjson,
+        ndjson.into_bytes(),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Notes import error.");
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let mut note_import = note_import;
+    for _ in 0..100 {
+        if note_import.status == NoteImportStatus::Completed {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        note_import = notes::get_import(note_import.id, &tokens.access_token)
+            .await
+            .expect("Note import fetch error.");
+    }
+    assert_eq!(note_import.status, NoteImportStatus::Completed);
+    assert_eq!(
+        note_import.results.len(),
+        NOTE_IMPORT_BACKGROUND_THRESHOLD + 1
+    );
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,219 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::security::jwt::{self, AccessClaims},
+    domain::models::note_comment::NoteCommentRequest,
+};
+
+pub mod common;
+use common::{
+    auth, comments,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app, users,
+};
+
+const NOTE_TEXT: &str = "# Design review\n\nThe draft.";
+
+fn comment(text: &str, parent_id: Option<Uuid>) -> NoteCommentRequest {
+    NoteCommentRequest {
+        text: text.to_string(),
+        parent_id,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn note_comment_threads_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let admin_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let reviewer = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let note = notes::add(notes::test_note(admin_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // Mentions resolve to user IDs, unknown users and emails are not mentions.
+    let text = format!(
+        "@{} please have a look, cc @nobody and mail admin@example.com",
+        reviewer.username
+    );
+    let root = comments::add(note.id, &comment(&text, None), &tokens.access_token)
+        .await
+        .expect("Comment creation error.");
+    assert_eq!(root.thread_id, root.id);
+    assert_eq!(root.mentions, vec![reviewer.id]);
+
+    // Replies to replies stay in the thread of the root comment.
+    let reply = comments::add(
+        note.id,
+        &comment("Looks good.", Some(root.id)),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Comment creation error.");
+    let nested = comments::add(
+        note.id,
+        &comment("Thanks @admin.", Some(reply.id)),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Comment creation error.");
+    assert_eq!(nested.thread_id, root.id);
+    assert_eq!(nested.parent_id, Some(reply.id));
+    assert_eq!(nested.mentions, vec![admin_id]);
+
+    // A parent must be a comment of the same note.
+    let result = comments::add(
+        note.id,
+        &comment("Lost.", Some(Uuid::new_v4())),
+        &tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Edits update the text and the mentions.
+    let edited = comments::update(
+        note.id,
+        reply.id,
+        &comment("Looks good to me.", None),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Comment update error.");
+    assert_eq!(edited.text, "Looks good to me.");
+    assert!(edited.edited_at.is_some());
+
+    // Threads are paginated with their replies.
+    let second = comments::add(
+        note.id,
+        &comment("Typo in the title.", None),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Comment creation error.");
+    let page = comments::list(note.id, "?per_page=1", &tokens.access_token)
+        .await
+        .expect("Comments fetch error.");
+    assert_eq!(page.total, 2);
+    assert_eq!(page.threads.len(), 1);
+    assert_eq!(page.threads[0].comment.id, root.id);
+    let reply_ids: Vec<Uuid> = page.threads[0].replies.iter().map(|c| c.id).collect();
+    assert_eq!(reply_ids, vec![reply.id, nested.id]);
+
+    let page = comments::list(note.id, "?page=2&per_page=1", &tokens.access_token)
+        .await
+        .expect("Comments fetch error.");
+    assert_eq!(page.threads[0].comment.id, second.id);
+    assert!(page.threads[0].replies.is_empty());
+
+    // Resolve and reopen a thread, replies cannot be resolved.
+    let resolved = comments::resolve(note.id, root.id, true, &tokens.access_token)
+        .await
+        .expect("Comment resolve error.");
+    assert_eq!(resolved.resolved_by, Some(admin_id));
+    let result = comments::resolve(note.id, reply.id, true, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let page = comments::list(note.id, "?resolved=false", &tokens.access_token)
+        .await
+        .expect("Comments fetch error.");
+    assert_eq!(page.total, 1);
+    assert_eq!(page.threads[0].comment.id, second.id);
+
+    let reopened = comments::resolve(note.id, root.id, false, &tokens.access_token)
+        .await
+        .expect("Comment unresolve error.");
+    assert!(reopened.resolved_at.is_none());
+
+    // Deleting a comment deletes its replies.
+    comments::delete(note.id, root.id, &tokens.access_token)
+        .await
+        .expect("Comment deletion error.");
+    let result = comments::get(note.id, nested.id, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn note_comment_access_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let owner = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let owner_tokens = auth::login(&owner.username, &owner.password_hash)
+        .await
+        .expect("Login error.");
+    let other = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let other_tokens = auth::login(&other.username, &other.password_hash)
+        .await
+        .expect("Login error.");
+
+    let note = notes::add(notes::test_note(owner.id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // Comments follow the visibility of the note.
+    let result = comments::add(note.id, &comment("Hi.", None), &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = comments::list(note.id, "", &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Only the author edits a comment, the note owner can delete it.
+    let review = comments::add(
+        note.id,
+        &comment("Please add numbers.", None),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Comment creation error.");
+    let result = comments::update(
+        note.id,
+        review.id,
+        &comment("Edited.", None),
+        &owner_tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    let page = comments::list(note.id, "", &owner_tokens.access_token)
+        .await
+        .expect("Comments fetch error.");
+    assert_eq!(page.total, 1);
+
+    comments::delete(note.id, review.id, &owner_tokens.access_token)
+        .await
+        .expect("Comment deletion error.");
+
+    // Empty comments are rejected.
+    let result = comments::add(note.id, &comment("  ", None), &owner_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
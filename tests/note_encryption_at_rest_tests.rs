@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        config::ConfigHandle,
+        constants::NOTE_TEXT_ENCRYPTED_PREFIX,
+        repository::note_repo,
+        security::{
+            encryption::NoteCipher,
+            jwt::{self, AccessClaims},
+        },
+        service::{envelope_service, notification_service::Notifiers},
+        state::AppState,
+    },
+    infrastructure::{kms::LocalKms, mail::LogMailer, redis, token_store::MemoryTokenStore},
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app,
+};
+
+const NOTE_TEXT: &str = "# Incident\n\nThe database password is hunter2.";
+const ROTATED_MASTER_KEY_ID: &str = "test-2";
+
+#[tokio::test]
+#[serial]
+async fn note_encryption_at_rest_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    // Add a note, the API works with the plaintext.
+    let note = notes::add(notes::test_note(user_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    assert_eq!(note.text, NOTE_TEXT);
+    assert_eq!(note.title.as_deref(), Some("Incident"));
+    let note_result = notes::get(note.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(note_result, note);
+
+    // The text is stored encrypted, with its keyed digest.
+    let (text, text_digest, encrypted_at_rest): (String, Option<String>, bool) =
+        sqlx::query_as("SELECT text, text_digest, encrypted_at_rest FROM notes WHERE id = $1")
+            .bind(note.id)
+            .fetch_one(test_db.pool())
+            .await
+            .unwrap();
+    assert!(text.starts_with(NOTE_TEXT_ENCRYPTED_PREFIX));
+    assert!(!text.contains("hunter2"));
+    assert!(text_digest.is_some());
+    assert!(encrypted_at_rest);
+
+    // So is the title, and the target of a link to the note.
+    let title: String = sqlx::query_scalar("SELECT title FROM notes WHERE id = $1")
+        .bind(note.id)
+        .fetch_one(test_db.pool())
+        .await
+        .unwrap();
+    assert!(!title.contains("Incident"));
+    let linking = notes::add(
+        notes::test_note(user_id, "See [[incident]]."),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+    let (target_ref, target_id): (String, Option<Uuid>) =
+        sqlx::query_as("SELECT target_ref, target_id FROM note_links WHERE source_id = $1")
+            .bind(linking.id)
+            .fetch_one(test_db.pool())
+            .await
+            .unwrap();
+    assert!(!target_ref.contains("incident"));
+    assert_eq!(target_id, Some(note.id));
+    let links = notes::links(linking.id, &tokens.access_token)
+        .await
+        .expect("Note links error.");
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].target_ref, "incident");
+
+    // A text starting like a ciphertext is encrypted as any other text.
+    let lookalike_text = format!("{}not a ciphertext", NOTE_TEXT_ENCRYPTED_PREFIX);
+    let lookalike = notes::add(
+        notes::test_note(user_id, &lookalike_text),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+    let lookalike_result = notes::get(lookalike.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(lookalike_result.text, lookalike_text);
+    let text: String = sqlx::query_scalar("SELECT text FROM notes WHERE id = $1")
+        .bind(lookalike.id)
+        .fetch_one(test_db.pool())
+        .await
+        .unwrap();
+    assert!(!text.contains("not a ciphertext"));
+
+    // The data key of the tenant is wrapped by the configured master key.
+    let master_key_id: String =
+        sqlx::query_scalar("SELECT master_key_id FROM tenant_keys WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(test_db.pool())
+            .await
+            .unwrap();
+    assert_eq!(master_key_id, config.master_key_id);
+
+    // Add a note written before the encryption was enabled.
+    let legacy_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO notes (id, user_id, title, text, created_at, updated_at) VALUES ($1, $2, $3, $4, now(), now())",
+    )
+    .bind(legacy_id)
+    .bind(user_id)
+    .bind("Legacy")
+    .bind("legacy plaintext note")
+    .execute(test_db.pool())
+    .await
+    .unwrap();
+
+    // Rotate the master key and run the re-encryption job.
+    let kms = LocalKms::from_file(&config.master_key_file, Some(ROTATED_MASTER_KEY_ID))
+        .expect("Master key file error.");
+    let state = Arc::new(AppState {
+        config: ConfigHandle::new(config.clone()),
+        db_pool: test_db.pool().clone(),
+        redis: redis::open(config).await,
+        token_store: Box::new(MemoryTokenStore::new()),
+        note_cipher: Some(NoteCipher::new(Box::new(kms))),
+        notifiers: Notifiers::new(Box::new(LogMailer), test_db.pool().clone()),
+    });
+    envelope_service::reencrypt(&state)
+        .await
+        .expect("Re-encryption error.");
+
+    let master_key_id: String =
+        sqlx::query_scalar("SELECT master_key_id FROM tenant_keys WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(test_db.pool())
+            .await
+            .unwrap();
+    assert_eq!(master_key_id, ROTATED_MASTER_KEY_ID);
+
+    let (legacy_text, legacy_title, encrypted_at_rest): (String, String, bool) =
+        sqlx::query_as("SELECT text, title, encrypted_at_rest FROM notes WHERE id = $1")
+            .bind(legacy_id)
+            .fetch_one(test_db.pool())
+            .await
+            .unwrap();
+    assert!(legacy_text.starts_with(NOTE_TEXT_ENCRYPTED_PREFIX));
+    assert_ne!(legacy_title, "Legacy");
+    assert!(encrypted_at_rest);
+
+    // Both notes are readable with the data key re-wrapped by the new master key.
+    let note_result = note_repo::get_by_id(note.id, &state).await.unwrap();
+    assert_eq!(note_result.text, NOTE_TEXT);
+    let legacy_note = note_repo::get_by_id(legacy_id, &state).await.unwrap();
+    assert_eq!(legacy_note.text, "legacy plaintext note");
+    assert_eq!(legacy_note.title.as_deref(), Some("Legacy"));
+    let legacy_title = note_repo::get_title(legacy_id, &state).await.unwrap();
+    assert_eq!(legacy_title.as_deref(), Some("Legacy"));
+
+    // Duplicates are found by the keyed digest.
+    let duplicate_id = note_repo::find_by_content(user_id, NOTE_TEXT, &state)
+        .await
+        .unwrap();
+    assert_eq!(duplicate_id, Some(note.id));
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,213 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use axum_web::{
+    application::security::jwt::{self, AccessClaims},
+    domain::models::{
+        note::{Note, NoteEncryption},
+        note_key::{NoteKeyRequest, UserKeyRequest},
+    },
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app, users,
+};
+
+const CIPHERTEXT: &str = "3q2+7wAAAAAAAAAAAAAAAA==";
+const NONCE: &str = "AAECAwQFBgcICQoL";
+const ALGORITHM: &str = "XChaCha20-Poly1305";
+const KEY_ALGORITHM: &str = "X25519";
+
+fn encrypted_note(user_id: Uuid, text: &str) -> Note {
+    Note {
+        id: Uuid::new_v4(),
+        user_id,
+        title: None,
+        text: text.to_string(),
+        encryption: Some(Json(NoteEncryption {
+            algorithm: ALGORITHM.to_string(),
+            nonce: NONCE.to_string(),
+        })),
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn user_key() -> UserKeyRequest {
+    UserKeyRequest {
+        public_key: "cHVibGljLWtleQ==".to_string(),
+        algorithm: KEY_ALGORITHM.to_string(),
+    }
+}
+
+fn note_key(wrapped_key: &str) -> NoteKeyRequest {
+    NoteKeyRequest {
+        wrapped_key: wrapped_key.to_string(),
+        algorithm: KEY_ALGORITHM.to_string(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn encrypted_note_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    // The text of an encrypted note must be ciphertext.
+    let result = notes::add(encrypted_note(user_id, "# plaintext"), &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Add an encrypted note, the server stores the ciphertext as is.
+    let note = notes::add(encrypted_note(user_id, CIPHERTEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    assert!(note.is_encrypted());
+    assert_eq!(note.title, None);
+    assert_eq!(note.text, CIPHERTEXT);
+
+    let note_result = notes::get(note.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(note_result, note);
+
+    // The server refuses to render an encrypted note.
+    let result = notes::get_rendered(note.id, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn share_encrypted_note_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    // Add a recipient and login as the recipient.
+    let recipient = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let recipient_tokens = auth::login(&recipient.username, &recipient.password_hash)
+        .await
+        .expect("Login error.");
+
+    // Add an encrypted note with the data key wrapped for the owner.
+    users::put_key(user_id, user_key(), &tokens.access_token)
+        .await
+        .expect("User key registration error.");
+    let note = notes::add(encrypted_note(user_id, CIPHERTEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    notes::put_key(note.id, user_id, note_key("b3duZXI="), &tokens.access_token)
+        .await
+        .expect("Note key creation error.");
+
+    // The recipient cannot read the note before it is shared.
+    let result = notes::get(note.id, &recipient_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // The key can only be wrapped for users with a registered public key.
+    let result = notes::put_key(
+        note.id,
+        recipient.id,
+        note_key("cmVjaXBpZW50"),
+        &tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Share the note by re-wrapping the data key for the recipient.
+    let recipient_key = users::put_key(recipient.id, user_key(), &recipient_tokens.access_token)
+        .await
+        .expect("User key registration error.");
+    let public_key = users::get_key(recipient.id, &tokens.access_token)
+        .await
+        .expect("User key fetch error.");
+    assert_eq!(public_key, recipient_key);
+
+    let shared_key = notes::put_key(
+        note.id,
+        recipient.id,
+        note_key("cmVjaXBpZW50"),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note key creation error.");
+    assert_eq!(shared_key.wrapped_by, user_id);
+
+    // The recipient can read the ciphertext and fetch the wrapped key.
+    let note_result = notes::get(note.id, &recipient_tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(note_result.text, CIPHERTEXT);
+    let note_key_result = notes::get_key(note.id, recipient.id, &recipient_tokens.access_token)
+        .await
+        .expect("Note key fetch error.");
+    assert_eq!(note_key_result, shared_key);
+
+    // Only the owner can update the note, the ID of the body must be the ID of the path.
+    let result = notes::update(note_result.clone(), &recipient_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let other_note = encrypted_note(recipient.id, CIPHERTEXT);
+    let result = notes::update_at(note.id, other_note, &recipient_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::BAD_REQUEST);
+
+    // Only the owner can list all the keys of the note.
+    let note_keys = notes::list_keys(note.id, &tokens.access_token)
+        .await
+        .expect("Note keys fetch error.");
+    assert_eq!(note_keys.len(), 2);
+    let result = notes::list_keys(note.id, &recipient_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Revoke the share.
+    notes::delete_key(note.id, recipient.id, &tokens.access_token)
+        .await
+        .expect("Note key deletion error.");
+    let result = notes::get(note.id, &recipient_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Plaintext notes cannot be shared by key.
+    let mut plain_note = encrypted_note(user_id, "plain text");
+    plain_note.encryption = None;
+    let plain_note = notes::add(plain_note, &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let result = notes::put_key(
+        plain_note.id,
+        recipient.id,
+        note_key("cmVjaXBpZW50"),
+        &tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
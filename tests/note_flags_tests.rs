@@ -0,0 +1,140 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+
+use axum_web::domain::models::note_flags::{NoteFlagsRequest, ViewedNote};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    notes, test_app, users,
+};
+
+fn titles(notes: &[ViewedNote]) -> Vec<&str> {
+    notes
+        .iter()
+        .map(|n| n.note.title.as_deref().unwrap_or_default())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn note_flags_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    // Newer notes are listed first.
+    let mut added = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let note = notes::add(
+            notes::test_note(user.id, &format!("# {}", title)),
+            &tokens.access_token,
+        )
+        .await
+        .expect("Note creation error.");
+        added.push(note);
+    }
+    let (first, second, third) = (&added[0], &added[1], &added[2]);
+
+    let listed = notes::list_by_user(user.id, "", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["Third", "Second", "First"]);
+
+    // Pinned notes come first, archived notes are hidden.
+    let pin = NoteFlagsRequest {
+        pinned: Some(true),
+        ..NoteFlagsRequest::default()
+    };
+    let flags = notes::put_flags(first.id, &pin, &user_tokens.access_token)
+        .await
+        .expect("Note flags update error.");
+    assert!(flags.pinned && !flags.archived && !flags.favorite);
+
+    let archive = NoteFlagsRequest {
+        archived: Some(true),
+        favorite: Some(true),
+        ..NoteFlagsRequest::default()
+    };
+    notes::put_flags(second.id, &archive, &user_tokens.access_token)
+        .await
+        .expect("Note flags update error.");
+
+    let listed = notes::list_by_user(user.id, "", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["First", "Third"]);
+    assert!(listed[0].pinned);
+
+    // Filter by the flags.
+    let listed = notes::list_by_user(user.id, "?archived=true", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["Second"]);
+    assert!(listed[0].favorite);
+
+    let listed = notes::list_by_user(user.id, "?pinned=false", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["Third"]);
+
+    // Changing a flag keeps the others.
+    let unarchive = NoteFlagsRequest {
+        archived: Some(false),
+        ..NoteFlagsRequest::default()
+    };
+    let flags = notes::put_flags(second.id, &unarchive, &user_tokens.access_token)
+        .await
+        .expect("Note flags update error.");
+    assert!(!flags.archived && flags.favorite);
+
+    let listed = notes::list_by_user(user.id, "?favorite=true", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["Second"]);
+
+    // The flags are per viewer.
+    let flags = notes::get_flags(first.id, &tokens.access_token)
+        .await
+        .expect("Note flags fetch error.");
+    assert!(!flags.pinned);
+    notes::put_flags(third.id, &pin, &tokens.access_token)
+        .await
+        .expect("Note flags update error.");
+
+    let listed = notes::list_by_user(user.id, "", &tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["Third", "Second", "First"]);
+    let listed = notes::list_by_user(user.id, "?pinned=true", &user_tokens.access_token)
+        .await
+        .expect("Notes fetch error.");
+    assert_eq!(titles(&listed), vec!["First"]);
+
+    // Users list their own notes only.
+    let other = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let other_tokens = auth::login(&other.username, &other.password_hash)
+        .await
+        .expect("Login error.");
+    let result = notes::list_by_user(user.id, "", &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = notes::put_flags(first.id, &pin, &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
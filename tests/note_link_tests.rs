@@ -0,0 +1,158 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::application::security::jwt::{self, AccessClaims};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app, users,
+};
+
+#[tokio::test]
+#[serial]
+async fn note_links_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    // Add a note linking to notes which do not exist yet.
+    let gamma_id = Uuid::new_v4();
+    let alpha_text = format!(
+        "# Alpha\n\nSee [[Beta]] and [[note:{}|the third note]].\n\n`[[Ignored]]`\n",
+        gamma_id
+    );
+    let alpha = notes::add(notes::test_note(user_id, &alpha_text), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    assert_eq!(alpha.title.as_deref(), Some("Alpha"));
+
+    let links = notes::links(alpha.id, &tokens.access_token)
+        .await
+        .expect("Note links fetch error.");
+    assert_eq!(links.len(), 2);
+    assert!(links.iter().all(|l| l.target_id.is_none()));
+
+    // Add the linked notes, dangling links get resolved.
+    let beta = notes::add(
+        notes::test_note(user_id, "# Beta\n\nBack to [[alpha]]."),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+
+    let mut gamma = notes::test_note(user_id, "No heading here.");
+    gamma.id = gamma_id;
+    gamma.title = Some("Gamma".to_string());
+    let gamma = notes::add(gamma, &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    assert_eq!(gamma.title.as_deref(), Some("Gamma"));
+
+    let links = notes::links(alpha.id, &tokens.access_token)
+        .await
+        .expect("Note links fetch error.");
+    let targets: Vec<Option<Uuid>> = links.iter().map(|l| l.target_id).collect();
+    assert_eq!(targets, vec![Some(beta.id), Some(gamma.id)]);
+
+    let backlinks = notes::backlinks(alpha.id, &tokens.access_token)
+        .await
+        .expect("Note backlinks fetch error.");
+    assert_eq!(backlinks.len(), 1);
+    assert_eq!(backlinks[0].id, beta.id);
+
+    // Rename the linked note and save the linking note again, the links stay intact.
+    let mut beta_renamed = beta.clone();
+    beta_renamed.title = None;
+    beta_renamed.text = "# Beta renamed\n\nBack to [[alpha]].".to_string();
+    let beta_renamed = notes::update(beta_renamed, &tokens.access_token)
+        .await
+        .expect("Note update error.");
+    assert_eq!(beta_renamed.title.as_deref(), Some("Beta renamed"));
+
+    notes::update(alpha.clone(), &tokens.access_token)
+        .await
+        .expect("Note update error.");
+
+    let backlinks = notes::backlinks(beta.id, &tokens.access_token)
+        .await
+        .expect("Note backlinks fetch error.");
+    assert_eq!(backlinks.len(), 1);
+    assert_eq!(backlinks[0].id, alpha.id);
+
+    // Export the graph.
+    let graph = notes::graph(user_id, &tokens.access_token)
+        .await
+        .expect("Note graph fetch error.");
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.edges.len(), 3);
+    assert!(
+        graph
+            .edges
+            .iter()
+            .any(|e| e.source_id == beta.id && e.target_id == alpha.id)
+    );
+
+    // Links by ID are resolved within the notes of the same user only.
+    let other = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let delta_id = Uuid::new_v4();
+    let epsilon = notes::add(
+        notes::test_note(user_id, &format!("# Epsilon\n\nSee [[note:{}]].", delta_id)),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+    let mut delta = notes::test_note(other.id, &format!("# Delta\n\nSee [[note:{}]].", alpha.id));
+    delta.id = delta_id;
+    notes::add(delta, &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    for source_id in [epsilon.id, delta_id] {
+        let links = notes::links(source_id, &tokens.access_token)
+            .await
+            .expect("Note links fetch error.");
+        assert_eq!(links.len(), 1);
+        assert!(links[0].target_id.is_none());
+    }
+
+    // Users read the links of their own notes only.
+    let other_tokens = auth::login(&other.username, &other.password_hash)
+        .await
+        .expect("Login error.");
+    let links = notes::links(delta_id, &other_tokens.access_token)
+        .await
+        .expect("Note links fetch error.");
+    assert_eq!(links.len(), 1);
+    let backlinks = notes::backlinks(delta_id, &other_tokens.access_token)
+        .await
+        .expect("Note backlinks fetch error.");
+    assert!(backlinks.is_empty());
+    let graph = notes::graph(other.id, &other_tokens.access_token)
+        .await
+        .expect("Note graph fetch error.");
+    assert_eq!(graph.nodes.len(), 1);
+
+    let result = notes::links(alpha.id, &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = notes::backlinks(alpha.id, &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = notes::graph(user_id, &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,97 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::security::jwt::{self, AccessClaims},
+    domain::models::note::Note,
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, test_app,
+};
+
+const MARKDOWN_TEXT: &str = r#"# Incident *review*
+
+Some **bold** text with ~~strikethrough~~ and a [link](https://example.com).
+
+- [x] done
+- [ ] todo
+
+| a | b |
+|---|---|
+| 1 | 2 |
+
+<script>alert("xss")</script>
+<a href="javascript:alert(1)" onclick="alert(2)">click</a>
+"#;
+
+#[tokio::test]
+#[serial]
+async fn render_note_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    // Add a note.
+    let note = Note {
+        id: Uuid::new_v4(),
+        user_id,
+        title: None,
+        text: MARKDOWN_TEXT.to_string(),
+        encryption: None,
+        created_at: None,
+        updated_at: None,
+    };
+    let note = notes::add(note, &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // The default representation is the raw note.
+    let note_result = notes::get(note.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(note_result, note);
+
+    // Fetch the rendered note with metadata.
+    let rendered = notes::get_rendered(note.id, &tokens.access_token)
+        .await
+        .expect("Rendered note fetch error.");
+    assert_eq!(rendered.id, note.id);
+    assert_eq!(rendered.version, note.version());
+    assert_eq!(rendered.title.as_deref(), Some("Incident review"));
+    assert!(rendered.word_count > 10);
+    assert!(rendered.html.contains("<h1>Incident <em>review</em></h1>"));
+    assert!(rendered.html.contains("<del>strikethrough</del>"));
+    assert!(rendered.html.contains("<table>"));
+    assert!(rendered.html.contains("type=\"checkbox\""));
+    assert!(!rendered.html.contains("<script"));
+    assert!(!rendered.html.contains("javascript:"));
+    assert!(!rendered.html.contains("onclick"));
+
+    // The cached rendering is served for the same version.
+    let cached = notes::get_rendered(note.id, &tokens.access_token)
+        .await
+        .expect("Rendered note fetch error.");
+    assert_eq!(cached, rendered);
+
+    // Content negotiation on `Accept: text/html`.
+    let html = notes::get_html(note.id, &tokens.access_token)
+        .await
+        .expect("HTML note fetch error.");
+    assert_eq!(html, rendered.html);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        config::ConfigHandle,
+        security::encryption,
+        service::{notification_service::Notifiers, stats_service},
+        state::AppState,
+    },
+    infrastructure::{mail::LogMailer, redis, token_store::MemoryTokenStore},
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, stats, test_app, users,
+};
+
+#[tokio::test]
+#[serial]
+async fn note_stats_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    // The stats follow the note changes.
+    let first = notes::add(notes::test_note(user.id, "hello"), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let second = notes::add(notes::test_note(user.id, "über"), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let user_stats = stats::get(user.id, &user_tokens.access_token)
+        .await
+        .expect("Stats fetch error.");
+    assert_eq!(user_stats.notes, 2);
+    assert_eq!(user_stats.notes_created, 2);
+    assert_eq!(user_stats.total_chars, 9);
+    assert!(user_stats.last_activity_at.is_some());
+
+    let mut updated = first.clone();
+    updated.text = "hello world".to_string();
+    notes::update(updated, &tokens.access_token)
+        .await
+        .expect("Note update error.");
+    notes::delete(second.id, &tokens.access_token)
+        .await
+        .expect("Note deletion error.");
+    let user_stats = stats::get(user.id, &user_tokens.access_token)
+        .await
+        .expect("Stats fetch error.");
+    assert_eq!(user_stats.notes, 1);
+    assert_eq!(user_stats.notes_created, 2);
+    assert_eq!(user_stats.notes_edited, 1);
+    assert_eq!(user_stats.notes_deleted, 1);
+    assert_eq!(user_stats.total_chars, 11);
+
+    // A note given to another user moves to the stats of the new owner.
+    let other = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let mut moved = notes::get(first.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    moved.user_id = other.id;
+    notes::update(moved, &tokens.access_token)
+        .await
+        .expect("Note update error.");
+    let user_stats = stats::get(user.id, &tokens.access_token)
+        .await
+        .expect("Stats fetch error.");
+    assert_eq!((user_stats.notes, user_stats.total_chars), (0, 0));
+    let other_stats = stats::get(other.id, &tokens.access_token)
+        .await
+        .expect("Stats fetch error.");
+    assert_eq!((other_stats.notes, other_stats.total_chars), (1, 11));
+
+    // Users cannot read the stats of other users.
+    let result = stats::get(other.id, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Unknown users have no stats.
+    let result = stats::get(Uuid::new_v4(), &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn note_stats_rebuild_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    notes::add(
+        notes::test_note(user.id, "first note"),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Note creation error.");
+    let note = notes::add(notes::test_note(user.id, "second"), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    notes::delete(note.id, &tokens.access_token)
+        .await
+        .expect("Note deletion error.");
+
+    // Corrupt the stats and forget a text length, as left by a release without the counters.
+    sqlx::query("UPDATE stats SET notes = 42, total_chars = 0 WHERE user_id = $1")
+        .bind(user.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    sqlx::query("UPDATE notes SET text_length = NULL WHERE user_id = $1")
+        .bind(user.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+
+    let state = Arc::new(AppState {
+        config: ConfigHandle::new(config.clone()),
+        db_pool: test_db.pool().clone(),
+        redis: redis::open(config).await,
+        token_store: Box::new(MemoryTokenStore::new()),
+        note_cipher: encryption::open(config).expect("Failed to load the master key."),
+        notifiers: Notifiers::new(Box::new(LogMailer), test_db.pool().clone()),
+    });
+    stats_service::rebuild(&state).await.unwrap();
+
+    // The counts are recomputed, the deletions are kept.
+    let user_stats = stats::get(user.id, &tokens.access_token)
+        .await
+        .expect("Stats fetch error.");
+    assert_eq!(user_stats.notes, 1);
+    assert_eq!(user_stats.notes_created, 2);
+    assert_eq!(user_stats.notes_deleted, 1);
+    assert_eq!(user_stats.total_chars, 10);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use serial_test::serial;
+
+use axum_web::domain::models::note_template::{
+    FromTemplateRequest, NoteTemplateRequest, TemplatePrompt,
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    notes, templates, test_app, users,
+};
+
+fn incident_template(global: bool) -> NoteTemplateRequest {
+    NoteTemplateRequest {
+        name: "Incident".to_string(),
+        title: Some("Incident {{date}}: {{service}}".to_string()),
+        text: "Reported by {{user.username}}.\n\nSeverity: {{ severity }}\n\n`{{not a placeholder}}`\n"
+            .to_string(),
+        prompts: vec![
+            TemplatePrompt {
+                name: "service".to_string(),
+                description: Some("The affected service".to_string()),
+                default: None,
+            },
+            TemplatePrompt {
+                name: "severity".to_string(),
+                description: None,
+                default: Some("low".to_string()),
+            },
+        ],
+        global,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn note_from_template_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    // Add a global template.
+    let template = templates::add(&incident_template(true), &tokens.access_token)
+        .await
+        .expect("Template creation error.");
+    assert!(template.user_id.is_none());
+
+    // Any user can create notes from a global template.
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    let request = FromTemplateRequest {
+        values: HashMap::from([("service".to_string(), "billing".to_string())]),
+    };
+    let note = templates::add_note(template.id, &request, &user_tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let date = Utc::now().format("%Y-%m-%d").to_string();
+    assert_eq!(note.user_id, user.id);
+    assert_eq!(
+        note.title.as_deref(),
+        Some(format!("Incident {}: billing", date).as_str())
+    );
+    assert_eq!(
+        note.text,
+        format!(
+            "Reported by {}.\n\nSeverity: low\n\n`{{{{not a placeholder}}}}`\n",
+            user.username
+        )
+    );
+
+    let stored = notes::get(note.id, &tokens.access_token)
+        .await
+        .expect("Note fetch error.");
+    assert_eq!(stored.text, note.text);
+
+    // Prompts without a default need a value, unknown prompts are rejected.
+    let result = templates::add_note(
+        template.id,
+        &FromTemplateRequest::default(),
+        &user_tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let mut request = request.clone();
+    request
+        .values
+        .insert("owner".to_string(), "nobody".to_string());
+    let result = templates::add_note(template.id, &request, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn template_access_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let global = templates::add(&incident_template(true), &tokens.access_token)
+        .await
+        .expect("Template creation error.");
+
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    // Only admins manage global templates.
+    let result = templates::add(&incident_template(true), &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = templates::delete(global.id, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // Unknown placeholders are rejected.
+    let mut request = incident_template(false);
+    request.text = "Hello {{user.name}}".to_string();
+    let result = templates::add(&request, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Users manage their own templates.
+    let mut request = incident_template(false);
+    request.name = "Meeting".to_string();
+    let own = templates::add(&request, &user_tokens.access_token)
+        .await
+        .expect("Template creation error.");
+    assert_eq!(own.user_id, Some(user.id));
+
+    request.text = "Attendees: {{user.username}}".to_string();
+    let own = templates::update(own.id, &request, &user_tokens.access_token)
+        .await
+        .expect("Template update error.");
+    assert_eq!(own.text, "Attendees: {{user.username}}");
+
+    // Users see their own and the global templates.
+    let user_templates = templates::list(&user_tokens.access_token)
+        .await
+        .expect("Templates fetch error.");
+    assert_eq!(user_templates.len(), 2);
+
+    let admin_templates = templates::list(&tokens.access_token)
+        .await
+        .expect("Templates fetch error.");
+    assert_eq!(admin_templates, vec![global.clone()]);
+
+    // Personal templates are private, admins can manage any.
+    let other = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let other_tokens = auth::login(&other.username, &other.password_hash)
+        .await
+        .expect("Login error.");
+    let result = templates::get(own.id, &other_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    templates::get(global.id, &other_tokens.access_token)
+        .await
+        .expect("Template fetch error.");
+
+    templates::delete(own.id, &tokens.access_token)
+        .await
+        .expect("Template deletion error.");
+    let result = templates::get(own.id, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -0,0 +1,177 @@
+use std::{sync::Arc, time::Duration};
+
+use ::redis::AsyncCommands;
+use reqwest::StatusCode;
+use serial_test::serial;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use axum_web::{
+    api::{APIError, APIErrorCode},
+    application::{
+        config::ConfigHandle,
+        constants::*,
+        security::{auth::AuthError, encryption, jwt::AccessClaims},
+        service::{health_service, notification_service::Notifiers, token_service},
+        state::{AppState, SharedState},
+    },
+    domain::models::health::HealthStatus,
+    infrastructure::{database::DatabasePool, mail::LogMailer, redis, token_store},
+};
+
+pub mod common;
+use common::{helpers, test_app};
+
+async fn build_state(redis_port: u16, db_pool: &DatabasePool) -> SharedState {
+    let mut config = helpers::config().clone();
+    config.redis_port = redis_port;
+    let redis = redis::open(&config).await;
+    Arc::new(AppState {
+        token_store: token_store::open(&config, &redis, db_pool),
+        redis,
+        note_cipher: encryption::open(&config).expect("Failed to load the master key."),
+        config: ConfigHandle::new(config),
+        db_pool: db_pool.clone(),
+        notifiers: Notifiers::new(Box::new(LogMailer), db_pool.clone()),
+    })
+}
+
+fn access_claims() -> AccessClaims {
+    let now = chrono::Utc::now().timestamp() as usize;
+    AccessClaims {
+        sub: Uuid::new_v4().to_string(),
+        jti: Uuid::new_v4().to_string(),
+        iat: now - 10,
+        exp: now + 3600,
+        typ: 0,
+        roles: "user".to_string(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn redis_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let config = helpers::config();
+
+    // The revocation checks run concurrently on the shared connection.
+    let state = build_state(config.redis_port, test_db.pool()).await;
+    // The global revocation of the other tests would revoke the claims.
+    let mut connection = state.redis.clone();
+    let _: () = connection
+        .del(JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY)
+        .await
+        .unwrap();
+    let claims: Vec<AccessClaims> = (0..32).map(|_| access_claims()).collect();
+    let checks = claims
+        .iter()
+        .map(|claims| token_service::is_revoked(claims, &state));
+    for revoked in futures::future::join_all(checks).await {
+        assert!(!revoked.unwrap());
+    }
+    token_service::revoke_user_tokens(&claims[0].sub, &state)
+        .await
+        .unwrap();
+    assert!(token_service::is_revoked(&claims[0], &state).await.unwrap());
+    assert!(!token_service::is_revoked(&claims[1], &state).await.unwrap());
+
+    // The revocation keys are in the same Redis Cluster slot.
+    let token_key = format!("{}.{}", JWT_REDIS_REVOKED_TOKEN_KEY_PREFIX, claims[0].jti);
+    let slot = ::redis::cluster_routing::get_slot(token_key.as_bytes());
+    for key in [
+        JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY,
+        JWT_REDIS_REVOKE_USER_BEFORE_KEY,
+        JWT_REDIS_REVOKED_TOKENS_KEY,
+    ] {
+        assert_eq!(::redis::cluster_routing::get_slot(key.as_bytes()), slot);
+    }
+
+    // The revoked tokens expire with the tokens.
+    state
+        .token_store
+        .revoke_tokens(&[&claims[0].jti], claims[0].exp)
+        .await
+        .unwrap();
+    let ttl: i64 = connection.ttl(&token_key).await.unwrap();
+    assert!(ttl > 3500 && ttl <= 3600);
+
+    // The tokens revoked in the hash under the key without hash tag are moved to their keys.
+    let legacy_claims = access_claims();
+    let expired_claims = AccessClaims {
+        exp: legacy_claims.iat,
+        ..access_claims()
+    };
+    let _: () = connection.del(JWT_REDIS_REVOKED_TOKENS_KEY).await.unwrap();
+    for claims in [&legacy_claims, &expired_claims] {
+        let _: () = connection
+            .hset("jwt.revoked.tokens", &claims.jti, claims.exp)
+            .await
+            .unwrap();
+    }
+    let _: () = connection
+        .hset("jwt.revoked.tokens", "invalid", "never")
+        .await
+        .unwrap();
+    assert!(
+        !token_service::is_revoked(&legacy_claims, &state)
+            .await
+            .unwrap()
+    );
+    // The key is renamed and its two tokens moved, the token with an invalid expiration is skipped.
+    assert_eq!(state.token_store.migrate().await.unwrap(), 3);
+    assert!(
+        token_service::is_revoked(&legacy_claims, &state)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !token_service::is_revoked(&expired_claims, &state)
+            .await
+            .unwrap()
+    );
+    let hash_exists: bool = connection
+        .exists(JWT_REDIS_REVOKED_TOKENS_KEY)
+        .await
+        .unwrap();
+    assert!(!hash_exists);
+    assert_eq!(state.token_store.migrate().await.unwrap(), 0);
+
+    // A key without hash tag is kept when the key with the hash tag exists.
+    let _: () = connection
+        .hset("jwt.revoke.user.before", &claims[1].sub, claims[1].iat)
+        .await
+        .unwrap();
+    assert_eq!(state.token_store.migrate().await.unwrap(), 0);
+    let legacy_exists: bool = connection.exists("jwt.revoke.user.before").await.unwrap();
+    assert!(legacy_exists);
+    assert!(!token_service::is_revoked(&claims[1], &state).await.unwrap());
+    let _: () = connection.del("jwt.revoke.user.before").await.unwrap();
+
+    // The service starts while Redis is unreachable.
+    let started = Instant::now();
+    let state = build_state(1, test_db.pool()).await;
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // The outage is reported as an unavailable Redis, without waiting for a connection.
+    let started = Instant::now();
+    let error = token_service::is_revoked(&access_claims(), &state)
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_millis(REDIS_CONNECTION_TIMEOUT_MILLIS));
+    let api_error = APIError::from(AuthError::from(error));
+    assert_eq!(api_error.status, StatusCode::SERVICE_UNAVAILABLE.as_u16());
+    assert_eq!(
+        api_error.errors[0].code,
+        Some(APIErrorCode::RedisError.to_string())
+    );
+
+    // Redis checks the revoked tokens, the service is not ready.
+    let readiness = health_service::readiness(&state).await;
+    assert_eq!(readiness.status, HealthStatus::Down);
+    assert_eq!(readiness.redis.status, HealthStatus::Down);
+    assert!(readiness.redis.error.is_some());
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
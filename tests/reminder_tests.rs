@@ -0,0 +1,358 @@
+use std::time::Duration;
+
+use axum::{Json, Router, extract::State, routing::post};
+use chrono::{TimeDelta, Utc};
+use reqwest::StatusCode;
+use serial_test::serial;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use axum_web::{
+    application::security::jwt::{self, AccessClaims},
+    domain::models::reminder::{
+        ReminderChannel, ReminderNotification, ReminderRequest, ReminderStatus, SnoozeRequest,
+    },
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, notes, reminders, test_app,
+};
+
+const NOTE_TEXT: &str = "# Call the bank\n\nAsk about the fees.";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Starts a webhook receiver on a random port and returns its URL.
+async fn start_webhook_receiver() -> (String, mpsc::UnboundedReceiver<ReminderNotification>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(sender): State<mpsc::UnboundedSender<ReminderNotification>>,
+                 Json(notification): Json<ReminderNotification>| async move {
+                    sender.send(notification).unwrap();
+                },
+            ),
+        )
+        .with_state(sender);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/hook", addr), receiver)
+}
+
+// Skips the keep-alive comments until the next reminder event.
+async fn next_event(events: &mut reqwest::Response) -> String {
+    tokio::time::timeout(DELIVERY_TIMEOUT, async {
+        loop {
+            let chunk = events.chunk().await.unwrap().unwrap();
+            let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+            if chunk.contains("event: reminder") {
+                return chunk;
+            }
+        }
+    })
+    .await
+    .expect("Event delivery timeout.")
+}
+
+#[tokio::test]
+#[serial]
+async fn reminder_webhook_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let note = notes::add(notes::test_note(user_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // Add a due reminder repeating every minute, twice.
+    let (webhook_url, mut receiver) = start_webhook_receiver().await;
+    let due_at = Utc::now().naive_utc() - TimeDelta::seconds(1);
+    let request = ReminderRequest {
+        due_at,
+        rrule: Some("FREQ=MINUTELY;COUNT=2".to_string()),
+        channel: ReminderChannel::Webhook,
+        webhook_url: Some(webhook_url),
+    };
+    let reminder = reminders::add(note.id, &request, &tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+    assert_eq!(reminder.status, ReminderStatus::Pending);
+
+    // The scheduler posts the notification to the webhook.
+    let notification = tokio::time::timeout(DELIVERY_TIMEOUT, receiver.recv())
+        .await
+        .expect("Webhook delivery timeout.")
+        .unwrap();
+    assert_eq!(notification.reminder_id, reminder.id);
+    assert_eq!(notification.note_id, note.id);
+    assert_eq!(notification.title.as_deref(), Some("Call the bank"));
+    assert_eq!(notification.occurrence, 1);
+
+    // The reminder moves to its second occurrence.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let starts_at = reminder.starts_at;
+    let reminder = reminders::get(reminder.id, &tokens.access_token)
+        .await
+        .expect("Reminder fetch error.");
+    assert_eq!(reminder.status, ReminderStatus::Pending);
+    assert_eq!(reminder.occurrences, 1);
+    assert_eq!(reminder.due_at, starts_at + TimeDelta::minutes(1));
+
+    let note_reminders = reminders::list_by_note(note.id, &tokens.access_token)
+        .await
+        .expect("Reminders fetch error.");
+    assert_eq!(note_reminders, vec![reminder]);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn reminder_failed_delivery_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let note = notes::add(notes::test_note(user_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // A reminder failing its delivery does not hold back the others of its batch.
+    let due_at = Utc::now().naive_utc() - TimeDelta::seconds(2);
+    let failing = ReminderRequest {
+        due_at,
+        rrule: None,
+        channel: ReminderChannel::Webhook,
+        webhook_url: Some("http://127.0.0.1:9/hook".to_string()),
+    };
+    let failing = reminders::add(note.id, &failing, &tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+    let (webhook_url, mut receiver) = start_webhook_receiver().await;
+    let request = ReminderRequest {
+        due_at: due_at + TimeDelta::seconds(1),
+        rrule: None,
+        channel: ReminderChannel::Webhook,
+        webhook_url: Some(webhook_url),
+    };
+    let reminder = reminders::add(note.id, &request, &tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+
+    let notification = tokio::time::timeout(DELIVERY_TIMEOUT, receiver.recv())
+        .await
+        .expect("Webhook delivery timeout.")
+        .unwrap();
+    assert_eq!(notification.reminder_id, reminder.id);
+
+    // The failure is recorded on the failing reminder, retried later.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let failing = reminders::get(failing.id, &tokens.access_token)
+        .await
+        .expect("Reminder fetch error.");
+    assert_eq!(failing.status, ReminderStatus::Pending);
+    assert_eq!(failing.attempts, 1);
+    assert!(failing.last_error.is_some());
+    assert!(failing.due_at > Utc::now().naive_utc());
+    assert!(failing.locked_until.is_none());
+    let reminder = reminders::get(reminder.id, &tokens.access_token)
+        .await
+        .expect("Reminder fetch error.");
+    assert_eq!(reminder.status, ReminderStatus::Fired);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn reminder_events_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let note = notes::add(notes::test_note(user_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // Listen to the event stream, then add a due reminder.
+    let mut events = reminders::events(&tokens.access_token)
+        .await
+        .expect("Event stream error.");
+    let request = ReminderRequest {
+        due_at: Utc::now().naive_utc(),
+        rrule: None,
+        channel: ReminderChannel::Sse,
+        webhook_url: None,
+    };
+    let reminder = reminders::add(note.id, &request, &tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+
+    let event = next_event(&mut events).await;
+    assert!(event.contains(&reminder.id.to_string()));
+
+    // A one-off reminder is done once fired.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let reminder = reminders::get(reminder.id, &tokens.access_token)
+        .await
+        .expect("Reminder fetch error.");
+    assert_eq!(reminder.status, ReminderStatus::Fired);
+    assert_eq!(reminder.occurrences, 1);
+
+    // The reminders fired by the other instances are pushed to the stream as well.
+    let notification = ReminderNotification {
+        reminder_id: Uuid::new_v4(),
+        note_id: note.id,
+        user_id,
+        title: None,
+        due_at: Utc::now().naive_utc(),
+        occurrence: 1,
+    };
+    sqlx::query("SELECT pg_notify('reminder_events', $1)")
+        .bind(serde_json::to_string(&notification).unwrap())
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    let event = next_event(&mut events).await;
+    assert!(event.contains(&notification.reminder_id.to_string()));
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn reminder_snooze_dismiss_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    let config = helpers::config();
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let access_claims = jwt::decode_token::<AccessClaims>(&tokens.access_token, config).unwrap();
+    let user_id: Uuid = access_claims.sub.parse().unwrap();
+
+    let note = notes::add(notes::test_note(user_id, NOTE_TEXT), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+
+    // Invalid recurrence rules and webhooks without a URL are rejected.
+    let mut request = ReminderRequest {
+        due_at: Utc::now().naive_utc() + TimeDelta::hours(1),
+        rrule: Some("FREQ=SOMETIMES".to_string()),
+        channel: ReminderChannel::Email,
+        webhook_url: None,
+    };
+    let result = reminders::add(note.id, &request, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    request.rrule = None;
+    request.channel = ReminderChannel::Webhook;
+    let result = reminders::add(note.id, &request, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // The webhooks can not target the internal addresses, unless their host is allowed.
+    for webhook_url in [
+        "ftp://127.0.0.1/hook",
+        "http://localhost:8080/hook",
+        "http://10.0.0.1/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/hook",
+        "http://[::ffff:192.168.0.1]/hook",
+    ] {
+        request.webhook_url = Some(webhook_url.to_string());
+        let result = reminders::add(note.id, &request, &tokens.access_token).await;
+        assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    request.webhook_url = None;
+    request.channel = ReminderChannel::Email;
+    let reminder = reminders::add(note.id, &request, &tokens.access_token)
+        .await
+        .expect("Reminder creation error.");
+
+    // Snooze for the given minutes.
+    let before = Utc::now().naive_utc();
+    let snooze = SnoozeRequest {
+        minutes: Some(30),
+        until: None,
+    };
+    let snoozed = reminders::snooze(reminder.id, &snooze, &tokens.access_token)
+        .await
+        .expect("Reminder snooze error.");
+    assert!(snoozed.due_at >= before + TimeDelta::minutes(30));
+    assert!(snoozed.due_at < before + TimeDelta::minutes(31));
+
+    // Snoozing to the past is rejected.
+    let snooze = SnoozeRequest {
+        minutes: None,
+        until: Some(before - TimeDelta::minutes(1)),
+    };
+    let result = reminders::snooze(reminder.id, &snooze, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // A dismissed reminder is not fired and cannot be snoozed anymore.
+    let dismissed = reminders::dismiss(reminder.id, &tokens.access_token)
+        .await
+        .expect("Reminder dismiss error.");
+    assert_eq!(dismissed.status, ReminderStatus::Dismissed);
+
+    let result =
+        reminders::snooze(reminder.id, &SnoozeRequest::default(), &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let all_reminders = reminders::list(&tokens.access_token)
+        .await
+        .expect("Reminders fetch error.");
+    assert_eq!(all_reminders.len(), 1);
+
+    // Delete the reminder.
+    reminders::delete(reminder.id, &tokens.access_token)
+        .await
+        .expect("Reminder deletion error.");
+    let result = reminders::get(reminder.id, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
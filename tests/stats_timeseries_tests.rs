@@ -0,0 +1,133 @@
+use chrono::{Datelike, TimeDelta, Utc};
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::domain::models::stats::Timeseries;
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    notes, stats, test_app, users,
+};
+
+fn values(timeseries: &Timeseries) -> Vec<i64> {
+    timeseries.points.iter().map(|p| p.value).collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn stats_timeseries_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+
+    let note = notes::add(notes::test_note(user.id, "first"), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    notes::add(notes::test_note(user.id, "second"), &tokens.access_token)
+        .await
+        .expect("Note creation error.");
+    let mut updated = note.clone();
+    updated.text = "first, edited".to_string();
+    notes::update(updated, &tokens.access_token)
+        .await
+        .expect("Note update error.");
+
+    // The days without activity are filled with zeros.
+    let today = Utc::now().date_naive();
+    let from = today - TimeDelta::days(2);
+    let range = format!("from={}&to={}", from, today);
+    let created = stats::timeseries(
+        &format!("metric=notes_created&{}&user_id={}", range, user.id),
+        &user_tokens.access_token,
+    )
+    .await
+    .expect("Time series fetch error.");
+    assert_eq!(values(&created), vec![0, 0, 2]);
+    let buckets: Vec<_> = created.points.iter().map(|p| p.bucket).collect();
+    assert_eq!(buckets, vec![from, from + TimeDelta::days(1), today]);
+
+    let edited = stats::timeseries(
+        &format!("metric=notes_edited&{}&user_id={}", range, user.id),
+        &user_tokens.access_token,
+    )
+    .await
+    .expect("Time series fetch error.");
+    assert_eq!(values(&edited), vec![0, 0, 1]);
+
+    let logins = stats::timeseries(
+        &format!("metric=logins&{}&user_id={}", range, user.id),
+        &user_tokens.access_token,
+    )
+    .await
+    .expect("Time series fetch error.");
+    assert_eq!(values(&logins), vec![0, 0, 1]);
+
+    // The system-wide series are admin-only.
+    let result = stats::timeseries(
+        &format!("metric=active_users&{}", range),
+        &user_tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = stats::timeseries(
+        &format!("metric=logins&{}&user_id={}", range, Uuid::new_v4()),
+        &user_tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // The admin and the user were active today.
+    let active_users = stats::timeseries(
+        &format!("metric=active_users&{}", range),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Time series fetch error.");
+    assert_eq!(values(&active_users), vec![0, 0, 2]);
+
+    // Weekly buckets start on Monday.
+    let weekly = stats::timeseries(
+        &format!(
+            "metric=notes_created&from={}&to={}&bucket=week",
+            today, today
+        ),
+        &tokens.access_token,
+    )
+    .await
+    .expect("Time series fetch error.");
+    let monday = today - TimeDelta::days(i64::from(today.weekday().num_days_from_monday()));
+    assert_eq!(weekly.points.len(), 1);
+    assert_eq!(weekly.points[0].bucket, monday);
+    assert_eq!(weekly.points[0].value, 2);
+
+    // Invalid ranges are rejected.
+    let result = stats::timeseries(
+        &format!("metric=logins&from={}&to={}", today, from),
+        &tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+    let result = stats::timeseries(
+        &format!("metric=logins&from=2020-01-01&to={}", today),
+        &tokens.access_token,
+    )
+    .await;
+    assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
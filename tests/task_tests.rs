@@ -0,0 +1,193 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{TimeDelta, Utc};
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::{
+    application::{
+        config::ConfigHandle,
+        repository::{note_import_repo, task_repo},
+        security::encryption,
+        service::{archive_service, notification_service::Notifiers, task_service},
+        state::{AppState, SharedState},
+    },
+    domain::models::{
+        note_import::NoteImportStatus,
+        task::{Task, TaskPayload, TaskStatus},
+    },
+    infrastructure::{database::DatabasePool, mail::LogMailer, redis, token_store},
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, tasks, test_app, users,
+};
+
+const TASK_TIMEOUT: Duration = Duration::from_secs(15);
+
+async fn build_state(db_pool: &DatabasePool) -> SharedState {
+    let config = helpers::config().clone();
+    let redis = redis::open(&config).await;
+    Arc::new(AppState {
+        token_store: token_store::open(&config, &redis, db_pool),
+        redis,
+        note_cipher: encryption::open(&config).expect("Failed to load the master key."),
+        config: ConfigHandle::new(config),
+        db_pool: db_pool.clone(),
+        notifiers: Notifiers::new(Box::new(LogMailer), db_pool.clone()),
+    })
+}
+
+// Queues the import of an empty archive of the admin, the data key of the tenant needs the user.
+async fn enqueue_import(state: &SharedState) -> Task {
+    let user_id: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE username = $1")
+        .bind(TEST_ADMIN_USERNAME)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+    let note_import = archive_service::enqueue_import(user_id, vec![], state)
+        .await
+        .unwrap();
+    let task_id: Uuid = sqlx::query_scalar("SELECT id FROM tasks WHERE payload->>'import_id' = $1")
+        .bind(note_import.id.to_string())
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+    task_repo::get_by_id(task_id, state).await.unwrap()
+}
+
+// Waits for the workers of the test service to finish the task.
+async fn finished(task: &Task, state: &SharedState) -> Task {
+    tokio::time::timeout(TASK_TIMEOUT, async {
+        loop {
+            let task = task_repo::get_by_id(task.id, state).await.unwrap();
+            if matches!(task.status, TaskStatus::Succeeded | TaskStatus::Dead) {
+                return task;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Task timeout.")
+}
+
+#[tokio::test]
+#[serial]
+async fn task_queue_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let config = helpers::config();
+    let state = build_state(test_db.pool()).await;
+
+    // Every task is run once by the concurrent workers.
+    let mut queued = Vec::new();
+    for _ in 0..10 {
+        queued.push(enqueue_import(&state).await);
+    }
+    for task in &queued {
+        let task = finished(task, &state).await;
+        assert_eq!(task.status, TaskStatus::Succeeded);
+        assert_eq!(task.attempts, 1);
+        let TaskPayload::ImportNotes { import_id, .. } = task.payload.0 else {
+            panic!("Unexpected task payload.");
+        };
+        let note_import = note_import_repo::get_by_id(import_id, &state)
+            .await
+            .unwrap();
+        assert_eq!(note_import.status, NoteImportStatus::Completed);
+
+        // The archive of the import is deleted once the import finished.
+        let archive = note_import_repo::get_archive(import_id, &state)
+            .await
+            .unwrap();
+        assert!(archive.is_none());
+    }
+
+    // A failing task is retried with a backoff, then dead.
+    let payload = TaskPayload::ImportNotes {
+        import_id: Uuid::new_v4(),
+    };
+    let failing = task_service::enqueue(payload, &state).await.unwrap();
+    let dead = finished(&failing, &state).await;
+    assert_eq!(dead.status, TaskStatus::Dead);
+    assert_eq!(dead.attempts, config.task_max_attempts);
+    assert!(dead.last_error.is_some());
+    assert!(dead.updated_at.unwrap() - dead.created_at.unwrap() >= TimeDelta::seconds(2));
+
+    // The dead tasks are listed and queued again by the admins.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+    let dead_tasks = tasks::list("status=dead", &tokens.access_token)
+        .await
+        .unwrap();
+    assert!(dead_tasks.iter().any(|task| task.id == dead.id));
+    let retried = tasks::retry(dead.id, &tokens.access_token).await.unwrap();
+    assert_eq!(retried.status, TaskStatus::Queued);
+    assert_eq!(retried.attempts, 0);
+    let result = tasks::retry(queued[0].id, &tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // The tasks are for the admins only.
+    let user = users::add(users::test_user(), &tokens.access_token)
+        .await
+        .expect("User creation error.");
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+    let result = tasks::list("", &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = tasks::retry(dead.id, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // A task whose worker stopped is run again after its visibility timeout.
+    let abandoned = enqueue_import(&state).await;
+    sqlx::query("UPDATE tasks SET status = $1, attempts = 1, locked_until = $2 WHERE id = $3")
+        .bind(TaskStatus::Running)
+        .bind(Utc::now().naive_utc() - TimeDelta::seconds(1))
+        .bind(abandoned.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    let task = finished(&abandoned, &state).await;
+    assert_eq!(task.status, TaskStatus::Succeeded);
+    assert_eq!(task.attempts, 2);
+
+    // Unless it has used all its attempts.
+    let abandoned = enqueue_import(&state).await;
+    sqlx::query("UPDATE tasks SET status = $1, attempts = $2, locked_until = $3 WHERE id = $4")
+        .bind(TaskStatus::Running)
+        .bind(config.task_max_attempts)
+        .bind(Utc::now().naive_utc() - TimeDelta::seconds(1))
+        .bind(abandoned.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    let task = finished(&abandoned, &state).await;
+    assert_eq!(task.status, TaskStatus::Dead);
+    assert_eq!(
+        task.last_error.as_deref(),
+        Some("visibility timeout exceeded")
+    );
+
+    // A running task is not taken by another worker.
+    let running = enqueue_import(&state).await;
+    sqlx::query("UPDATE tasks SET status = $1, attempts = 1, locked_until = $2 WHERE id = $3")
+        .bind(TaskStatus::Running)
+        .bind(Utc::now().naive_utc() + TimeDelta::hours(1))
+        .bind(running.id)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let task = task_repo::get_by_id(running.id, &state).await.unwrap();
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.attempts, 1);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
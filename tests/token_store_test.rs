@@ -0,0 +1,110 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::infrastructure::{
+    redis,
+    token_store::{MemoryTokenStore, PostgresTokenStore, RedisTokenStore, Revocation, TokenStore},
+};
+
+pub mod common;
+use common::{helpers, test_app};
+
+// The same scenario runs against every backend.
+async fn check_store(store: &dyn TokenStore) {
+    let user_id = Uuid::new_v4().to_string();
+    let other_user_id = Uuid::new_v4().to_string();
+    let jti = Uuid::new_v4().to_string();
+    let paired_jti = Uuid::new_v4().to_string();
+    let expired_jti = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp() as usize;
+
+    let revocation = store.revocation(&user_id, &jti).await.unwrap();
+    assert_eq!(revocation.user_before, None);
+    assert!(!revocation.token_revoked);
+
+    // The user revocation only applies to the user.
+    store.revoke_user(&user_id, now).await.unwrap();
+    store.revoke_user(&user_id, now + 1).await.unwrap();
+    assert_eq!(
+        store.revocation(&user_id, &jti).await.unwrap().user_before,
+        Some(now + 1)
+    );
+    assert_eq!(
+        store
+            .revocation(&other_user_id, &jti)
+            .await
+            .unwrap()
+            .user_before,
+        None
+    );
+
+    // The tokens are revoked until their expiration.
+    let count = store.revoked_tokens_count().await.unwrap();
+    store
+        .revoke_tokens(&[&jti, &paired_jti], now + 60)
+        .await
+        .unwrap();
+    store
+        .revoke_tokens(&[&expired_jti], now - 60)
+        .await
+        .unwrap();
+    assert!(
+        store
+            .revocation(&user_id, &jti)
+            .await
+            .unwrap()
+            .token_revoked
+    );
+    assert!(
+        store
+            .revocation(&other_user_id, &paired_jti)
+            .await
+            .unwrap()
+            .token_revoked
+    );
+
+    // The expired tokens are deleted, by the cleanup or by the store itself.
+    store.cleanup_expired(now).await.unwrap();
+    assert!(
+        !store
+            .revocation(&user_id, &expired_jti)
+            .await
+            .unwrap()
+            .token_revoked
+    );
+    assert!(
+        store
+            .revocation(&user_id, &jti)
+            .await
+            .unwrap()
+            .token_revoked
+    );
+    assert_eq!(store.revoked_tokens_count().await.unwrap(), count + 2);
+
+    // The global revocation applies to every user.
+    store.revoke_global(now).await.unwrap();
+    assert_eq!(
+        store.revocation(&other_user_id, &jti).await.unwrap(),
+        Revocation {
+            global_before: Some(now),
+            user_before: None,
+            token_revoked: true,
+        }
+    );
+    store.revoke_global(0).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn token_store_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let config = helpers::config();
+
+    check_store(&MemoryTokenStore::new()).await;
+    check_store(&PostgresTokenStore::new(test_db.pool().clone())).await;
+    check_store(&RedisTokenStore::new(redis::open(config).await)).await;
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
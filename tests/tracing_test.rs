@@ -0,0 +1,70 @@
+use reqwest::StatusCode;
+use serial_test::serial;
+use uuid::Uuid;
+
+use axum_web::api::APIError;
+
+pub mod common;
+use common::{
+    auth,
+    constants::{API_PATH_USERS, API_V1, TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    helpers, test_app,
+};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+#[tokio::test]
+#[serial]
+async fn tracing_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+
+    // Login as an admin.
+    let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.");
+    let authorization = format!("Bearer {}", tokens.access_token);
+    let url = helpers::build_url(API_V1, API_PATH_USERS, &Uuid::new_v4().to_string());
+
+    // The request continues the trace of the `traceparent` header.
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Authorization", &authorization)
+        .header("traceparent", TRACEPARENT)
+        .header("tracestate", "vendor=value")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let traceparent = response.headers()["traceparent"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    assert!(traceparent.starts_with(&format!("00-{}-", TRACE_ID)));
+    assert_ne!(traceparent, TRACEPARENT);
+    assert_eq!(response.headers()["tracestate"], "vendor=value");
+
+    // The error carries the trace id of the request.
+    let api_error: APIError = response.json().await.unwrap();
+    assert_eq!(api_error.errors[0].trace_id.as_deref(), Some(TRACE_ID));
+
+    // Without a `traceparent` header the request starts a new trace.
+    let response = reqwest::Client::new()
+        .get(url.as_str())
+        .header("Authorization", &authorization)
+        .send()
+        .await
+        .unwrap();
+    let traceparent = response.headers()["traceparent"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let trace_id = traceparent.split('-').nth(1).unwrap().to_owned();
+    assert_ne!(trace_id, TRACE_ID);
+    let api_error: APIError = response.json().await.unwrap();
+    assert_eq!(api_error.errors[0].trace_id, Some(trace_id));
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}
@@ -1,11 +1,7 @@
-use chrono::Utc;
 use serial_test::serial;
 use uuid::Uuid;
 
-use axum_web::{
-    application::security::jwt::{self, AccessClaims},
-    domain::models::user::User,
-};
+use axum_web::application::security::jwt::{self, AccessClaims};
 use reqwest::StatusCode;
 
 pub mod common;
@@ -16,21 +12,6 @@ use common::{
     test_app, users,
 };
 
-fn test_user() -> User {
-    let username = format!("test-{}", Utc::now().timestamp() as usize);
-    User {
-        id: Uuid::new_v4(),
-        username: username.clone(),
-        email: format!("{}@email.com", username),
-        password_hash: "xyz123".to_string(),
-        password_salt: "xyz123".to_string(),
-        active: true,
-        roles: "guest".to_string(),
-        created_at: None,
-        updated_at: None,
-    }
-}
-
 #[tokio::test]
 #[serial]
 async fn user_unauthorized_test() {
@@ -40,7 +21,7 @@ async fn user_unauthorized_test() {
     // Try unauthorized access to user handlers.
     let wrong_access_token = "xyz";
 
-    let user = test_user();
+    let user = users::test_user();
 
     let result = users::get(user.id, wrong_access_token).await;
     assert_api_error_status!(result, StatusCode::UNAUTHORIZED);
@@ -125,7 +106,7 @@ async fn add_get_update_delete_user_test() {
     // Start API server.
     let test_db = test_app::run().await;
 
-    let mut user = test_user();
+    let mut user = users::test_user();
 
     // Login as an admin.
     let tokens = auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
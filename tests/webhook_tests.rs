@@ -0,0 +1,273 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{Router, body::Bytes, extract::State, http::HeaderMap, routing::post};
+use reqwest::StatusCode;
+use serial_test::serial;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use axum_web::{
+    application::service::webhook_service,
+    domain::models::{
+        event::EventKind,
+        webhook::{WebhookDeliveryStatus, WebhookPayload, WebhookRequest},
+    },
+};
+
+pub mod common;
+use common::{
+    auth,
+    constants::{TEST_ADMIN_PASSWORD_HASH, TEST_ADMIN_USERNAME},
+    notes, test_app, users, webhooks,
+};
+
+const NOTE_TEXT: &str = "# Release notes\n\nWebhooks are signed.";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const SECRET: &str = "a-secret-of-the-receiver";
+
+// A request received by the webhook receiver.
+struct Received {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> &str {
+        self.headers.get(name).unwrap().to_str().unwrap()
+    }
+
+    fn payload(&self) -> WebhookPayload {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+#[derive(Clone)]
+struct Receiver {
+    sender: mpsc::UnboundedSender<Received>,
+    failures: Arc<AtomicUsize>,
+}
+
+// Starts a webhook receiver on a random port and returns its URL. The receiver answers the
+// requests with a `500` status while it has failures left.
+async fn start_webhook_receiver() -> (String, mpsc::UnboundedReceiver<Received>, Arc<AtomicUsize>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let failures = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes| async move {
+                    receiver.sender.send(Received { headers, body }).unwrap();
+                    let failed = receiver
+                        .failures
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |f| f.checked_sub(1))
+                        .is_ok();
+                    if failed {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    } else {
+                        StatusCode::OK
+                    }
+                },
+            ),
+        )
+        .with_state(Receiver {
+            sender,
+            failures: Arc::clone(&failures),
+        });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/hook", addr), receiver, failures)
+}
+
+async fn receive(receiver: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+    tokio::time::timeout(DELIVERY_TIMEOUT, receiver.recv())
+        .await
+        .expect("Webhook delivery timeout.")
+        .unwrap()
+}
+
+async fn admin_token() -> String {
+    auth::login(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD_HASH)
+        .await
+        .expect("Login error.")
+        .access_token
+}
+
+#[tokio::test]
+#[serial]
+async fn webhook_test() {
+    // Start API server.
+    let test_db = test_app::run().await;
+    let access_token = admin_token().await;
+
+    // Subscribe to the note and user creations, the secret is never returned.
+    let (url, mut receiver, failures) = start_webhook_receiver().await;
+    let request = WebhookRequest {
+        url: url.clone(),
+        secret: SECRET.to_string(),
+        events: vec![EventKind::NoteCreated, EventKind::UserCreated],
+        active: true,
+    };
+    let webhook = webhooks::add(&request, &access_token).await.unwrap();
+    assert_eq!(webhook.url, url);
+    assert!(webhook.secret.is_empty());
+    assert_eq!(webhook.events, request.events);
+    let listed = webhooks::list(&access_token).await.unwrap();
+    assert!(listed.iter().any(|w| w.id == webhook.id));
+
+    // The users are sent without their credentials.
+    let user = users::add(users::test_user(), &access_token)
+        .await
+        .expect("User creation error.");
+    let received = receive(&mut receiver).await;
+    assert_eq!(received.header("X-Webhook-Event"), "user.created");
+    let payload = received.payload();
+    assert_eq!(payload.data["username"], user.username);
+    assert!(payload.data.get("password_hash").is_none());
+
+    // The payloads are signed with the secret.
+    let note = notes::add(notes::test_note(user.id, NOTE_TEXT), &access_token)
+        .await
+        .expect("Note creation error.");
+    let received = receive(&mut receiver).await;
+    assert_eq!(received.header("X-Webhook-Event"), "note.created");
+    let timestamp: i64 = received.header("X-Webhook-Timestamp").parse().unwrap();
+    assert_eq!(
+        received.header("X-Webhook-Signature"),
+        webhook_service::sign(SECRET, timestamp, &received.body)
+    );
+    let payload = received.payload();
+    assert_eq!(payload.event, EventKind::NoteCreated);
+    assert_eq!(payload.data["id"], note.id.to_string());
+    assert_eq!(payload.data["user_id"], user.id.to_string());
+    assert!(payload.data.get("text").is_none());
+    // The title of a note encrypted at rest is left out.
+    assert!(payload.data["title"].is_null());
+    let note_delivery_id: Uuid = received.header("X-Webhook-Id").parse().unwrap();
+
+    // The events without subscription are not delivered.
+    notes::update(note, &access_token).await.unwrap();
+
+    // A failed delivery is retried with a backoff.
+    failures.store(1, Ordering::SeqCst);
+    let access_token = admin_token().await;
+    notes::add(notes::test_note(user.id, NOTE_TEXT), &access_token)
+        .await
+        .expect("Note creation error.");
+    let failed = receive(&mut receiver).await;
+    let retried = receive(&mut receiver).await;
+    assert_eq!(failed.header("X-Webhook-Event"), "note.created");
+    assert_eq!(
+        failed.header("X-Webhook-Id"),
+        retried.header("X-Webhook-Id")
+    );
+    assert_eq!(failed.body, retried.body);
+    let retried_id: Uuid = retried.header("X-Webhook-Id").parse().unwrap();
+
+    // The deliveries are logged, the most recent first.
+    let access_token = admin_token().await;
+    let deliveries = tokio::time::timeout(DELIVERY_TIMEOUT, async {
+        loop {
+            let deliveries = webhooks::deliveries(webhook.id, &access_token)
+                .await
+                .unwrap();
+            if deliveries[0].status != WebhookDeliveryStatus::Pending {
+                return deliveries;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Webhook delivery log timeout.");
+    assert_eq!(deliveries.len(), 3);
+    assert_eq!(deliveries[0].id, retried_id);
+    assert_eq!(deliveries[0].status, WebhookDeliveryStatus::Succeeded);
+    assert_eq!(deliveries[0].attempts, 2);
+    assert_eq!(deliveries[0].response_status, Some(200));
+    assert!(deliveries[0].delivered_at.is_some());
+    assert_eq!(deliveries[1].id, note_delivery_id);
+    assert_eq!(deliveries[2].event, EventKind::UserCreated);
+
+    // A delivery is redelivered with the same event.
+    let redelivery = webhooks::redeliver(webhook.id, note_delivery_id, &access_token)
+        .await
+        .unwrap();
+    assert_ne!(redelivery.id, note_delivery_id);
+    assert_eq!(redelivery.event_id, deliveries[1].event_id);
+    let received = receive(&mut receiver).await;
+    assert_eq!(received.header("X-Webhook-Id"), redelivery.id.to_string());
+    assert_eq!(received.payload(), deliveries[1].payload.0);
+    let result = webhooks::redeliver(Uuid::new_v4(), note_delivery_id, &access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // An inactive webhook receives no event.
+    let access_token = admin_token().await;
+    let request = WebhookRequest {
+        active: false,
+        ..request
+    };
+    let updated = webhooks::update(webhook.id, &request, &access_token)
+        .await
+        .unwrap();
+    assert!(!updated.active);
+    notes::add(notes::test_note(user.id, NOTE_TEXT), &access_token)
+        .await
+        .expect("Note creation error.");
+    let received = tokio::time::timeout(Duration::from_secs(2), receiver.recv()).await;
+    assert!(received.is_err());
+
+    // The webhooks are validated.
+    let access_token = admin_token().await;
+    for invalid in [
+        WebhookRequest {
+            url: "ftp://localhost/hook".to_string(),
+            ..request.clone()
+        },
+        WebhookRequest {
+            url: "http://localhost:9/hook".to_string(),
+            ..request.clone()
+        },
+        WebhookRequest {
+            url: "http://10.0.0.1/hook".to_string(),
+            ..request.clone()
+        },
+        WebhookRequest {
+            secret: "short".to_string(),
+            ..request.clone()
+        },
+        WebhookRequest {
+            events: vec![],
+            ..request.clone()
+        },
+    ] {
+        let result = webhooks::add(&invalid, &access_token).await;
+        assert_api_error_status!(result, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // The webhooks are for the admins only.
+    let user_tokens = auth::login(&user.username, &user.password_hash)
+        .await
+        .expect("Login error.");
+    let result = webhooks::list(&user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+    let result = webhooks::add(&request, &user_tokens.access_token).await;
+    assert_api_error_status!(result, StatusCode::FORBIDDEN);
+
+    // A deleted webhook is not found.
+    webhooks::delete(webhook.id, &access_token).await.unwrap();
+    let result = webhooks::get(webhook.id, &access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+    let result = webhooks::delete(webhook.id, &access_token).await;
+    assert_api_error_status!(result, StatusCode::NOT_FOUND);
+
+    // Drop test database.
+    test_db.drop().await.unwrap();
+}